@@ -1,13 +1,71 @@
-use crate::graph::{AABB, Graph, Node, Quadtree};
-use crate::layout::{ForceLayout, ForceParams};
+use crate::graph::algorithms::{GraphStats, louvain, pagerank, shortest_path};
+use crate::graph::spatial::DEFAULT_CAPACITY;
+use crate::graph::{Graph, Node, Quadtree};
+use crate::layout::{
+    DEFAULT_MAX_DISPLACEMENT, ForceLayout, ForceParams, SETTLE_THRESHOLD_PER_NODE, seed_positions,
+};
+use crate::protocol::DecodeError;
 use crate::protocol::decode::Decoder;
 use crate::render::camera::Camera;
+use crate::render::text::DEFAULT_LABEL_THRESHOLD;
+use crate::render::{ColorScale, Frame, Highlight, NodeColoring, edge_lines, visible_nodes};
+use crate::render::{EdgeStyle, EdgeWeightStyle, FrameStyle, NodeSizing, NodeStyle, SizeDomain};
 
+const INITIAL_LAYOUT_SEED: u64 = 42;
+/// Layout frames the quadtree may lag behind by default; see
+/// `set_spatial_rebuild_interval`.
+pub const DEFAULT_SPATIAL_REBUILD_INTERVAL: u32 = 4;
+/// Average distance, in world units, nodes may drift from their indexed
+/// positions before the quadtree is rebuilt early. Well under
+/// `MIN_NODE_RADIUS`, so a stale index still finds what's under the cursor.
+pub const SPATIAL_DRIFT_THRESHOLD: f32 = 1.0;
+
+/// Whether the quadtree should be rebuilt after `frames` moving layout
+/// frames that shifted the average node `drift` world units since the last
+/// rebuild. An `interval` of 0 or 1 rebuilds after every moving frame.
+pub fn should_rebuild_spatial(frames: u32, drift: f32, interval: u32) -> bool {
+    frames > 0 && (frames >= interval || drift >= SPATIAL_DRIFT_THRESHOLD)
+}
+
+/// Movement the quadtree hasn't caught up with yet.
+#[derive(Default)]
+struct SpatialLag {
+    frames: u32,
+    /// Total node movement, summed over the lagging frames.
+    movement: f32,
+}
+
+/// Engine state — graph, layout, camera, spatial index and styling — with
+/// no canvas or browser dependency, so it builds and is tested natively.
+/// The `#[wasm_bindgen]` `BloomEngine` in `lib.rs` pairs one of these with
+/// the renderer and forwards to it.
 pub struct BloomEngine {
     graph: Option<Graph>,
     layout: Option<ForceLayout>,
+    /// Per-step movement cap handed to each new layout.
+    max_displacement: f32,
     camera: Camera,
     quadtree: Option<Quadtree>,
+    spatial_rebuild_interval: u32,
+    spatial_lag: SpatialLag,
+    sizing: NodeSizing,
+    edge_style: EdgeStyle,
+    /// On-screen radius in pixels a node needs before its label is drawn.
+    label_threshold: f64,
+    /// Graph maxima for `sizing`, cached at load so drawing and
+    /// hit-testing can size nodes without scanning them all.
+    size_domain: SizeDomain,
+    /// Louvain partition from the last `detect_communities`, or the one
+    /// the loaded file carried; always matches `Node::community`.
+    /// Replaced whenever a new graph loads.
+    communities: Option<Vec<usize>>,
+    coloring: NodeColoring,
+    /// Fill per node under `coloring`, index-aligned with the graph's
+    /// nodes. Recomputed whenever the ranks, partition or mode change.
+    node_colors: Vec<[u8; 4]>,
+    /// Hovered node and its neighbours, drawn at full opacity while the
+    /// rest of the graph is dimmed.
+    highlight: Option<Highlight>,
     canvas_width: f32,
     canvas_height: f32,
 }
@@ -17,36 +75,55 @@ impl BloomEngine {
         Self {
             graph: None,
             layout: None,
+            max_displacement: DEFAULT_MAX_DISPLACEMENT,
             camera: Camera::new(),
             quadtree: None,
+            spatial_rebuild_interval: DEFAULT_SPATIAL_REBUILD_INTERVAL,
+            spatial_lag: SpatialLag::default(),
+            sizing: NodeSizing::default(),
+            edge_style: EdgeStyle::default(),
+            label_threshold: DEFAULT_LABEL_THRESHOLD,
+            size_domain: SizeDomain::default(),
+            communities: None,
+            coloring: NodeColoring::default(),
+            node_colors: Vec::new(),
+            highlight: None,
             canvas_width: width,
             canvas_height: height,
         }
     }
 
-    pub fn load_graph(&mut self, data: &[u8]) -> Result<(), String> {
+    pub fn load_graph(&mut self, data: &[u8]) -> Result<(), DecodeError> {
         let mut decoder = Decoder::new(data);
         let mut graph = decoder.decode_graph()?;
 
-        // Randomize initial positions with deterministic LCG (seed=42)
+        // Deterministic starting layout so reloads look the same
         let n = graph.node_count();
-        let radius = (n as f32).sqrt() * 10.0;
-        let mut lcg = 42u32;
-        for node in graph.nodes_mut() {
-            lcg = lcg.wrapping_mul(1664525).wrapping_add(1013904223);
-            let rx = (lcg as f32 / u32::MAX as f32) * 2.0 - 1.0;
-            lcg = lcg.wrapping_mul(1664525).wrapping_add(1013904223);
-            let ry = (lcg as f32 / u32::MAX as f32) * 2.0 - 1.0;
-            node.x = rx * radius;
-            node.y = ry * radius;
-        }
-
-        let layout = ForceLayout::new(n, ForceParams::default());
+        let side = (n as f32).sqrt() * 20.0;
+        seed_positions(&mut graph, INITIAL_LAYOUT_SEED, side, side);
+
+        let mut layout = ForceLayout::new(n, ForceParams::default());
+        layout.set_max_displacement(self.max_displacement);
         let quadtree = build_quadtree(&graph);
 
+        self.size_domain = SizeDomain::from_graph(&graph);
+        // Keep the chosen weight mapping but fit it to the new weights
+        if let Some(w) = &mut self.edge_style.weights {
+            *w = EdgeWeightStyle::fitted(&graph, w.alpha, w.width);
+        }
+        // A file that carries a full partition doesn't need Louvain again
+        self.communities = graph
+            .nodes()
+            .iter()
+            .map(|n| n.community.map(|c| c as usize))
+            .collect::<Option<Vec<_>>>()
+            .filter(|c| !c.is_empty());
         self.graph = Some(graph);
         self.layout = Some(layout);
         self.quadtree = Some(quadtree);
+        self.spatial_lag = SpatialLag::default();
+        self.highlight = None;
+        self.refresh_colors();
         self.camera.focus_on(0.0, 0.0, 1.0);
 
         Ok(())
@@ -54,9 +131,88 @@ impl BloomEngine {
 
     pub fn tick(&mut self, dt: f32) {
         if let (Some(graph), Some(layout)) = (&mut self.graph, &mut self.layout) {
-            layout.step(graph);
+            let moved = layout.step(graph);
+            let threshold = SETTLE_THRESHOLD_PER_NODE * graph.node_count() as f32;
+            let settled = layout.is_settled(threshold);
+            self.catch_up_spatial(moved, settled);
+        }
+        self.camera.update(dt);
+    }
+
+    /// Steps the layout until it settles or `max_ms` have passed on the
+    /// `now` clock (milliseconds, e.g. `performance.now()`), and returns
+    /// whether it has settled. Always runs at least one step while
+    /// unsettled, so a zero budget still makes progress. With no graph, or
+    /// an empty one, there is nothing to move and this returns true.
+    pub fn run_layout(&mut self, max_ms: f64, mut now: impl FnMut() -> f64) -> bool {
+        let (Some(graph), Some(layout)) = (&mut self.graph, &mut self.layout) else {
+            return true;
+        };
+        let n = graph.node_count();
+        if n == 0 {
+            return true;
+        }
+        let threshold = SETTLE_THRESHOLD_PER_NODE * n as f32;
+
+        let start = now();
+        let mut moved = 0.0;
+        while !layout.is_settled(threshold) {
+            moved += layout.step(graph);
+            if now() - start >= max_ms {
+                break;
+            }
+        }
+        let settled = layout.is_settled(threshold);
+        self.catch_up_spatial(moved, settled);
+        settled
+    }
+
+    /// How many moving layout frames the quadtree may lag behind before it
+    /// is rebuilt (default `DEFAULT_SPATIAL_REBUILD_INTERVAL`). Rebuilding
+    /// is O(n log n), so skipping frames saves time while the layout
+    /// animates; picking and culling meanwhile see slightly stale
+    /// positions. The index still catches up early once nodes drift
+    /// `SPATIAL_DRIFT_THRESHOLD` on average, and as soon as the layout
+    /// settles. 0 or 1 rebuilds after every moving frame.
+    pub fn set_spatial_rebuild_interval(&mut self, frames: u32) {
+        self.spatial_rebuild_interval = frames;
+    }
+
+    /// Caps how far the layout moves any node per step, in world units
+    /// (default `DEFAULT_MAX_DISPLACEMENT`). Applies to the current layout
+    /// and to those of graphs loaded later. Negative and NaN caps count
+    /// as 0.
+    pub fn set_max_displacement(&mut self, px: f32) {
+        self.max_displacement = px.max(0.0);
+        if let Some(layout) = &mut self.layout {
+            layout.set_max_displacement(px);
+        }
+    }
+
+    /// Records one layout frame that moved nodes `moved` in total and
+    /// rebuilds the quadtree if it has fallen too far behind. The quadtree
+    /// indexes positions, so only movement makes it stale.
+    fn catch_up_spatial(&mut self, moved: f32, settled: bool) {
+        let Some(graph) = &self.graph else {
+            return;
+        };
+        let lag = &mut self.spatial_lag;
+        if moved > 0.0 {
+            lag.frames += 1;
+            lag.movement += moved;
+        }
+        let drift = lag.movement / graph.node_count().max(1) as f32;
+        let due = should_rebuild_spatial(lag.frames, drift, self.spatial_rebuild_interval);
+        // Settling is the last move for a while, so index the final positions
+        if due || (settled && lag.frames > 0) {
             self.quadtree = Some(build_quadtree(graph));
+            self.spatial_lag = SpatialLag::default();
         }
+    }
+
+    /// Advances camera smoothing by `dt` seconds without touching the
+    /// layout.
+    pub fn update_camera(&mut self, dt: f32) {
         self.camera.update(dt);
     }
 
@@ -65,6 +221,11 @@ impl BloomEngine {
         self.canvas_height = height;
     }
 
+    /// The node drawn under the screen point, if any. A node is hit when
+    /// the point lies within its rendered radius under the current sizing; where
+    /// circles overlap the node whose centre is closest wins. The quadtree
+    /// narrows candidates to the largest possible radius first, so this
+    /// stays O(log n).
     pub fn node_at(&self, screen_x: f32, screen_y: f32) -> Option<&Node> {
         let graph = self.graph.as_ref()?;
         let quadtree = self.quadtree.as_ref()?;
@@ -76,27 +237,55 @@ impl BloomEngine {
             self.canvas_height as f64,
         );
 
-        let hit_radius = 10.0 / self.camera.zoom;
-        let candidates = quadtree.query_point(wx, wy, hit_radius);
-
+        // Radii are world-space, so the zoom cancels out here
         let nodes = graph.nodes();
-        candidates
-            .iter()
-            .filter_map(|&idx| {
+        quadtree
+            .query_point(wx, wy, self.sizing.max_radius())
+            .into_iter()
+            .filter_map(|idx| {
                 let node = &nodes[idx];
                 let dx = node.x - wx;
                 let dy = node.y - wy;
-                let dist = (dx * dx + dy * dy).sqrt();
-                if dist <= hit_radius {
-                    Some((dist, node))
-                } else {
-                    None
-                }
+                let dist_sq = dx * dx + dy * dy;
+                let radius = self.sizing.radius_for(node, &self.size_domain);
+                (dist_sq <= radius * radius).then_some((dist_sq, node))
             })
-            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .min_by(|a, b| a.0.total_cmp(&b.0))
             .map(|(_, node)| node)
     }
 
+    /// Source and target ids of the edge drawn nearest the screen point,
+    /// if one passes within `tolerance` pixels of it. Measures against the
+    /// lines as drawn, so the camera and curved edges are accounted for,
+    /// and only considers edges the current frame would draw.
+    pub fn edge_at(&self, screen_x: f32, screen_y: f32, tolerance: f32) -> Option<(u32, u32)> {
+        let graph = self.graph.as_ref()?;
+        let quadtree = self.quadtree.as_ref()?;
+        let (width, height) = (self.canvas_width as f64, self.canvas_height as f64);
+        let visible = visible_nodes(
+            quadtree,
+            &self.camera,
+            self.sizing.max_radius(),
+            width,
+            height,
+        );
+        edge_lines(
+            graph,
+            &visible,
+            &self.edge_style,
+            &self.node_style(),
+            None,
+            &self.camera,
+            width,
+            height,
+        )
+        .into_iter()
+        .map(|line| (line.distance_to(screen_x as f64, screen_y as f64), line))
+        .filter(|&(dist, _)| dist <= tolerance as f64)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, line)| (line.source, line.target))
+    }
+
     pub fn focus_node(&mut self, node_id: u32) {
         if let Some(graph) = &self.graph
             && let Some(node) = graph.node_by_id(node_id)
@@ -105,62 +294,258 @@ impl BloomEngine {
         }
     }
 
-    pub fn graph(&self) -> Option<&Graph> {
-        self.graph.as_ref()
+    /// Screen-space draw list for the current positions and camera, culled
+    /// to the viewport. Empty before a graph is loaded.
+    pub fn frame(&self) -> Frame {
+        match (&self.graph, &self.quadtree) {
+            (Some(graph), Some(quadtree)) => Frame::build(
+                graph,
+                quadtree,
+                &FrameStyle {
+                    nodes: self.node_style(),
+                    edges: self.edge_style,
+                    label_threshold: self.label_threshold,
+                    highlight: self.highlight.as_ref(),
+                },
+                &self.camera,
+                self.canvas_width as f64,
+                self.canvas_height as f64,
+            ),
+            _ => Frame::default(),
+        }
     }
 
-    pub fn camera(&self) -> &Camera {
-        &self.camera
+    fn node_style(&self) -> NodeStyle<'_> {
+        NodeStyle {
+            sizing: self.sizing,
+            domain: self.size_domain,
+            colors: &self.node_colors,
+        }
     }
-}
 
-fn build_quadtree(graph: &Graph) -> Quadtree {
-    let nodes = graph.nodes();
-    if nodes.is_empty() {
-        return Quadtree::new(
-            AABB {
-                min_x: -100.0,
-                min_y: -100.0,
-                max_x: 100.0,
-                max_y: 100.0,
-            },
-            4,
+    /// Drags the view by `(dx, dy)` screen pixels.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.camera.pan(dx, dy);
+    }
+
+    /// Zooms by `factor` around a screen point, which stays put.
+    pub fn zoom_at(&mut self, screen_x: f32, screen_y: f32, factor: f32) {
+        self.camera.zoom_at(
+            screen_x as f64,
+            screen_y as f64,
+            factor,
+            self.canvas_width as f64,
+            self.canvas_height as f64,
         );
     }
 
-    let mut min_x = f32::INFINITY;
-    let mut min_y = f32::INFINITY;
-    let mut max_x = f32::NEG_INFINITY;
-    let mut max_y = f32::NEG_INFINITY;
+    pub fn reset_view(&mut self) {
+        self.camera.reset_view();
+    }
+
+    /// Runs PageRank on the loaded graph and writes the scores into each
+    /// node's `pagerank`, so node sizes follow the new ranking. Returns the
+    /// scores index-aligned with `graph().nodes()`; empty before a load.
+    pub fn compute_pagerank(&mut self, iterations: usize, damping: f32) -> Vec<f32> {
+        let Some(graph) = &mut self.graph else {
+            return vec![];
+        };
+        let scores = pagerank(graph, iterations, damping);
+        for (node, &score) in graph.nodes_mut().iter_mut().zip(&scores) {
+            node.pagerank = score;
+        }
+        self.size_domain = SizeDomain::from_graph(graph);
+        self.refresh_colors();
+        scores
+    }
 
-    for node in nodes {
-        min_x = min_x.min(node.x);
-        min_y = min_y.min(node.y);
-        max_x = max_x.max(node.x);
-        max_y = max_y.max(node.y);
+    /// Runs Louvain on the loaded graph, writes each node's `community`
+    /// (so encoding with `HasCommunities` persists it) and keeps the
+    /// partition for later `communities()` calls. Returns a community id
+    /// per node, index-aligned with `graph().nodes()`; empty before a load.
+    pub fn compute_communities(&mut self) -> Vec<usize> {
+        self.detect_communities();
+        self.refresh_colors();
+        self.communities.clone().unwrap_or_default()
     }
 
-    // 5% padding
-    let pad_x = (max_x - min_x) * 0.05 + 1.0;
-    let pad_y = (max_y - min_y) * 0.05 + 1.0;
+    /// Runs Louvain, writes each node's `community` and caches the
+    /// partition, so the nodes and `communities()` always agree. Does
+    /// nothing before a load.
+    fn detect_communities(&mut self) {
+        let Some(graph) = &mut self.graph else {
+            return;
+        };
+        let communities = louvain(graph);
+        for (node, &c) in graph.nodes_mut().iter_mut().zip(&communities) {
+            node.community = Some(c as u32);
+        }
+        self.communities = Some(communities);
+    }
 
-    let bounds = AABB {
-        min_x: min_x - pad_x,
-        min_y: min_y - pad_y,
-        max_x: max_x + pad_x,
-        max_y: max_y + pad_y,
-    };
+    /// Weighted shortest path between two node ids, as node ids from
+    /// source to target inclusive. None if either id is unknown, the target
+    /// is unreachable, or nothing is loaded.
+    pub fn compute_shortest_path(&self, source_id: u32, target_id: u32) -> Option<Vec<u32>> {
+        let graph = self.graph.as_ref()?;
+        let path = shortest_path(graph, source_id, target_id)?;
+        let nodes = graph.nodes();
+        Some(path.into_iter().map(|idx| nodes[idx].id).collect())
+    }
+
+    /// Summary metrics for the loaded graph, or None before a load.
+    pub fn stats(&self) -> Option<GraphStats> {
+        self.graph.as_ref().map(GraphStats::compute)
+    }
 
-    let mut qt = Quadtree::new(bounds, 4);
-    for (i, node) in nodes.iter().enumerate() {
-        qt.insert(i, node);
+    /// The partition from the last `compute_communities` on this graph.
+    pub fn communities(&self) -> Option<&[usize]> {
+        self.communities.as_deref()
+    }
+
+    /// Changes how node radii are chosen. Drawing and `node_at` both
+    /// follow it, so the clickable area always matches what is drawn.
+    pub fn set_node_sizing(&mut self, sizing: NodeSizing) {
+        self.sizing = sizing;
+    }
+
+    /// Switches edges between straight and curved, and arrowheads (drawn
+    /// for directed graphs only) on or off.
+    pub fn set_edge_style(&mut self, style: EdgeStyle) {
+        self.edge_style = style;
+    }
+
+    pub fn edge_style(&self) -> EdgeStyle {
+        self.edge_style
+    }
+
+    /// Maps edge weight onto the `alpha` and `width` ranges, fitted to the
+    /// loaded graph's weight range and refitted on each load.
+    pub fn map_edge_weights(&mut self, alpha: (f64, f64), width: (f64, f64)) {
+        let weights = match &self.graph {
+            Some(graph) => EdgeWeightStyle::fitted(graph, alpha, width),
+            None => EdgeWeightStyle {
+                alpha,
+                width,
+                ..EdgeWeightStyle::default()
+            },
+        };
+        self.edge_style.weights = Some(weights);
+    }
+
+    pub fn clear_edge_weight_mapping(&mut self) {
+        self.edge_style.weights = None;
+    }
+
+    /// Only nodes drawn at least `px` pixels in radius get labels, so
+    /// zooming out leaves just the prominent ones. 0 labels everything
+    /// (up to the per-frame cap).
+    pub fn set_label_threshold(&mut self, px: f64) {
+        self.label_threshold = px;
+    }
+
+    /// Highlights node `id` and its neighbours: later frames draw them and
+    /// the node's own edges as usual and dim everything else. Replaces any
+    /// earlier highlight; an unknown id clears it.
+    pub fn highlight_node(&mut self, id: u32) {
+        self.highlight = self
+            .graph
+            .as_ref()
+            .and_then(|graph| Highlight::around(graph, id));
+    }
+
+    pub fn clear_highlight(&mut self) {
+        self.highlight = None;
+    }
+
+    pub fn highlight(&self) -> Option<&Highlight> {
+        self.highlight.as_ref()
+    }
+
+    /// Colours nodes by PageRank (a sequential scale over the graph's
+    /// range) or by community (a categorical scale, running Louvain first
+    /// if no partition has been computed yet).
+    pub fn set_node_coloring(&mut self, coloring: NodeColoring) {
+        self.coloring = coloring;
+        self.refresh_colors();
+    }
+
+    pub fn node_coloring(&self) -> NodeColoring {
+        self.coloring
+    }
+
+    fn refresh_colors(&mut self) {
+        if self.coloring == NodeColoring::Community && self.communities.is_none() {
+            self.detect_communities();
+        }
+        let Some(graph) = &self.graph else {
+            self.node_colors.clear();
+            return;
+        };
+        let nodes = graph.nodes();
+        self.node_colors = match self.coloring {
+            NodeColoring::PageRank => {
+                let min = nodes
+                    .iter()
+                    .map(|n| n.pagerank)
+                    .fold(f32::INFINITY, f32::min);
+                let scale = ColorScale::sequential(min, self.size_domain.max_pagerank);
+                nodes.iter().map(|n| scale.color_for(n.pagerank)).collect()
+            }
+            NodeColoring::Community => {
+                let communities = self.communities.as_deref().unwrap_or_default();
+                let count = communities.iter().max().map_or(0, |&c| c + 1);
+                let scale = ColorScale::categorical(count);
+                communities
+                    .iter()
+                    .map(|&c| scale.color_for(c as f32))
+                    .collect()
+            }
+        };
+    }
+
+    /// Nodes in the loaded graph, or 0 before anything is loaded.
+    pub fn node_count(&self) -> usize {
+        self.graph.as_ref().map_or(0, Graph::node_count)
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.as_ref().map_or(0, Graph::edge_count)
+    }
+
+    /// Every node's position flattened as `[x0, y0, x1, y1, ...]` in node
+    /// order, so callers can read them all at once. Empty before a load.
+    pub fn positions(&self) -> Vec<f32> {
+        self.graph.as_ref().map_or_else(Vec::new, |graph| {
+            graph.nodes().iter().flat_map(|n| [n.x, n.y]).collect()
+        })
+    }
+
+    /// Node ids in the same order as `positions`.
+    pub fn node_ids(&self) -> Vec<u32> {
+        self.graph.as_ref().map_or_else(Vec::new, |graph| {
+            graph.nodes().iter().map(|n| n.id).collect()
+        })
+    }
+
+    pub fn graph(&self) -> Option<&Graph> {
+        self.graph.as_ref()
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
     }
-    qt
+}
+
+fn build_quadtree(graph: &Graph) -> Quadtree {
+    Quadtree::from_nodes(graph.nodes(), DEFAULT_CAPACITY)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::Flags;
+    use crate::render::nodes::{MAX_NODE_RADIUS, MIN_NODE_RADIUS};
     use crate::test_utils::build_blom;
 
     #[test]
@@ -170,7 +555,10 @@ mod tests {
         let data = build_blom(nodes, edges, None);
 
         let mut engine = BloomEngine::new(800.0, 600.0);
+        assert_eq!(engine.node_count(), 0);
         engine.load_graph(&data).unwrap();
+        assert_eq!(engine.node_count(), 3);
+        assert_eq!(engine.edge_count(), 2);
 
         let graph = engine.graph().unwrap();
         assert_eq!(graph.node_count(), 3);
@@ -211,6 +599,52 @@ mod tests {
         assert_ne!(before, after, "positions should change after tick");
     }
 
+    #[test]
+    fn positions_flatten_in_node_order() {
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        assert!(engine.positions().is_empty());
+        assert!(engine.node_ids().is_empty());
+
+        let data = build_blom(&[(7, 0.5, 1), (3, 0.5, 1)], &[(7, 3)], None);
+        engine.load_graph(&data).unwrap();
+        assert_eq!(engine.node_ids(), vec![7, 3]);
+        let nodes = engine.graph().unwrap().nodes();
+        assert_eq!(
+            engine.positions(),
+            vec![nodes[0].x, nodes[0].y, nodes[1].x, nodes[1].y]
+        );
+    }
+
+    #[test]
+    fn max_displacement_bounds_each_tick() {
+        let data = build_blom(&[(1, 0.1, 1), (2, 0.2, 1), (3, 0.2, 0)], &[(1, 2)], None);
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.set_max_displacement(0.5);
+        engine.load_graph(&data).unwrap();
+        let positions = |engine: &BloomEngine| -> Vec<(f32, f32)> {
+            let nodes = engine.graph().unwrap().nodes();
+            nodes.iter().map(|n| (n.x, n.y)).collect()
+        };
+        for cap in [0.5, 0.1] {
+            let before = positions(&engine);
+            engine.tick(0.016);
+            for ((x0, y0), (x1, y1)) in before.into_iter().zip(positions(&engine)) {
+                assert!(((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt() <= cap + 1e-4);
+            }
+            // Also reaches the layout already running
+            engine.set_max_displacement(0.1);
+        }
+
+        // Input from JS isn't trusted to be sane
+        for px in [-3.0, f32::NAN] {
+            engine.set_max_displacement(px);
+            assert_eq!(engine.max_displacement, 0.0);
+            let before = positions(&engine);
+            engine.tick(0.016);
+            assert_eq!(positions(&engine), before);
+        }
+    }
+
     #[test]
     fn node_at_hit_test() {
         let nodes = &[(1, 0.0f32, 0u16), (2, 0.0, 0)];
@@ -226,15 +660,531 @@ mod tests {
         engine.graph.as_mut().unwrap().nodes_mut()[1].y = 100.0;
 
         // Rebuild quadtree with new positions
-        engine.tick(0.0);
+        engine.quadtree = engine.graph.as_ref().map(build_quadtree);
 
         // Screen center maps to world origin (camera at 0,0 zoom 1)
-        // After tick(0.0), camera hasn't moved much from focus_on(0,0,1)
         // Screen center = (400, 300), which maps to world ~(0,0)
         let hit = engine.node_at(400.0, 300.0);
         assert!(hit.is_some(), "should hit node near origin");
     }
 
+    #[test]
+    fn spatial_rebuild_predicate() {
+        // Nothing moved, nothing to catch up on
+        assert!(!should_rebuild_spatial(0, 0.0, 4));
+        assert!(!should_rebuild_spatial(0, 10.0, 4));
+        // Within the interval and below the drift threshold, wait
+        assert!(!should_rebuild_spatial(1, 0.1, 4));
+        assert!(!should_rebuild_spatial(3, SPATIAL_DRIFT_THRESHOLD * 0.9, 4));
+        // Either limit triggers a rebuild
+        assert!(should_rebuild_spatial(4, 0.0, 4));
+        assert!(should_rebuild_spatial(9, 0.0, 4));
+        assert!(should_rebuild_spatial(1, SPATIAL_DRIFT_THRESHOLD, 4));
+        // Intervals of 0 and 1 rebuild after every moving frame
+        assert!(should_rebuild_spatial(1, 0.0, 1));
+        assert!(should_rebuild_spatial(1, 0.0, 0));
+    }
+
+    #[test]
+    fn quadtree_rebuilds_are_throttled() {
+        let data = build_blom(
+            &[(1, 0.0, 1), (2, 0.0, 2), (3, 0.0, 1)],
+            &[(1, 2), (2, 3)],
+            None,
+        );
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+        engine.set_spatial_rebuild_interval(1_000);
+        // Ignore drift so only the settle rule can rebuild
+        engine.spatial_lag.movement = f32::NEG_INFINITY;
+        engine.tick(0.0);
+        assert_eq!(engine.spatial_lag.frames, 1);
+
+        // Settling rebuilds whatever the interval
+        assert!(engine.run_layout(1.0, || 0.0));
+        assert_eq!(engine.spatial_lag.frames, 0);
+        let camera = engine.camera();
+        for node in engine.graph().unwrap().nodes() {
+            let (sx, sy) = camera.world_to_screen(node.x, node.y, 800.0, 600.0);
+            assert_eq!(
+                engine.node_at(sx as f32, sy as f32).map(|n| n.id),
+                Some(node.id)
+            );
+        }
+    }
+
+    #[test]
+    fn frame_draws_loaded_graph() {
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        let empty = engine.frame();
+        assert!(empty.nodes.is_empty() && empty.edges.is_empty());
+
+        let data = build_blom(
+            &[(1, 0.1, 1), (2, 0.4, 2), (3, 0.2, 1)],
+            &[(1, 2), (2, 3)],
+            None,
+        );
+        engine.load_graph(&data).unwrap();
+        let frame = engine.frame();
+        assert_eq!(frame.nodes.len(), 3);
+        assert_eq!(frame.edges.len(), 2);
+        // The highest-ranked node is drawn largest
+        let biggest = frame
+            .nodes
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.radius.total_cmp(&b.1.radius))
+            .map(|(i, _)| i);
+        assert_eq!(biggest, Some(1));
+    }
+
+    #[test]
+    fn highlight_dims_everything_else() {
+        // Path 1-2-3-4 plus a branch 2-5
+        let data = build_blom(
+            &[
+                (1, 0.1, 1),
+                (2, 0.4, 3),
+                (3, 0.2, 2),
+                (4, 0.1, 1),
+                (5, 0.1, 1),
+            ],
+            &[(1, 2), (2, 3), (3, 4), (2, 5)],
+            None,
+        );
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+        let plain = engine.frame();
+
+        engine.highlight_node(2);
+        let highlight = engine.highlight().unwrap();
+        let graph = engine.graph().unwrap();
+        let mut expected: Vec<usize> = graph.neighbor_indices(highlight.focus).to_vec();
+        expected.push(highlight.focus);
+        expected.sort_unstable();
+        assert_eq!(highlight.nodes, expected);
+        let ids: Vec<u32> = highlight
+            .nodes
+            .iter()
+            .map(|&i| graph.nodes()[i].id)
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3, 5]);
+
+        let lit = engine.frame();
+        let opaque = |frame: &Frame| frame.nodes.iter().filter(|c| c.color[3] == 255).count();
+        assert_eq!(opaque(&plain), 5);
+        // Node 4, the only one off the hovered node's neighbourhood
+        assert_eq!(opaque(&lit), 4);
+        // Only 3-4 doesn't touch node 2
+        let dimmed = lit
+            .edges
+            .iter()
+            .zip(&plain.edges)
+            .filter(|(l, p)| l.alpha < p.alpha)
+            .count();
+        assert_eq!(dimmed, 1);
+
+        engine.clear_highlight();
+        let cleared = engine.frame();
+        assert_eq!(cleared.nodes, plain.nodes);
+        assert_eq!(cleared.edges, plain.edges);
+        engine.highlight_node(99);
+        assert!(engine.highlight().is_none());
+        engine.highlight_node(4);
+        engine.load_graph(&data).unwrap();
+        assert!(engine.highlight().is_none());
+    }
+
+    #[test]
+    fn edge_at_picks_nearest_drawn_edge() {
+        let data = build_blom(
+            &[(1, 0.1, 2), (2, 0.1, 1), (3, 0.1, 1)],
+            &[(1, 2), (3, 1)],
+            None,
+        );
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        assert_eq!(engine.edge_at(400.0, 300.0, 5.0), None);
+        engine.load_graph(&data).unwrap();
+        // Lay the nodes out by hand: 1 at the origin, 2 right, 3 below
+        let graph = engine.graph.as_mut().unwrap();
+        for (node, (x, y)) in
+            graph
+                .nodes_mut()
+                .iter_mut()
+                .zip([(0.0, 0.0), (100.0, 0.0), (0.0, 100.0)])
+        {
+            node.x = x;
+            node.y = y;
+        }
+        engine.quadtree = Some(build_quadtree(engine.graph().unwrap()));
+
+        // At zoom 1, world (50, 2) is screen (450, 302): 2px off the 1-2 edge
+        assert_eq!(engine.edge_at(450.0, 302.0, 5.0), Some((1, 2)));
+        assert_eq!(engine.edge_at(450.0, 302.0, 1.0), None);
+        // Edges report their stored direction
+        assert_eq!(engine.edge_at(398.0, 350.0, 5.0), Some((3, 1)));
+        // Nearer the 3-1 edge than the 1-2 edge
+        assert_eq!(engine.edge_at(410.0, 418.0, 50.0), Some((3, 1)));
+
+        // Curved edges are measured along the arc, which bows away from
+        // the chord
+        engine.set_edge_style(EdgeStyle {
+            curved: true,
+            ..EdgeStyle::default()
+        });
+        assert_eq!(engine.edge_at(450.0, 300.0, 5.0), None);
+        let line = engine
+            .frame()
+            .edges
+            .into_iter()
+            .find(|l| l.source == 1 && l.target == 2)
+            .unwrap();
+        // The bezier at t = 0.5
+        let (cx, cy) = line.control.unwrap();
+        let apex_x = (line.x1 + 2.0 * cx + line.x2) / 4.0;
+        let apex_y = (line.y1 + 2.0 * cy + line.y2) / 4.0;
+        assert_eq!(
+            engine.edge_at(apex_x as f32, apex_y as f32, 1.0),
+            Some((1, 2))
+        );
+    }
+
+    #[test]
+    fn node_at_respects_rendered_radius() {
+        // Node 1 is the hub (max radius), node 2 the smallest
+        let nodes = &[(1, 0.5f32, 2u16), (2, 0.0, 1), (3, 0.1, 1)];
+        let data = build_blom(nodes, &[(1, 2), (1, 3)], None);
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+
+        let positions = [(0.0, 0.0), (50.0, 0.0), (0.0, 60.0)];
+        for (node, (x, y)) in engine
+            .graph
+            .as_mut()
+            .unwrap()
+            .nodes_mut()
+            .iter_mut()
+            .zip(positions)
+        {
+            node.x = x;
+            node.y = y;
+        }
+        engine.quadtree = engine.graph.as_ref().map(build_quadtree);
+
+        // Screen centre is world origin; offsets below are world units too
+        let id_at = |x: f32, y: f32| engine.node_at(400.0 + x, 300.0 + y).map(|n| n.id);
+        assert_eq!(id_at(2.0, -1.0), Some(1));
+        // Inside the hub's radius but outside the small node's
+        assert_eq!(id_at(MAX_NODE_RADIUS - 1.0, 0.0), Some(1));
+        assert_eq!(id_at(50.0 - MIN_NODE_RADIUS + 0.5, 0.0), Some(2));
+        assert_eq!(id_at(50.0 - MIN_NODE_RADIUS - 1.0, 0.0), None);
+        assert_eq!(id_at(1.0, 59.0), Some(3));
+        assert_eq!(id_at(25.0, 30.0), None);
+    }
+
+    #[test]
+    fn node_at_follows_sizing() {
+        // Node 1 has the higher rank but node 2 the higher degree
+        let data = build_blom(&[(1, 0.5, 1), (2, 0.1, 4)], &[], None);
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+        let positions = [(0.0, 0.0), (100.0, 0.0)];
+        for (node, (x, y)) in engine
+            .graph
+            .as_mut()
+            .unwrap()
+            .nodes_mut()
+            .iter_mut()
+            .zip(positions)
+        {
+            node.x = x;
+            node.y = y;
+        }
+        engine.quadtree = engine.graph.as_ref().map(build_quadtree);
+
+        let id_at = |engine: &BloomEngine, x: f32| engine.node_at(400.0 + x, 300.0).map(|n| n.id);
+        let radii = |engine: &BloomEngine| -> Vec<f64> {
+            engine.frame().nodes.iter().map(|c| c.radius).collect()
+        };
+        assert_eq!(id_at(&engine, 10.0), Some(1));
+        assert_eq!(id_at(&engine, 90.0), None);
+        assert!(radii(&engine)[0] > radii(&engine)[1]);
+
+        engine.set_node_sizing(NodeSizing::ByDegree {
+            min: 2.0,
+            max: 20.0,
+            log: false,
+        });
+        assert_eq!(id_at(&engine, 10.0), None);
+        assert_eq!(id_at(&engine, 85.0), Some(2));
+        assert_eq!(radii(&engine), vec![6.5, 20.0]);
+
+        // A huge fixed radius still hits from far away
+        engine.set_node_sizing(NodeSizing::Fixed(40.0));
+        assert_eq!(id_at(&engine, 35.0), Some(1));
+        assert_eq!(id_at(&engine, 65.0), Some(2));
+    }
+
+    #[test]
+    fn node_at_follows_pan_and_zoom() {
+        let data = build_blom(&[(1, 0.0, 0), (2, 0.0, 0)], &[], None);
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+        let positions = [(0.0, 0.0), (100.0, 0.0)];
+        for (node, (x, y)) in engine
+            .graph
+            .as_mut()
+            .unwrap()
+            .nodes_mut()
+            .iter_mut()
+            .zip(positions)
+        {
+            node.x = x;
+            node.y = y;
+        }
+        engine.quadtree = engine.graph.as_ref().map(build_quadtree);
+
+        // Zoom 2x around node 1, then drag it 100px left
+        engine.zoom_at(400.0, 300.0, 2.0);
+        engine.pan(-100.0, 0.0);
+        let id_at = |x, y| engine.node_at(x, y).map(|n| n.id);
+        assert_eq!(id_at(300.0, 300.0), Some(1));
+        assert_eq!(id_at(500.0, 300.0), Some(2));
+        assert_eq!(id_at(400.0, 300.0), None);
+
+        let frame = engine.frame();
+        assert_eq!((frame.nodes[1].x, frame.nodes[1].y), (500.0, 300.0));
+
+        engine.reset_view();
+        assert_eq!(engine.node_at(400.0, 300.0).map(|n| n.id), Some(1));
+    }
+
+    #[test]
+    fn run_layout_respects_budget_and_settles() {
+        let data = build_blom(
+            &[(1, 0.0, 1), (2, 0.0, 2), (3, 0.0, 1)],
+            &[(1, 2), (2, 3)],
+            None,
+        );
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+
+        // Each clock read advances 1ms, so a 3ms budget allows 3 steps
+        let mut clock = 0.0;
+        let mut reads = 0;
+        let settled = engine.run_layout(3.0, || {
+            reads += 1;
+            clock += 1.0;
+            clock
+        });
+        assert!(!settled);
+        assert_eq!(reads, 4, "start plus one read per step");
+
+        // A zero budget still takes a step
+        let before = engine.graph().unwrap().nodes()[0].x;
+        engine.run_layout(0.0, || 0.0);
+        assert_ne!(engine.graph().unwrap().nodes()[0].x, before);
+
+        // A frozen clock never runs out, so this runs until settled
+        assert!(engine.run_layout(1.0, || 0.0));
+        // Once settled, no steps are taken at all
+        let mut reads = 0;
+        assert!(engine.run_layout(100.0, || {
+            reads += 1;
+            0.0
+        }));
+        assert_eq!(reads, 1);
+    }
+
+    #[test]
+    fn load_layout_then_pick_each_node() {
+        let data = build_blom(
+            &[(1, 0.4, 2), (2, 0.3, 3), (3, 0.2, 2), (4, 0.1, 1)],
+            &[(1, 2), (2, 3), (3, 1), (2, 4)],
+            None,
+        );
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+        assert!(engine.run_layout(1.0, || 0.0));
+
+        // Every laid-out node is picked at its own projected centre
+        let camera = engine.camera();
+        for node in engine.graph().unwrap().nodes() {
+            let (sx, sy) = camera.world_to_screen(node.x, node.y, 800.0, 600.0);
+            let hit = engine.node_at(sx as f32, sy as f32).map(|n| n.id);
+            assert_eq!(hit, Some(node.id));
+        }
+        // Far off every node there's nothing to pick
+        assert!(engine.node_at(-1e6, -1e6).is_none());
+    }
+
+    #[test]
+    fn run_layout_without_graph_is_settled() {
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        assert!(engine.run_layout(16.0, || 0.0));
+        engine.load_graph(&build_blom(&[], &[], None)).unwrap();
+        assert!(engine.run_layout(16.0, || 0.0));
+    }
+
+    #[test]
+    fn compute_algorithms_on_loaded_graph() {
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        assert!(engine.compute_pagerank(20, 0.85).is_empty());
+        assert!(engine.compute_communities().is_empty());
+        assert_eq!(engine.compute_shortest_path(1, 2), None);
+        assert_eq!(engine.stats(), None);
+
+        // Two triangles joined by the edge 3-4
+        let data = build_blom(
+            &[
+                (10, 0.0, 2),
+                (20, 0.0, 2),
+                (30, 0.0, 3),
+                (40, 0.0, 3),
+                (50, 0.0, 2),
+                (60, 0.0, 2),
+            ],
+            &[
+                (10, 20),
+                (20, 30),
+                (30, 10),
+                (30, 40),
+                (40, 50),
+                (50, 60),
+                (60, 40),
+            ],
+            None,
+        );
+        engine.load_graph(&data).unwrap();
+
+        let scores = engine.compute_pagerank(50, 0.85);
+        assert_eq!(scores.len(), 6);
+        let stored: Vec<f32> = engine
+            .graph()
+            .unwrap()
+            .nodes()
+            .iter()
+            .map(|n| n.pagerank)
+            .collect();
+        assert_eq!(stored, scores);
+        assert!(scores[2] > scores[0], "bridge nodes rank higher");
+        assert_eq!(
+            engine.size_domain,
+            SizeDomain::from_graph(engine.graph().unwrap())
+        );
+
+        let communities = engine.compute_communities();
+        assert_eq!(communities[0], communities[1]);
+        assert_eq!(communities[0], communities[2]);
+        assert_eq!(communities[3], communities[5]);
+        assert_ne!(communities[0], communities[3]);
+        assert_eq!(engine.communities(), Some(communities.as_slice()));
+
+        assert_eq!(
+            engine.compute_shortest_path(10, 60),
+            Some(vec![10, 30, 40, 60])
+        );
+        assert_eq!(engine.compute_shortest_path(10, 99), None);
+
+        let stats = engine.stats().unwrap();
+        assert_eq!(stats.component_count, 1);
+        assert!((stats.density - 7.0 / 15.0).abs() < 1e-6);
+
+        // A new load drops the old partition
+        engine.load_graph(&data).unwrap();
+        assert_eq!(engine.communities(), None);
+    }
+
+    #[test]
+    fn labels_follow_zoom_and_threshold() {
+        let data = build_blom(
+            &[(1, 0.4, 1), (2, 0.1, 1), (3, 0.4, 0)],
+            &[(1, 2)],
+            Some(&["hub", "leaf", ""]),
+        );
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+        let texts = |engine: &BloomEngine| -> Vec<String> {
+            engine.frame().labels.into_iter().map(|l| l.text).collect()
+        };
+
+        // At zoom 1 only the hub (radius 12) clears the default 8px
+        assert_eq!(texts(&engine), vec!["hub"]);
+        engine.set_label_threshold(0.0);
+        assert_eq!(texts(&engine), vec!["hub", "leaf"]);
+        engine.set_label_threshold(8.0);
+        // Zoomed out, even the hub is too small
+        engine.zoom_at(400.0, 300.0, 0.5);
+        assert!(texts(&engine).is_empty());
+    }
+
+    #[test]
+    fn nodes_colored_by_pagerank_or_community() {
+        let data = build_blom(
+            &[
+                (1, 0.1, 1),
+                (2, 0.4, 2),
+                (3, 0.2, 1),
+                (4, 0.0, 1),
+                (5, 0.0, 1),
+            ],
+            &[(1, 2), (2, 3), (4, 5)],
+            None,
+        );
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+        assert_eq!(engine.node_coloring(), NodeColoring::PageRank);
+
+        let scale = ColorScale::sequential(0.0, 0.4);
+        let colors: Vec<[u8; 4]> = engine.frame().nodes.iter().map(|c| c.color).collect();
+        assert_eq!(colors[1], scale.color_for(0.4));
+        assert_eq!(colors[3], scale.color_for(0.0));
+        assert_ne!(colors[0], colors[2]);
+
+        // Switching modes runs Louvain on demand, recording it on the
+        // nodes just as `compute_communities` does
+        engine.set_node_coloring(NodeColoring::Community);
+        let communities = engine.communities().unwrap().to_vec();
+        let on_nodes: Vec<Option<u32>> = engine
+            .graph()
+            .unwrap()
+            .nodes()
+            .iter()
+            .map(|n| n.community)
+            .collect();
+        let expected: Vec<Option<u32>> = communities.iter().map(|&c| Some(c as u32)).collect();
+        assert_eq!(on_nodes, expected);
+        let colors: Vec<[u8; 4]> = engine.frame().nodes.iter().map(|c| c.color).collect();
+        assert_eq!(colors[0], colors[1]);
+        assert_eq!(colors[3], colors[4]);
+        assert_ne!(colors[0], colors[3]);
+        let scale = ColorScale::categorical(2);
+        assert_eq!(colors[3], scale.color_for(communities[3] as f32));
+    }
+
+    #[test]
+    fn communities_persist_through_encoding() {
+        let data = build_blom(
+            &[(1, 0.0, 2), (2, 0.0, 2), (3, 0.0, 2), (4, 0.0, 1)],
+            &[(1, 2), (2, 3), (3, 1), (3, 4)],
+            None,
+        );
+        let mut engine = BloomEngine::new(800.0, 600.0);
+        engine.load_graph(&data).unwrap();
+        let computed = engine.compute_communities();
+        let graph = engine.graph().unwrap();
+        assert!(graph.nodes().iter().all(|n| n.community.is_some()));
+
+        let saved = graph.to_bloom_bytes(Flags::HasCommunities);
+        let mut reloaded = BloomEngine::new(800.0, 600.0);
+        reloaded.load_graph(&saved).unwrap();
+        assert_eq!(reloaded.communities(), Some(&computed[..]));
+
+        // Without the flag there is nothing to adopt
+        reloaded.load_graph(&data).unwrap();
+        assert_eq!(reloaded.communities(), None);
+    }
+
     #[test]
     fn load_graph_replaces_previous() {
         let data1 = build_blom(&[(1, 0.0, 0), (2, 0.0, 0)], &[], None);
@@ -1,56 +1,266 @@
-use crate::graph::types::Graph;
+use crate::graph::types::{Edge, Graph};
+
+mod centrality;
+mod clustering;
+mod community;
+mod components;
+mod degree;
+mod paths;
+mod sampling;
+mod spanning;
+mod stats;
+
+pub use centrality::closeness_centrality;
+pub use clustering::{local_clustering_coefficient, triangle_count};
+pub use community::{LouvainState, label_propagation, louvain, modularity};
+pub use components::{
+    connected_components, strongly_connected_components, weakly_connected_components,
+};
+pub use degree::{average_degree, degree_histogram, max_degree, min_degree, strength_distribution};
+pub use paths::{
+    DEFAULT_ALL_PAIRS_MAX_NODES, SearchStats, all_pairs_shortest_paths, astar_path,
+    astar_path_with_stats, bfs_distances, bfs_path, diameter, eccentricity, shortest_path,
+};
+pub use sampling::{SampleStrategy, sample_subgraph};
+pub use spanning::minimum_spanning_tree;
+pub use stats::GraphStats;
 
 /// Iterative PageRank until convergence.
 ///
-/// d = 0.85 is the standard damping factor.
+/// d = 0.85 is the standard damping factor. Rank flows along edge direction
+/// on directed graphs and to every neighbour on undirected ones, split in
+/// proportion to `Edge::weight` (uniformly when the graph is unweighted).
+/// Nodes with no outgoing weight redistribute their rank evenly.
 /// Returns a Vec<f32> of scores aligned with graph.nodes().
 pub fn pagerank(graph: &Graph, iterations: usize, damping: f32) -> Vec<f32> {
-    let n = graph.node_count();
-    if n == 0 {
-        return vec![];
+    run_pagerank(graph, graph.is_directed(), iterations, damping, None, None).0
+}
+
+/// PageRank whose random jumps (and dangling-node leaks) land only on the
+/// nodes in `seed_ids`, split evenly between them, so scores measure
+/// closeness to the seeds rather than global importance. With no seeds
+/// at all this is plain `pagerank`.
+/// Returns a Vec<f32> of scores aligned with graph.nodes(), summing to 1,
+/// or an error naming the first seed id not in the graph.
+pub fn personalized_pagerank(
+    graph: &Graph,
+    seed_ids: &[u32],
+    iterations: usize,
+    damping: f32,
+) -> Result<Vec<f32>, String> {
+    let seeds = seed_ids
+        .iter()
+        .map(|&id| {
+            graph
+                .node_index(id)
+                .ok_or_else(|| format!("unknown seed node id {id}"))
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+    if seeds.is_empty() {
+        return Ok(pagerank(graph, iterations, damping));
+    }
+    let mut teleport = vec![0.0; graph.node_count()];
+    for &i in &seeds {
+        teleport[i] += 1.0 / seeds.len() as f32;
     }
+    let directed = graph.is_directed();
+    Ok(run_pagerank(graph, directed, iterations, damping, None, Some(&teleport)).0)
+}
 
-    let mut scores = vec![1.0 / n as f32; n];
+/// PageRank that stops early once the L1 change between successive
+/// iterations drops below `tolerance`, running at most `max_iterations`.
+/// Returns the scores and the number of iterations actually performed.
+pub fn pagerank_until(
+    graph: &Graph,
+    max_iterations: usize,
+    damping: f32,
+    tolerance: f32,
+) -> (Vec<f32>, usize) {
+    let directed = graph.is_directed();
+    run_pagerank(
+        graph,
+        directed,
+        max_iterations,
+        damping,
+        Some(tolerance),
+        None,
+    )
+}
 
-    for _ in 0..iterations {
-        let mut next = vec![(1.0 - damping) / n as f32; n];
+/// PageRank reading every edge strictly as `source -> target`, whatever
+/// the graph's kind, for directed data stored in an undirected `Graph`.
+/// Rank flows only along outgoing edges, so nodes many edges point at
+/// collect it. On a directed graph this is the same as `pagerank`.
+pub fn pagerank_directed(graph: &Graph, iterations: usize, damping: f32) -> Vec<f32> {
+    run_pagerank(graph, true, iterations, damping, None, None).0
+}
 
-        for (i, node) in graph.nodes().iter().enumerate() {
-            let out_neighbors = graph.neighbors(node.id);
-            if out_neighbors.is_empty() {
-                // Dangling node: distribute evenly
-                let share = scores[i] * damping / n as f32;
-                for s in next.iter_mut() {
-                    *s += share;
-                }
-            } else {
-                let share = scores[i] * damping / out_neighbors.len() as f32;
-                for neighbor_id in &out_neighbors {
-                    if let Some(j) = graph.node_index(*neighbor_id) {
-                        next[j] += share;
+/// Score and edge buffers for re-running PageRank, e.g. each time an
+/// interactive graph changes. The free functions allocate fresh vectors
+/// per call; this keeps them between runs and only grows them when the
+/// graph does. Results are identical to `pagerank`.
+#[derive(Debug, Default)]
+pub struct PageRankState {
+    scores: Vec<f32>,
+    next: Vec<f32>,
+    out_weight: Vec<f32>,
+    out_edges: OutEdges,
+}
+
+impl PageRankState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `pagerank(graph, iterations, damping)` into the kept buffers
+    /// and returns the scores, aligned with graph.nodes().
+    pub fn run(&mut self, graph: &Graph, iterations: usize, damping: f32) -> &[f32] {
+        self.out_edges.fill(graph, graph.is_directed());
+        self.iterate(iterations, damping, None, None);
+        &self.scores
+    }
+
+    /// Scores from the last `run`; empty before the first.
+    pub fn scores(&self) -> &[f32] {
+        &self.scores
+    }
+
+    /// Power iteration shared by the PageRank variants over the filled
+    /// `out_edges`, leaving the result in `scores`. `teleport` is the jump
+    /// distribution (summing to 1); None means uniform. Returns the number
+    /// of iterations performed.
+    fn iterate(
+        &mut self,
+        max_iterations: usize,
+        damping: f32,
+        tolerance: Option<f32>,
+        teleport: Option<&[f32]>,
+    ) -> usize {
+        let n = self.out_edges.node_count();
+        self.scores.clear();
+        if n == 0 {
+            return 0;
+        }
+
+        self.out_weight.clear();
+        self.out_weight
+            .extend((0..n).map(|i| self.out_edges.of(i).iter().map(|&(_, w)| w).sum::<f32>()));
+        self.scores.resize(n, 1.0 / n as f32);
+
+        for iteration in 1..=max_iterations {
+            // Rank leaking from dangling nodes follows the teleport distribution
+            let dangling: f32 = self
+                .scores
+                .iter()
+                .zip(&self.out_weight)
+                .filter(|&(_, &w)| w <= 0.0)
+                .map(|(&score, _)| score)
+                .sum();
+            let jump = 1.0 - damping + damping * dangling;
+            self.next.clear();
+            match teleport {
+                Some(t) => self.next.extend(t.iter().map(|&p| jump * p)),
+                None => self.next.resize(n, jump / n as f32),
+            }
+
+            for (i, &score) in self.scores.iter().enumerate() {
+                if self.out_weight[i] > 0.0 {
+                    let share = score * damping / self.out_weight[i];
+                    for &(j, w) in self.out_edges.of(i) {
+                        self.next[j] += share * w;
                     }
                 }
             }
-        }
 
-        scores = next;
+            let delta: f32 = self
+                .scores
+                .iter()
+                .zip(&self.next)
+                .map(|(a, b)| (a - b).abs())
+                .sum();
+            std::mem::swap(&mut self.scores, &mut self.next);
+            if tolerance.is_some_and(|tol| delta < tol) {
+                return iteration;
+            }
+        }
+        max_iterations
     }
+}
 
-    scores
+/// One-shot power iteration for the free PageRank functions; see
+/// `PageRankState::iterate`.
+fn run_pagerank(
+    graph: &Graph,
+    directed: bool,
+    max_iterations: usize,
+    damping: f32,
+    tolerance: Option<f32>,
+    teleport: Option<&[f32]>,
+) -> (Vec<f32>, usize) {
+    let mut state = PageRankState::new();
+    state.out_edges.fill(graph, directed);
+    let iterations = state.iterate(max_iterations, damping, tolerance, teleport);
+    (state.scores, iterations)
 }
 
-/// Stub: Louvain community detection.
-/// Returns a community ID per node (index-aligned with graph.nodes()).
-pub fn louvain(_graph: &Graph) -> Vec<usize> {
-    // TODO: implement Louvain modularity optimisation
-    vec![]
+/// `(target_index, weight)` lists per node, following edge direction when
+/// `directed` and both ways otherwise, flattened into one buffer: node
+/// `i`'s list is `targets[offsets[i]..offsets[i + 1]]`, in edge order.
+/// Dangling edges are skipped.
+#[derive(Debug, Default)]
+struct OutEdges {
+    offsets: Vec<usize>,
+    targets: Vec<(usize, f32)>,
 }
 
-/// Stub: Dijkstra shortest path.
-/// Returns the node-index path from `source_id` to `target_id`, or None.
-pub fn shortest_path(_graph: &Graph, _source_id: u32, _target_id: u32) -> Option<Vec<usize>> {
-    // TODO: implement Dijkstra
-    None
+impl OutEdges {
+    /// Refills the lists from `graph`, reusing the allocations.
+    fn fill(&mut self, graph: &Graph, directed: bool) {
+        let n = graph.node_count();
+        let endpoints = |edge: &Edge| {
+            let i = graph.node_index(edge.source)?;
+            let j = graph.node_index(edge.target)?;
+            Some((i, j, !directed && i != j))
+        };
+
+        // Count each node's list, then turn the counts into end offsets
+        self.offsets.clear();
+        self.offsets.resize(n + 1, 0);
+        for (i, j, both_ways) in graph.edges().iter().filter_map(endpoints) {
+            self.offsets[i] += 1;
+            if both_ways {
+                self.offsets[j] += 1;
+            }
+        }
+        let mut end = 0;
+        for offset in &mut self.offsets {
+            end += *offset;
+            *offset = end;
+        }
+
+        // Placing back to front walks each end offset down to its start
+        // and keeps every list in edge order
+        self.targets.clear();
+        self.targets.resize(end, (0, 0.0));
+        for edge in graph.edges().iter().rev() {
+            if let Some((i, j, both_ways)) = endpoints(edge) {
+                if both_ways {
+                    self.offsets[j] -= 1;
+                    self.targets[self.offsets[j]] = (i, edge.weight);
+                }
+                self.offsets[i] -= 1;
+                self.targets[self.offsets[i]] = (j, edge.weight);
+            }
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    fn of(&self, i: usize) -> &[(usize, f32)] {
+        &self.targets[self.offsets[i]..self.offsets[i + 1]]
+    }
 }
 
 /// Stub: betweenness centrality.
@@ -62,34 +272,27 @@ pub fn betweenness_centrality(_graph: &Graph) -> Vec<f32> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::graph::types::{Edge, Node};
-
-    fn make_node(id: u32) -> Node {
-        Node {
-            id,
-            label: String::new(),
-            pagerank: 0.0,
-            degree: 0,
-            x: 0.0,
-            y: 0.0,
-        }
-    }
+    use crate::graph::types::GraphKind;
+    use crate::test_utils::node;
 
     fn triangle_graph() -> Graph {
         // 1 -> 2 -> 3 -> 1
-        let nodes = vec![make_node(1), make_node(2), make_node(3)];
+        let nodes = vec![node(1), node(2), node(3)];
         let edges = vec![
             Edge {
                 source: 1,
                 target: 2,
+                weight: 1.0,
             },
             Edge {
                 source: 2,
                 target: 3,
+                weight: 1.0,
             },
             Edge {
                 source: 3,
                 target: 1,
+                weight: 1.0,
             },
         ];
         Graph::new(nodes, edges)
@@ -129,19 +332,22 @@ mod tests {
     #[test]
     fn pagerank_star_graph_center_ranks_higher() {
         // 1 is the hub: 2->1, 3->1, 4->1
-        let nodes = vec![make_node(1), make_node(2), make_node(3), make_node(4)];
+        let nodes = vec![node(1), node(2), node(3), node(4)];
         let edges = vec![
             Edge {
                 source: 2,
                 target: 1,
+                weight: 1.0,
             },
             Edge {
                 source: 3,
                 target: 1,
+                weight: 1.0,
             },
             Edge {
                 source: 4,
                 target: 1,
+                weight: 1.0,
             },
         ];
         let g = Graph::new(nodes, edges);
@@ -156,11 +362,217 @@ mod tests {
         assert_eq!(max_idx, 0, "hub node should rank highest");
     }
 
+    #[test]
+    fn pagerank_respects_direction() {
+        // 1 -> 2 -> 3: directed, the sink collects rank; undirected, the
+        // middle node has the most neighbours
+        let chain = |kind| {
+            let nodes = vec![node(1), node(2), node(3)];
+            let edges = vec![
+                Edge {
+                    source: 1,
+                    target: 2,
+                    weight: 1.0,
+                },
+                Edge {
+                    source: 2,
+                    target: 3,
+                    weight: 1.0,
+                },
+            ];
+            Graph::with_kind(nodes, edges, kind)
+        };
+        let directed = pagerank(&chain(GraphKind::Directed), 50, 0.85);
+        assert!(directed[2] > directed[1] && directed[1] > directed[0]);
+        let undirected = pagerank(&chain(GraphKind::Undirected), 50, 0.85);
+        assert!(undirected[1] > undirected[0]);
+        assert!((undirected[0] - undirected[2]).abs() < 1e-5);
+    }
+
+    #[test]
+    fn pagerank_directed_on_undirected_storage() {
+        // Leaves 2..=6 all point at 1, stored in an undirected graph
+        let star = |kind| {
+            let nodes = (1..=6).map(node).collect();
+            let edges = (2..=6)
+                .map(|leaf| Edge {
+                    source: leaf,
+                    target: 1,
+                    weight: 1.0,
+                })
+                .collect();
+            Graph::with_kind(nodes, edges, kind)
+        };
+        let g = star(GraphKind::Undirected);
+        let directed = pagerank_directed(&g, 50, 0.85);
+        let undirected = pagerank(&g, 50, 0.85);
+
+        assert!((directed.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+        // The centre soaks up rank it never passes back
+        assert!(directed[0] > undirected[0]);
+        for leaf in 1..6 {
+            assert!(directed[leaf] < undirected[leaf]);
+            assert!(directed[0] > 4.0 * directed[leaf]);
+        }
+        // Same as plain PageRank on a graph that really is directed
+        let on_directed = pagerank(&star(GraphKind::Directed), 50, 0.85);
+        assert_eq!(directed, on_directed);
+    }
+
+    #[test]
+    fn pagerank_follows_heavier_edge() {
+        // 1 splits its rank between 2 (weight 4) and 3 (weight 1); both
+        // feed back to 1 so nothing dangles
+        let nodes = vec![node(1), node(2), node(3)];
+        let edge = |source, target, weight| Edge {
+            source,
+            target,
+            weight,
+        };
+        let edges = vec![
+            edge(1, 2, 4.0),
+            edge(1, 3, 1.0),
+            edge(2, 1, 1.0),
+            edge(3, 1, 1.0),
+        ];
+        let g = Graph::with_kind(nodes, edges, GraphKind::Directed);
+        let scores = pagerank(&g, 50, 0.85);
+        assert!(scores[1] > 2.0 * scores[2], "scores {scores:?}");
+        let sum: f32 = scores.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn pagerank_until_stops_early() {
+        // A path starts far from its fixed point, unlike the symmetric cycle
+        let nodes = (1..=5).map(node).collect();
+        let edges = (1..5)
+            .map(|i| Edge {
+                source: i,
+                target: i + 1,
+                weight: 1.0,
+            })
+            .collect();
+        let g = Graph::new(nodes, edges);
+        let (scores, used) = pagerank_until(&g, 1000, 0.85, 1e-6);
+        assert!(used > 1 && used < 1000, "used {used} iterations");
+        assert_eq!(scores, pagerank(&g, used, 0.85));
+    }
+
+    #[test]
+    fn pagerank_until_empty_graph() {
+        let (scores, used) = pagerank_until(&Graph::new(vec![], vec![]), 10, 0.85, 1e-6);
+        assert!(scores.is_empty());
+        assert_eq!(used, 0);
+    }
+
+    #[test]
+    fn pagerank_until_zero_tolerance_runs_to_max() {
+        let g = triangle_graph();
+        let (_, used) = pagerank_until(&g, 7, 0.85, 0.0);
+        assert_eq!(used, 7);
+    }
+
+    #[test]
+    fn personalized_pagerank_favours_seed_neighbourhood() {
+        // Path 1-2-3-4-5-6: globally the middle ranks highest
+        let nodes = (1..=6).map(node).collect();
+        let edges = (1..6)
+            .map(|i| Edge {
+                source: i,
+                target: i + 1,
+                weight: 1.0,
+            })
+            .collect();
+        let g = Graph::new(nodes, edges);
+        let global = pagerank(&g, 50, 0.85);
+        let seeded = personalized_pagerank(&g, &[1], 50, 0.85).unwrap();
+
+        let sum: f32 = seeded.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-4);
+        // The seed and its neighbour gain, the far end loses
+        assert!(seeded[0] > global[0] && seeded[1] > global[1]);
+        assert!(seeded[5] < global[5] && seeded[4] < global[4]);
+        assert!(seeded[0] > seeded[3] && seeded[1] > seeded[5]);
+    }
+
+    #[test]
+    fn personalized_pagerank_falls_back_to_uniform() {
+        let g = triangle_graph();
+        let global = pagerank(&g, 20, 0.85);
+        assert_eq!(personalized_pagerank(&g, &[], 20, 0.85), Ok(global.clone()));
+        // Seeding every node is the uniform case
+        let all = personalized_pagerank(&g, &[1, 2, 3], 20, 0.85).unwrap();
+        for (a, b) in all.iter().zip(&global) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn personalized_pagerank_rejects_unknown_seeds() {
+        let g = triangle_graph();
+        assert_eq!(
+            personalized_pagerank(&g, &[42], 20, 0.85),
+            Err("unknown seed node id 42".to_string())
+        );
+        // A typo among valid seeds is still an error, naming the first one
+        assert_eq!(
+            personalized_pagerank(&g, &[1, 7, 9], 20, 0.85),
+            Err("unknown seed node id 7".to_string())
+        );
+    }
+
     #[test]
     fn stubs_return_empty() {
         let g = triangle_graph();
-        assert!(louvain(&g).is_empty());
-        assert!(shortest_path(&g, 1, 3).is_none());
         assert!(betweenness_centrality(&g).is_empty());
     }
+
+    #[test]
+    fn pagerank_state_matches_and_reuses_buffers() {
+        let nodes = (0..6).map(node).collect();
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (4, 5)]
+            .into_iter()
+            .map(|(source, target)| Edge {
+                source,
+                target,
+                weight: 1.0,
+            })
+            .collect();
+        let g = Graph::new(nodes, edges);
+        let expected = pagerank(&g, 30, 0.85);
+
+        let mut state = PageRankState::new();
+        assert!(state.scores().is_empty());
+        assert_eq!(state.run(&g, 30, 0.85), expected.as_slice());
+        // Undirected, so node 2 lists both ends of its edges in edge order
+        assert_eq!(state.out_edges.of(2), &[(1, 1.0), (0, 1.0), (3, 1.0)]);
+        let buffers = |state: &PageRankState| {
+            let mut ptrs = [state.scores.as_ptr(), state.next.as_ptr()];
+            ptrs.sort();
+            let edges = &state.out_edges;
+            (
+                ptrs,
+                state.out_weight.as_ptr(),
+                edges.offsets.as_ptr(),
+                edges.targets.as_ptr(),
+            )
+        };
+        let before = buffers(&state);
+        // Odd and even iteration counts leave the result in either buffer
+        for iterations in [30, 7, 30] {
+            let scores = state.run(&g, iterations, 0.85).to_vec();
+            assert_eq!(scores, pagerank(&g, iterations, 0.85));
+            assert_eq!(buffers(&state), before);
+        }
+
+        // A smaller graph fits in the same allocations
+        let small = Graph::new((0..3).map(node).collect(), vec![]);
+        assert_eq!(
+            state.run(&small, 5, 0.85),
+            pagerank(&small, 5, 0.85).as_slice()
+        );
+        assert_eq!(buffers(&state), before);
+        assert!(state.run(&Graph::new(vec![], vec![]), 5, 0.85).is_empty());
+    }
 }
@@ -0,0 +1,72 @@
+use super::paths::bfs_distances;
+use crate::graph::types::Graph;
+
+/// Closeness centrality over hop distances, treating edges as undirected.
+///
+/// Uses the Wasserman–Faust form `((r - 1) / (n - 1)) * ((r - 1) / sum)`,
+/// where `r` counts the nodes reachable from (and including) the node and
+/// `sum` is their total distance. Scaling by the reachable fraction keeps
+/// small components from scoring as high as well-connected nodes, and
+/// isolated nodes score `0.0`. Index-aligned with graph.nodes().
+pub fn closeness_centrality(graph: &Graph) -> Vec<f32> {
+    let n = graph.node_count();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+
+    graph
+        .nodes()
+        .iter()
+        .map(|node| {
+            let (reached, total) = bfs_distances(graph, node.id)
+                .into_iter()
+                .flatten()
+                .fold((0usize, 0u64), |(r, t), d| (r + 1, t + d as u64));
+            let others = reached - 1;
+            if others == 0 {
+                return 0.0;
+            }
+            let fraction = others as f64 / (n - 1) as f64;
+            (fraction * others as f64 / total as f64) as f32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{edge, node};
+
+    #[test]
+    fn closeness_path_middle_highest() {
+        let nodes = (0..5).map(node).collect();
+        let edges = (0..4).map(|i| edge(i, i + 1)).collect();
+        let scores = closeness_centrality(&Graph::new(nodes, edges));
+        // Middle node: distances 2+1+1+2 = 6 to four others
+        assert!((scores[2] - 4.0 / 6.0).abs() < 1e-6);
+        assert!((scores[0] - 4.0 / 10.0).abs() < 1e-6);
+        assert!(scores[2] > scores[1] && scores[1] > scores[0]);
+        assert_eq!(scores[0], scores[4]);
+    }
+
+    #[test]
+    fn closeness_disconnected() {
+        // Pair {0, 1} plus isolated node 2
+        let nodes = (0..3).map(node).collect();
+        let scores = closeness_centrality(&Graph::new(nodes, vec![edge(0, 1)]));
+        // One of two other nodes reachable at distance 1
+        assert!((scores[0] - 0.5).abs() < 1e-6);
+        assert_eq!(scores[2], 0.0);
+        assert!(scores.iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn closeness_tiny_graphs() {
+        assert!(closeness_centrality(&Graph::new(vec![], vec![])).is_empty());
+        assert_eq!(
+            closeness_centrality(&Graph::new(vec![node(1)], vec![])),
+            vec![0.0]
+        );
+    }
+}
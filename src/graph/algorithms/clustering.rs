@@ -0,0 +1,133 @@
+use crate::graph::types::Graph;
+
+/// Total number of triangles in the undirected interpretation of the graph.
+/// Self-loops and parallel edges are ignored.
+pub fn triangle_count(graph: &Graph) -> usize {
+    triangles_per_node(&simple_neighbors(graph))
+        .iter()
+        .sum::<usize>()
+        / 3
+}
+
+/// Fraction of each node's neighbour pairs that are themselves connected,
+/// in `[0, 1]`. Nodes with fewer than two distinct neighbours score `0.0`.
+/// Index-aligned with graph.nodes().
+pub fn local_clustering_coefficient(graph: &Graph) -> Vec<f32> {
+    let neighbors = simple_neighbors(graph);
+    triangles_per_node(&neighbors)
+        .into_iter()
+        .zip(&neighbors)
+        .map(|(t, adj)| {
+            let k = adj.len();
+            if k < 2 {
+                0.0
+            } else {
+                (2 * t) as f32 / (k * (k - 1)) as f32
+            }
+        })
+        .collect()
+}
+
+/// Triangles through each node, counting each triangle once per corner.
+fn triangles_per_node(neighbors: &[Vec<usize>]) -> Vec<usize> {
+    let n = neighbors.len();
+    let mut counts = vec![0usize; n];
+    let mut marked = vec![false; n];
+
+    for u in 0..n {
+        for &v in &neighbors[u] {
+            marked[v] = true;
+        }
+        // Visit each triangle once, from its lowest index u with u < v < w
+        for &v in neighbors[u].iter().filter(|&&v| v > u) {
+            for &w in neighbors[v].iter().filter(|&&w| w > v) {
+                if marked[w] {
+                    counts[u] += 1;
+                    counts[v] += 1;
+                    counts[w] += 1;
+                }
+            }
+        }
+        for &v in &neighbors[u] {
+            marked[v] = false;
+        }
+    }
+    counts
+}
+
+/// Sorted, de-duplicated neighbour indices with self-loops removed.
+fn simple_neighbors(graph: &Graph) -> Vec<Vec<usize>> {
+    (0..graph.node_count())
+        .map(|i| {
+            let mut adj: Vec<usize> = graph
+                .neighbor_indices(i)
+                .iter()
+                .copied()
+                .filter(|&j| j != i)
+                .collect();
+            adj.sort_unstable();
+            adj.dedup();
+            adj
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{edge, node};
+
+    #[test]
+    fn single_triangle() {
+        let nodes = (0..3).map(node).collect();
+        let g = Graph::new(nodes, vec![edge(0, 1), edge(1, 2), edge(2, 0)]);
+        assert_eq!(triangle_count(&g), 1);
+        assert_eq!(local_clustering_coefficient(&g), vec![1.0; 3]);
+    }
+
+    #[test]
+    fn star_has_no_triangles() {
+        let nodes = (0..5).map(node).collect();
+        let edges = (1..5).map(|i| edge(0, i)).collect();
+        let g = Graph::new(nodes, edges);
+        assert_eq!(triangle_count(&g), 0);
+        assert_eq!(local_clustering_coefficient(&g), vec![0.0; 5]);
+    }
+
+    #[test]
+    fn ignores_duplicates_and_self_loops() {
+        // Triangle 0-1-2 with a pendant node 3 hanging off 0
+        let nodes = (0..4).map(node).collect();
+        let edges = vec![
+            edge(0, 1),
+            edge(1, 0),
+            edge(1, 2),
+            edge(2, 0),
+            edge(0, 0),
+            edge(0, 3),
+        ];
+        let g = Graph::new(nodes, edges);
+        assert_eq!(triangle_count(&g), 1);
+        let cc = local_clustering_coefficient(&g);
+        // Node 0 has neighbours {1, 2, 3}: one linked pair out of three
+        assert!((cc[0] - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(cc[1], 1.0);
+        assert_eq!(cc[3], 0.0);
+    }
+
+    #[test]
+    fn complete_graph() {
+        let nodes = (0..5).map(node).collect();
+        let mut edges = Vec::new();
+        for a in 0..5 {
+            for b in a + 1..5 {
+                edges.push(edge(a, b));
+            }
+        }
+        let g = Graph::new(nodes, edges);
+        // C(5, 3)
+        assert_eq!(triangle_count(&g), 10);
+        assert!(local_clustering_coefficient(&g).iter().all(|&c| c == 1.0));
+    }
+}
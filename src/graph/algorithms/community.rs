@@ -0,0 +1,533 @@
+use crate::graph::types::Graph;
+use crate::rng::SplitMix64;
+
+/// Louvain community detection.
+///
+/// Treats the graph as undirected and weighted by `Edge::weight`. Each level
+/// greedily moves nodes to the neighbouring community with the largest
+/// modularity gain, then collapses communities into super-nodes and repeats
+/// until no node moves. Returns a dense community ID per node (index-aligned
+/// with graph.nodes()), numbered in order of first appearance.
+pub fn louvain(graph: &Graph) -> Vec<usize> {
+    let n = graph.node_count();
+    if n == 0 {
+        return vec![];
+    }
+
+    let mut level = LevelGraph::from_graph(graph);
+    // Community of each original node in the current level's node space
+    let mut membership: Vec<usize> = (0..n).collect();
+
+    loop {
+        let (communities, moved) = level.local_moving();
+        if !moved {
+            break;
+        }
+        let (dense, count) = renumber(&communities);
+        for m in membership.iter_mut() {
+            *m = dense[*m];
+        }
+        level = level.aggregate(&dense, count);
+    }
+
+    renumber(&membership).0
+}
+
+/// Modularity Q of a partition: the fraction of edge weight inside
+/// communities minus what a random graph with the same degrees would put
+/// there. Uses the same undirected, `Edge::weight`-weighted view as
+/// `louvain`. `communities` is index-aligned with graph.nodes(); the labels
+/// themselves are arbitrary. Ranges over `[-0.5, 1)`; 0.0 for a graph with
+/// no edge weight.
+pub fn modularity(graph: &Graph, communities: &[usize]) -> f32 {
+    debug_assert_eq!(communities.len(), graph.node_count());
+    LevelGraph::from_graph(graph).modularity(communities) as f32
+}
+
+/// Label propagation community detection: a faster, rougher alternative to
+/// `louvain` for interactive use.
+///
+/// Every node starts in its own community. Each round visits the nodes in
+/// a fresh shuffled order and moves each to the label most common among
+/// its undirected neighbours (self-loops ignored), updating in place.
+/// A node keeps its label when that is among the most common; other ties
+/// are broken at random. Stops after a round in which nothing changes, or
+/// after `max_iters` rounds. The same `seed` always gives the same result.
+/// Returns a dense community ID per node (index-aligned with
+/// graph.nodes()), numbered in order of first appearance.
+pub fn label_propagation(graph: &Graph, max_iters: usize, seed: u64) -> Vec<usize> {
+    let n = graph.node_count();
+    let mut labels: Vec<usize> = (0..n).collect();
+    let mut order: Vec<usize> = (0..n).collect();
+    let mut rng = SplitMix64(seed);
+    // Neighbour label tallies, reset through `seen` after each node
+    let mut counts = vec![0usize; n];
+    let mut seen = Vec::new();
+    let mut best = Vec::new();
+
+    for _ in 0..max_iters {
+        rng.shuffle(&mut order);
+        let mut changed = false;
+        for &i in &order {
+            for &j in graph.neighbor_indices(i) {
+                if j != i {
+                    if counts[labels[j]] == 0 {
+                        seen.push(labels[j]);
+                    }
+                    counts[labels[j]] += 1;
+                }
+            }
+            let top = seen.iter().map(|&l| counts[l]).max().unwrap_or(0);
+            if top > 0 && counts[labels[i]] < top {
+                best.clear();
+                best.extend(seen.iter().copied().filter(|&l| counts[l] == top));
+                labels[i] = best[rng.below(best.len())];
+                changed = true;
+            }
+            for l in seen.drain(..) {
+                counts[l] = 0;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    renumber(&labels).0
+}
+
+/// Weighted undirected graph used for one Louvain level. Self-loops are
+/// stored as `2 * w` on the diagonal so that `degree[i]` is the row sum.
+struct LevelGraph {
+    adjacency: Vec<Vec<(usize, f64)>>,
+    degree: Vec<f64>,
+    total_weight: f64,
+}
+
+impl LevelGraph {
+    fn from_graph(graph: &Graph) -> Self {
+        let n = graph.node_count();
+        let mut adjacency = vec![Vec::new(); n];
+        for edge in graph.edges() {
+            if let (Some(i), Some(j)) =
+                (graph.node_index(edge.source), graph.node_index(edge.target))
+            {
+                let w = edge.weight as f64;
+                if i == j {
+                    adjacency[i].push((i, 2.0 * w));
+                } else {
+                    adjacency[i].push((j, w));
+                    adjacency[j].push((i, w));
+                }
+            }
+        }
+        Self::from_adjacency(adjacency)
+    }
+
+    fn from_adjacency(adjacency: Vec<Vec<(usize, f64)>>) -> Self {
+        let degree: Vec<f64> = adjacency
+            .iter()
+            .map(|row| row.iter().map(|&(_, w)| w).sum())
+            .collect();
+        let total_weight = degree.iter().sum();
+        Self {
+            adjacency,
+            degree,
+            total_weight,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    fn modularity(&self, community: &[usize]) -> f64 {
+        if self.total_weight <= 0.0 {
+            return 0.0;
+        }
+        let count = community.iter().max().map_or(0, |&m| m + 1);
+        let mut inside = vec![0.0f64; count];
+        let mut totals = vec![0.0f64; count];
+        for (i, &c) in community.iter().enumerate().take(self.len()) {
+            totals[c] += self.degree[i];
+            inside[c] += self.adjacency[i]
+                .iter()
+                .filter(|&&(j, _)| community.get(j) == Some(&c))
+                .map(|&(_, w)| w)
+                .sum::<f64>();
+        }
+        let m2 = self.total_weight;
+        inside
+            .iter()
+            .zip(&totals)
+            .map(|(&w_in, &tot)| w_in / m2 - (tot / m2).powi(2))
+            .sum()
+    }
+
+    /// Repeats local-moving sweeps in node order until a sweep makes no
+    /// moves. Returns the community per node and whether anything moved.
+    fn local_moving(&self) -> (Vec<usize>, bool) {
+        let mut moving = LocalMoving::new(self);
+        while moving.sweep(self) {}
+        (moving.community, moving.moved_any)
+    }
+
+    /// Collapses each community into a single node, summing edge weights.
+    fn aggregate(&self, dense: &[usize], count: usize) -> Self {
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for (i, &c) in dense.iter().enumerate() {
+            members[c].push(i);
+        }
+
+        let mut rows: Vec<Vec<(usize, f64)>> = vec![Vec::new(); count];
+        let mut scratch = vec![0.0f64; count];
+        let mut touched = Vec::new();
+
+        for (c, row) in rows.iter_mut().enumerate() {
+            for &i in &members[c] {
+                for &(j, w) in &self.adjacency[i] {
+                    let d = dense[j];
+                    if scratch[d] == 0.0 {
+                        touched.push(d);
+                    }
+                    scratch[d] += w;
+                }
+            }
+            touched.sort_unstable();
+            for &d in &touched {
+                row.push((d, scratch[d]));
+                scratch[d] = 0.0;
+            }
+            touched.clear();
+        }
+
+        Self::from_adjacency(rows)
+    }
+}
+
+/// Local-moving progress on one `LevelGraph`: the community per node and
+/// each community's total degree, carried between sweeps.
+struct LocalMoving {
+    community: Vec<usize>,
+    totals: Vec<f64>,
+    /// Per-community scratch, zeroed again after each node
+    weight_to: Vec<f64>,
+    touched: Vec<usize>,
+    moved_any: bool,
+}
+
+impl LocalMoving {
+    fn new(level: &LevelGraph) -> Self {
+        let n = level.len();
+        Self {
+            community: (0..n).collect(),
+            totals: level.degree.clone(),
+            weight_to: vec![0.0; n],
+            touched: Vec::new(),
+            moved_any: false,
+        }
+    }
+
+    /// One pass over the nodes in order, moving each to the neighbouring
+    /// community with the largest modularity gain. Returns whether any
+    /// node moved.
+    fn sweep(&mut self, level: &LevelGraph) -> bool {
+        if level.total_weight <= 0.0 {
+            return false;
+        }
+        let mut moved = false;
+        for i in 0..level.len() {
+            let current = self.community[i];
+            let k_i = level.degree[i];
+
+            for &(j, w) in &level.adjacency[i] {
+                if j == i {
+                    continue;
+                }
+                let c = self.community[j];
+                if self.weight_to[c] == 0.0 {
+                    self.touched.push(c);
+                }
+                self.weight_to[c] += w;
+            }
+
+            self.totals[current] -= k_i;
+            let totals = &self.totals;
+            let gain = |c: usize, w_in: f64| w_in - totals[c] * k_i / level.total_weight;
+
+            let mut best = current;
+            let mut best_gain = gain(current, self.weight_to[current]);
+            for &c in &self.touched {
+                let g = gain(c, self.weight_to[c]);
+                // Small margin keeps floating-point noise from causing churn
+                if g > best_gain + 1e-12 {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+
+            self.totals[best] += k_i;
+            if best != current {
+                self.community[i] = best;
+                moved = true;
+            }
+
+            for &c in &self.touched {
+                self.weight_to[c] = 0.0;
+            }
+            self.weight_to[current] = 0.0;
+            self.touched.clear();
+        }
+        self.moved_any |= moved;
+        moved
+    }
+}
+
+/// Louvain run one local-moving sweep at a time, so a large graph can be
+/// partitioned across animation frames without blocking the main thread.
+/// Stepping to completion gives exactly `louvain`'s partition.
+pub struct LouvainState {
+    level: LevelGraph,
+    /// Node of `level` each original node currently belongs to
+    membership: Vec<usize>,
+    moving: LocalMoving,
+    done: bool,
+}
+
+impl LouvainState {
+    pub fn new(graph: &Graph) -> Self {
+        let level = LevelGraph::from_graph(graph);
+        let moving = LocalMoving::new(&level);
+        Self {
+            membership: (0..graph.node_count()).collect(),
+            done: graph.node_count() == 0,
+            level,
+            moving,
+        }
+    }
+
+    /// Runs one local-moving sweep over the current level, collapsing the
+    /// level once a sweep moves nothing. Returns true once the partition
+    /// is final; later calls do nothing. Each sweep is O(V + E) in the
+    /// current level, which shrinks as communities merge.
+    pub fn step(&mut self) -> bool {
+        if self.done || self.moving.sweep(&self.level) {
+            return self.done;
+        }
+        // This level has converged; a level where nothing moved is the last
+        if !self.moving.moved_any {
+            self.done = true;
+            return true;
+        }
+        let (dense, count) = renumber(&self.moving.community);
+        for m in self.membership.iter_mut() {
+            *m = dense[*m];
+        }
+        self.level = self.level.aggregate(&dense, count);
+        self.moving = LocalMoving::new(&self.level);
+        false
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// The partition so far, numbered like `louvain`'s: a dense community
+    /// ID per node, index-aligned with graph.nodes(). Meaningful mid-run,
+    /// e.g. to preview progress, and equal to `louvain` once done.
+    pub fn result(&self) -> Vec<usize> {
+        let labels: Vec<usize> = self
+            .membership
+            .iter()
+            .map(|&m| self.moving.community[m])
+            .collect();
+        renumber(&labels).0
+    }
+}
+
+/// Maps arbitrary labels to dense IDs `0..k` in order of first appearance.
+fn renumber(labels: &[usize]) -> (Vec<usize>, usize) {
+    let mut mapping = vec![usize::MAX; labels.iter().max().map_or(0, |&m| m + 1)];
+    let mut next = 0;
+    let dense = labels
+        .iter()
+        .map(|&l| {
+            if mapping[l] == usize::MAX {
+                mapping[l] = next;
+                next += 1;
+            }
+            mapping[l]
+        })
+        .collect();
+    (dense, next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{edge, node};
+
+    /// Two 4-cliques {0..3} and {4..7} joined by the bridge 3-4.
+    fn two_cliques() -> Graph {
+        let nodes = (0..8).map(node).collect();
+        let mut edges = Vec::new();
+        for base in [0, 4] {
+            for a in base..base + 4 {
+                for b in a + 1..base + 4 {
+                    edges.push(edge(a, b));
+                }
+            }
+        }
+        edges.push(edge(3, 4));
+        Graph::new(nodes, edges)
+    }
+
+    #[test]
+    fn louvain_empty_graph() {
+        let g = Graph::new(vec![], vec![]);
+        assert!(louvain(&g).is_empty());
+    }
+
+    #[test]
+    fn louvain_two_cliques() {
+        let g = two_cliques();
+        let communities = louvain(&g);
+        assert_eq!(communities.len(), 8);
+        assert!(communities[..4].iter().all(|&c| c == communities[0]));
+        assert!(communities[4..].iter().all(|&c| c == communities[4]));
+        assert_ne!(communities[0], communities[4]);
+        assert_eq!(communities, vec![0, 0, 0, 0, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn louvain_isolated_nodes_are_singletons() {
+        let nodes = (0..4).map(node).collect();
+        let g = Graph::new(nodes, vec![edge(0, 1)]);
+        let communities = louvain(&g);
+        assert_eq!(communities[0], communities[1]);
+        assert_ne!(communities[2], communities[0]);
+        assert_ne!(communities[3], communities[0]);
+        assert_ne!(communities[2], communities[3]);
+    }
+
+    #[test]
+    fn louvain_no_edges() {
+        let nodes = (0..3).map(node).collect();
+        let g = Graph::new(nodes, vec![]);
+        assert_eq!(louvain(&g), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn louvain_ring_of_cliques() {
+        // Six 5-cliques, each linked to the next by a single edge
+        let k = 5u32;
+        let rings = 6u32;
+        let nodes = (0..k * rings).map(node).collect();
+        let mut edges = Vec::new();
+        for r in 0..rings {
+            let base = r * k;
+            for a in base..base + k {
+                for b in a + 1..base + k {
+                    edges.push(edge(a, b));
+                }
+            }
+            edges.push(edge(base + k - 1, ((r + 1) % rings) * k));
+        }
+        let communities = louvain(&Graph::new(nodes, edges));
+
+        for r in 0..rings as usize {
+            let block = &communities[r * k as usize..(r + 1) * k as usize];
+            assert!(block.iter().all(|&c| c == block[0]), "clique {r} split");
+        }
+        let distinct: std::collections::HashSet<_> = communities.iter().collect();
+        assert_eq!(distinct.len(), rings as usize);
+    }
+
+    #[test]
+    fn stepped_louvain_matches_one_shot() {
+        let ring = {
+            let nodes = (0..30).map(node).collect();
+            let mut edges = Vec::new();
+            for r in 0..6 {
+                let base = r * 5;
+                for a in base..base + 5 {
+                    for b in a + 1..base + 5 {
+                        edges.push(edge(a, b));
+                    }
+                }
+                edges.push(edge(base + 4, ((r + 1) % 6) * 5));
+            }
+            Graph::new(nodes, edges)
+        };
+        let no_edges = Graph::new((0..3).map(node).collect(), vec![]);
+        let empty = Graph::new(vec![], vec![]);
+
+        for g in [two_cliques(), ring, no_edges, empty] {
+            let mut state = LouvainState::new(&g);
+            let mut steps = 0;
+            while !state.step() {
+                steps += 1;
+                assert!(steps < 100, "never finished");
+                // Every intermediate partition covers every node
+                assert_eq!(state.result().len(), g.node_count());
+            }
+            assert!(state.is_done());
+            assert_eq!(state.result(), louvain(&g));
+            // Stepping a finished run changes nothing
+            assert!(state.step());
+            assert_eq!(state.result(), louvain(&g));
+        }
+    }
+
+    #[test]
+    fn label_propagation_two_cliques() {
+        // With the seed fixed the tie-breaks are fixed too, so this is
+        // stable; other seeds can occasionally merge across the bridge
+        let g = two_cliques();
+        let labels = label_propagation(&g, 100, 7);
+        assert_eq!(labels, vec![0, 0, 0, 0, 1, 1, 1, 1]);
+        assert_eq!(label_propagation(&g, 100, 7), labels);
+    }
+
+    #[test]
+    fn label_propagation_edge_cases() {
+        assert!(label_propagation(&Graph::new(vec![], vec![]), 10, 1).is_empty());
+        // Isolated nodes and zero rounds both leave every node alone
+        let nodes = (0..3).map(node).collect();
+        let g = Graph::new(nodes, vec![edge(0, 0)]);
+        assert_eq!(label_propagation(&g, 10, 1), vec![0, 1, 2]);
+        assert_eq!(
+            label_propagation(&two_cliques(), 0, 1),
+            (0..8).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn modularity_prefers_clique_partition() {
+        let g = two_cliques();
+        let cliques = [0, 0, 0, 0, 1, 1, 1, 1];
+        let mixed = [0, 1, 0, 1, 1, 0, 1, 0];
+        let q = modularity(&g, &cliques);
+        assert!(q > modularity(&g, &mixed));
+        // 13 edges: each clique keeps 6 inside and has total degree 13
+        let expected = 2.0 * (6.0 / 13.0 - 0.25);
+        assert!((q - expected).abs() < 1e-6, "q = {q}");
+        // Louvain finds exactly that split; one big community scores 0
+        assert_eq!(modularity(&g, &louvain(&g)), q);
+        assert!(modularity(&g, &[0; 8]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn modularity_uses_weights() {
+        let path = |middle: f32| {
+            let mut edges = vec![edge(0, 1), edge(1, 2), edge(2, 3)];
+            edges[1].weight = middle;
+            Graph::new((0..4).map(node).collect(), edges)
+        };
+        let split = [0, 0, 1, 1];
+        // A heavy middle edge makes cutting it worse
+        assert!(modularity(&path(10.0), &split) < modularity(&path(1.0), &split));
+        assert_eq!(modularity(&Graph::new(vec![], vec![]), &[]), 0.0);
+    }
+}
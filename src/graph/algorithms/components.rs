@@ -0,0 +1,258 @@
+use crate::graph::types::Graph;
+
+/// Connected components of the undirected interpretation of the graph.
+/// Returns a component ID per node (index-aligned with graph.nodes()). IDs are
+/// dense (`0..k`) and assigned in order of each component's first node.
+pub fn connected_components(graph: &Graph) -> Vec<usize> {
+    let mut sets = DisjointSet::new(graph.node_count());
+    for edge in graph.edges() {
+        if let (Some(i), Some(j)) = (graph.node_index(edge.source), graph.node_index(edge.target)) {
+            sets.union(i, j);
+        }
+    }
+    sets.dense_labels()
+}
+
+/// Weakly connected components: nodes joined by edges in either direction.
+/// `connected_components` already ignores direction, so this is the same
+/// labelling under the name directed-graph users look for, as the
+/// counterpart to `strongly_connected_components`.
+pub fn weakly_connected_components(graph: &Graph) -> Vec<usize> {
+    connected_components(graph)
+}
+
+/// Strongly connected components, following edge direction via
+/// `out_neighbor_indices` (on an undirected graph these coincide with
+/// `connected_components`). Iterative Tarjan, so deep graphs can't overflow
+/// the wasm stack. IDs are dense (`0..k`) and assigned in order of each
+/// component's first node.
+pub fn strongly_connected_components(graph: &Graph) -> Vec<usize> {
+    const UNVISITED: usize = usize::MAX;
+    let n = graph.node_count();
+    let mut index = vec![UNVISITED; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    // Raw SCC number per node, in Tarjan's completion order
+    let mut component = vec![0; n];
+    let mut next_index = 0;
+    let mut next_component = 0;
+    // Explicit call stack of (node, position in its out-neighbour list)
+    let mut calls: Vec<(usize, usize)> = Vec::new();
+
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        calls.push((root, 0));
+
+        while let Some(&mut (v, ref mut pos)) = calls.last_mut() {
+            let successors = graph.out_neighbor_indices(v);
+            if let Some(&w) = successors.get(*pos) {
+                *pos += 1;
+                if index[w] == UNVISITED {
+                    index[w] = next_index;
+                    lowlink[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if lowlink[v] == index[v] {
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component[w] = next_component;
+                    if w == v {
+                        break;
+                    }
+                }
+                next_component += 1;
+            }
+            if let Some(&(parent, _)) = calls.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[v]);
+            }
+        }
+    }
+
+    // Renumber so IDs follow node order rather than completion order
+    let mut dense = vec![UNVISITED; next_component];
+    let mut next = 0;
+    component
+        .into_iter()
+        .map(|c| {
+            if dense[c] == UNVISITED {
+                dense[c] = next;
+                next += 1;
+            }
+            dense[c]
+        })
+        .collect()
+}
+
+/// Union-find with path halving and union by size.
+pub(super) struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    pub(super) fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    pub(super) fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// Merges the sets containing `a` and `b`. Returns false if they were
+    /// already joined.
+    pub(super) fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut ra, mut rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false;
+        }
+        if self.size[ra] < self.size[rb] {
+            std::mem::swap(&mut ra, &mut rb);
+        }
+        self.parent[rb] = ra;
+        self.size[ra] += self.size[rb];
+        true
+    }
+
+    /// Dense set ID per element, numbered by first appearance.
+    pub(super) fn dense_labels(&mut self) -> Vec<usize> {
+        let n = self.parent.len();
+        let mut label_of_root = vec![usize::MAX; n];
+        let mut next = 0;
+        (0..n)
+            .map(|i| {
+                let root = self.find(i);
+                if label_of_root[root] == usize::MAX {
+                    label_of_root[root] = next;
+                    next += 1;
+                }
+                label_of_root[root]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::{Edge, GraphKind};
+    use crate::test_utils::{edge, node};
+
+    #[test]
+    fn components_empty_graph() {
+        let g = Graph::new(vec![], vec![]);
+        assert!(connected_components(&g).is_empty());
+    }
+
+    #[test]
+    fn components_two_triangles() {
+        let nodes = (0..6).map(node).collect();
+        let edges = vec![
+            edge(0, 1),
+            edge(1, 2),
+            edge(2, 0),
+            edge(5, 4),
+            edge(4, 3),
+            edge(3, 5),
+        ];
+        let g = Graph::new(nodes, edges);
+        assert_eq!(connected_components(&g), vec![0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn components_fully_connected() {
+        let nodes = (0..4).map(node).collect();
+        let edges = vec![edge(3, 2), edge(2, 1), edge(1, 0)];
+        let g = Graph::new(nodes, edges);
+        assert_eq!(connected_components(&g), vec![0; 4]);
+    }
+
+    #[test]
+    fn components_ids_follow_node_order() {
+        // Node 0 is isolated, so it gets ID 0 even though edges come first
+        let nodes = (0..4).map(node).collect();
+        let g = Graph::new(nodes, vec![edge(2, 3), edge(1, 3)]);
+        assert_eq!(connected_components(&g), vec![0, 1, 1, 1]);
+    }
+
+    fn directed(n: u32, edges: Vec<Edge>) -> Graph {
+        Graph::with_kind((0..n).map(node).collect(), edges, GraphKind::Directed)
+    }
+
+    #[test]
+    fn scc_directed_cycle() {
+        let g = directed(4, (0..4).map(|i| edge(i, (i + 1) % 4)).collect());
+        assert_eq!(strongly_connected_components(&g), vec![0; 4]);
+    }
+
+    #[test]
+    fn scc_dag_is_singletons() {
+        let g = directed(4, vec![edge(0, 1), edge(0, 2), edge(1, 3), edge(2, 3)]);
+        assert_eq!(strongly_connected_components(&g), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn scc_mixed() {
+        // Cycle {1, 2, 3} feeding the 2-cycle {4, 5}; 0 only points in
+        let edges = vec![
+            edge(0, 1),
+            edge(1, 2),
+            edge(2, 3),
+            edge(3, 1),
+            edge(3, 4),
+            edge(4, 5),
+            edge(5, 4),
+        ];
+        let g = directed(6, edges);
+        assert_eq!(strongly_connected_components(&g), vec![0, 1, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn scc_long_chain_does_not_overflow() {
+        let n = 100_000;
+        let g = directed(n, (0..n - 1).map(|i| edge(i, i + 1)).collect());
+        let labels = strongly_connected_components(&g);
+        assert_eq!(labels[n as usize - 1], n as usize - 1);
+    }
+
+    #[test]
+    fn scc_undirected_matches_components() {
+        let nodes = (0..5).map(node).collect();
+        let g = Graph::new(nodes, vec![edge(0, 1), edge(3, 4)]);
+        assert_eq!(strongly_connected_components(&g), connected_components(&g));
+    }
+
+    #[test]
+    fn weak_components_ignore_direction() {
+        // 0 -> 1 -> 2 -> 0 is one SCC; 3 and 4 hang off it one way only,
+        // so the graph is a single weak component but three SCCs
+        let nodes = (0..6).map(node).collect();
+        let edges = vec![edge(0, 1), edge(1, 2), edge(2, 0), edge(2, 3), edge(4, 0)];
+        let g = Graph::with_kind(nodes, edges, GraphKind::Directed);
+        assert_eq!(weakly_connected_components(&g), vec![0, 0, 0, 0, 0, 1]);
+        assert_eq!(strongly_connected_components(&g), vec![0, 0, 0, 1, 2, 3]);
+    }
+}
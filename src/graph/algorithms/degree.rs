@@ -0,0 +1,128 @@
+use crate::graph::types::Graph;
+use std::collections::BTreeMap;
+
+/// `(degree, node count)` pairs sorted by degree, skipping degrees no node
+/// has. Degrees are undirected neighbour counts from the adjacency index
+/// (clamped to `u16::MAX`), not the stored `Node::degree`. Empty for an
+/// empty graph.
+pub fn degree_histogram(graph: &Graph) -> Vec<(u16, usize)> {
+    let mut counts = BTreeMap::new();
+    for degree in degrees(graph) {
+        *counts.entry(degree).or_insert(0) += 1;
+    }
+    counts.into_iter().collect()
+}
+
+/// Largest undirected degree, or None for an empty graph.
+pub fn max_degree(graph: &Graph) -> Option<u16> {
+    degrees(graph).max()
+}
+
+/// Smallest undirected degree, or None for an empty graph.
+pub fn min_degree(graph: &Graph) -> Option<u16> {
+    degrees(graph).min()
+}
+
+/// Mean undirected degree, or `0.0` for an empty graph.
+pub fn average_degree(graph: &Graph) -> f32 {
+    let n = graph.node_count();
+    if n == 0 {
+        return 0.0;
+    }
+    degrees(graph).map(|d| d as f64).sum::<f64>() as f32 / n as f32
+}
+
+/// Strength (summed incident edge weight, see `Graph::node_strength`) of
+/// every node, aligned with `graph.nodes()`.
+pub fn strength_distribution(graph: &Graph) -> Vec<f32> {
+    let edges = graph.edges();
+    (0..graph.node_count())
+        .map(|i| {
+            graph
+                .incident_edge_indices(i)
+                .iter()
+                .map(|&e| edges[e].weight)
+                .sum()
+        })
+        .collect()
+}
+
+fn degrees(graph: &Graph) -> impl Iterator<Item = u16> + '_ {
+    (0..graph.node_count()).map(|i| graph.neighbor_indices(i).len().min(u16::MAX as usize) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::{Edge, Node};
+    use crate::test_utils::edge;
+
+    fn make_node(id: u32) -> Node {
+        Node {
+            id,
+            // Deliberately wrong: only the adjacency counts
+            degree: 99,
+            ..Node::default()
+        }
+    }
+
+    #[test]
+    fn star_plus_isolated_node() {
+        // Hub 0 with leaves 1..=4, and 5 on its own
+        let nodes = (0..6).map(make_node).collect();
+        let edges = (1..5).map(|i| edge(0, i)).collect();
+        let g = Graph::new(nodes, edges);
+
+        assert_eq!(degree_histogram(&g), vec![(0, 1), (1, 4), (4, 1)]);
+        assert_eq!(max_degree(&g), Some(4));
+        assert_eq!(min_degree(&g), Some(0));
+        // 8 endpoint slots over 6 nodes
+        assert!((average_degree(&g) - 8.0 / 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn heavy_edges_outweigh_many_light_ones() {
+        // 0 has two heavy edges, 3 has four light ones
+        let nodes = (0..8).map(make_node).collect();
+        let weighted = |source, target, weight| Edge {
+            source,
+            target,
+            weight,
+        };
+        let edges = vec![
+            weighted(0, 1, 5.0),
+            weighted(0, 2, 4.0),
+            weighted(3, 4, 0.5),
+            weighted(3, 5, 0.5),
+            weighted(3, 6, 0.5),
+            weighted(3, 7, 0.5),
+        ];
+        let g = Graph::new(nodes, edges);
+
+        assert!(g.compute_degree(0) < g.compute_degree(3));
+        assert_eq!(g.node_strength(0), 9.0);
+        assert_eq!(g.node_strength(3), 2.0);
+        assert_eq!(g.node_strength(99), 0.0);
+        assert_eq!(
+            strength_distribution(&g),
+            vec![9.0, 5.0, 4.0, 2.0, 0.5, 0.5, 0.5, 0.5]
+        );
+    }
+
+    #[test]
+    fn unweighted_strength_is_degree() {
+        let nodes = (0..4).map(make_node).collect();
+        let g = Graph::new(nodes, vec![edge(0, 1), edge(0, 2), edge(1, 2)]);
+        let degrees: Vec<f32> = (0..4).map(|id| g.compute_degree(id) as f32).collect();
+        assert_eq!(strength_distribution(&g), degrees);
+    }
+
+    #[test]
+    fn empty_graph() {
+        let g = Graph::new(vec![], vec![]);
+        assert!(degree_histogram(&g).is_empty());
+        assert_eq!(max_degree(&g), None);
+        assert_eq!(min_degree(&g), None);
+        assert_eq!(average_degree(&g), 0.0);
+    }
+}
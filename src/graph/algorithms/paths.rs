@@ -0,0 +1,579 @@
+use crate::graph::types::Graph;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// Dijkstra shortest path, treating edges as undirected and weighted by
+/// `Edge::weight` (1.0 for unweighted graphs, so this degrades to fewest
+/// hops). Weights are assumed non-negative.
+/// Returns the node-index path from `source_id` to `target_id` inclusive, or
+/// None if either id is unknown or the target is unreachable.
+pub fn shortest_path(graph: &Graph, source_id: u32, target_id: u32) -> Option<Vec<usize>> {
+    let source = graph.node_index(source_id)?;
+    let target = graph.node_index(target_id)?;
+    best_first(graph, source, target, |_| 0.0).0
+}
+
+/// Work done by a path search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Nodes popped from the frontier and had their edges relaxed.
+    pub expanded: usize,
+}
+
+/// A* shortest path: the same answer as `shortest_path` (same cost, node
+/// indices from source to target inclusive), guided towards the target by
+/// the straight-line distance between node positions.
+///
+/// Layout positions are in arbitrary units, so the distance is scaled by
+/// the smallest weight-per-unit-length over all edges. That keeps the
+/// heuristic admissible (and consistent) for any weights, so results stay
+/// exact; when positions carry no information (all zero, say) the scale is
+/// 0 and this is plain Dijkstra.
+pub fn astar_path(graph: &Graph, source_id: u32, target_id: u32) -> Option<Vec<usize>> {
+    astar_path_with_stats(graph, source_id, target_id).0
+}
+
+/// `astar_path` plus how many nodes the search expanded.
+pub fn astar_path_with_stats(
+    graph: &Graph,
+    source_id: u32,
+    target_id: u32,
+) -> (Option<Vec<usize>>, SearchStats) {
+    let (Some(source), Some(target)) = (graph.node_index(source_id), graph.node_index(target_id))
+    else {
+        return (None, SearchStats::default());
+    };
+    let nodes = graph.nodes();
+    let scale = heuristic_scale(graph);
+    let (tx, ty) = (nodes[target].x, nodes[target].y);
+    best_first(graph, source, target, |i| {
+        scale * (nodes[i].x - tx).hypot(nodes[i].y - ty)
+    })
+}
+
+/// Largest factor `s` such that `s * euclidean length <= weight` for every
+/// edge, or 0 when no edge has a positive length.
+fn heuristic_scale(graph: &Graph) -> f32 {
+    let nodes = graph.nodes();
+    let scale = graph
+        .edges()
+        .iter()
+        .filter_map(|edge| {
+            let (a, b) = (
+                &nodes[graph.node_index(edge.source)?],
+                &nodes[graph.node_index(edge.target)?],
+            );
+            let length = (a.x - b.x).hypot(a.y - b.y);
+            (length > 0.0).then(|| edge.weight / length)
+        })
+        .fold(f32::INFINITY, f32::min);
+    if scale.is_finite() {
+        scale.max(0.0)
+    } else {
+        0.0
+    }
+}
+
+/// Dijkstra ordered by `cost + heuristic(node)`; with a zero heuristic
+/// this is plain Dijkstra. The heuristic must be consistent, as each node
+/// is expanded at most once.
+fn best_first(
+    graph: &Graph,
+    source: usize,
+    target: usize,
+    heuristic: impl Fn(usize) -> f32,
+) -> (Option<Vec<usize>>, SearchStats) {
+    let adjacency = weighted_adjacency(graph);
+    let mut dist = vec![f32::INFINITY; graph.node_count()];
+    let mut prev: Vec<Option<usize>> = vec![None; graph.node_count()];
+    let mut closed = vec![false; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    let mut stats = SearchStats::default();
+
+    dist[source] = 0.0;
+    heap.push(HeapEntry {
+        cost: heuristic(source),
+        node: source,
+    });
+
+    while let Some(HeapEntry { node, .. }) = heap.pop() {
+        if closed[node] {
+            continue; // stale entry
+        }
+        closed[node] = true;
+        stats.expanded += 1;
+        if node == target {
+            break;
+        }
+        for &(next, weight) in &adjacency[node] {
+            let candidate = dist[node] + weight;
+            if candidate < dist[next] {
+                dist[next] = candidate;
+                prev[next] = Some(node);
+                heap.push(HeapEntry {
+                    cost: candidate + heuristic(next),
+                    node: next,
+                });
+            }
+        }
+    }
+
+    if dist[target].is_infinite() {
+        return (None, stats);
+    }
+    (Some(reconstruct_path(&prev, target)), stats)
+}
+
+/// Hop distance from `source_id` to every node (index-aligned with
+/// graph.nodes()), treating edges as undirected. `None` marks unreachable
+/// nodes; an unknown source leaves every entry `None`.
+pub fn bfs_distances(graph: &Graph, source_id: u32) -> Vec<Option<u32>> {
+    match graph.node_index(source_id) {
+        Some(source) => hop_distances(graph, source),
+        None => vec![None; graph.node_count()],
+    }
+}
+
+/// Greatest hop distance from each node to any node it can reach, treating
+/// edges as undirected (index-aligned with graph.nodes()). Unreachable
+/// nodes are ignored, so on a disconnected graph each node's value is
+/// measured within its own component; an isolated node gets `Some(0)`.
+///
+/// Runs a BFS from every node: O(V·(V + E)), so keep it to graphs of a
+/// few thousand nodes.
+pub fn eccentricity(graph: &Graph) -> Vec<Option<u32>> {
+    (0..graph.node_count())
+        .map(|source| hop_distances(graph, source).into_iter().flatten().max())
+        .collect()
+}
+
+/// Largest eccentricity over all nodes: the longest shortest path within
+/// any component. None for an empty graph. Same cost as `eccentricity`.
+pub fn diameter(graph: &Graph) -> Option<u32> {
+    eccentricity(graph).into_iter().flatten().max()
+}
+
+/// Default node cap for `all_pairs_shortest_paths`. The result alone is
+/// `n²` entries of 8 bytes, so 2,000 nodes already need 32 MB.
+pub const DEFAULT_ALL_PAIRS_MAX_NODES: usize = 2_000;
+/// Density at and above which `all_pairs_shortest_paths` switches from a
+/// BFS per node to Floyd-Warshall. On dense graphs both are O(V³), and the
+/// flat matrix's tight loop wins; below this the BFS's O(V·(V + E)) does.
+const ALL_PAIRS_DENSE_THRESHOLD: f32 = 0.25;
+
+/// Hop distance between every pair of nodes, treating edges as
+/// undirected: `result[i][j]` is the distance from node `i` to node `j`
+/// (indices into graph.nodes()), `None` when unreachable. Sparse graphs
+/// run a BFS per node, dense ones (see `Graph::density`) Floyd-Warshall.
+///
+/// The result is quadratic in the node count, so graphs with more than
+/// `max_nodes` nodes (`DEFAULT_ALL_PAIRS_MAX_NODES` is a sensible cap) are
+/// refused with an error instead.
+pub fn all_pairs_shortest_paths(
+    graph: &Graph,
+    max_nodes: usize,
+) -> Result<Vec<Vec<Option<u32>>>, String> {
+    let n = graph.node_count();
+    if n > max_nodes {
+        return Err(format!(
+            "All-pairs shortest paths on {} nodes exceeds the cap of {}",
+            n, max_nodes
+        ));
+    }
+    if graph.density() >= ALL_PAIRS_DENSE_THRESHOLD {
+        Ok(floyd_warshall(graph))
+    } else {
+        Ok((0..n).map(|source| hop_distances(graph, source)).collect())
+    }
+}
+
+fn floyd_warshall(graph: &Graph) -> Vec<Vec<Option<u32>>> {
+    const UNREACHABLE: u32 = u32::MAX;
+    let n = graph.node_count();
+    let mut dist = vec![UNREACHABLE; n * n];
+    for i in 0..n {
+        dist[i * n + i] = 0;
+        for &j in graph.neighbor_indices(i) {
+            if j != i {
+                dist[i * n + j] = 1;
+            }
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            let via = dist[i * n + k];
+            if via == UNREACHABLE {
+                continue;
+            }
+            for j in 0..n {
+                let through = via.saturating_add(dist[k * n + j]);
+                if through < dist[i * n + j] {
+                    dist[i * n + j] = through;
+                }
+            }
+        }
+    }
+    dist.chunks(n.max(1))
+        .take(n)
+        .map(|row| {
+            row.iter()
+                .map(|&d| (d != UNREACHABLE).then_some(d))
+                .collect()
+        })
+        .collect()
+}
+
+fn hop_distances(graph: &Graph, source: usize) -> Vec<Option<u32>> {
+    let mut dist = vec![None; graph.node_count()];
+    let mut queue = VecDeque::new();
+    dist[source] = Some(0);
+    queue.push_back(source);
+
+    while let Some(node) = queue.pop_front() {
+        let next_dist = dist[node].map(|d| d + 1);
+        for &next in graph.neighbor_indices(node) {
+            if dist[next].is_none() {
+                dist[next] = next_dist;
+                queue.push_back(next);
+            }
+        }
+    }
+    dist
+}
+
+/// Fewest-hops path from `source_id` to `target_id` inclusive, as node
+/// indices. Cheaper than `shortest_path` when weights don't matter.
+pub fn bfs_path(graph: &Graph, source_id: u32, target_id: u32) -> Option<Vec<usize>> {
+    let source = graph.node_index(source_id)?;
+    let target = graph.node_index(target_id)?;
+
+    let mut visited = vec![false; graph.node_count()];
+    let mut prev: Vec<Option<usize>> = vec![None; graph.node_count()];
+    let mut queue = VecDeque::new();
+    visited[source] = true;
+    queue.push_back(source);
+
+    while let Some(node) = queue.pop_front() {
+        if node == target {
+            return Some(reconstruct_path(&prev, target));
+        }
+        for &next in graph.neighbor_indices(node) {
+            if !visited[next] {
+                visited[next] = true;
+                prev[next] = Some(node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Undirected `(neighbor_index, weight)` lists, skipping dangling edges.
+fn weighted_adjacency(graph: &Graph) -> Vec<Vec<(usize, f32)>> {
+    let mut adjacency = vec![Vec::new(); graph.node_count()];
+    for edge in graph.edges() {
+        if let (Some(i), Some(j)) = (graph.node_index(edge.source), graph.node_index(edge.target)) {
+            adjacency[i].push((j, edge.weight));
+            if i != j {
+                adjacency[j].push((i, edge.weight));
+            }
+        }
+    }
+    adjacency
+}
+
+fn reconstruct_path(prev: &[Option<usize>], target: usize) -> Vec<usize> {
+    let mut path = vec![target];
+    let mut current = target;
+    while let Some(p) = prev[current] {
+        path.push(p);
+        current = p;
+    }
+    path.reverse();
+    path
+}
+
+/// Min-heap entry: `BinaryHeap` is a max-heap, so the ordering is reversed.
+/// Ties are broken by node index to keep results deterministic.
+#[derive(Clone, Copy, PartialEq)]
+struct HeapEntry {
+    cost: f32,
+    node: usize,
+}
+
+impl Eq for HeapEntry {}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::{Edge, Node};
+    use crate::test_utils::{node, weighted_edge};
+
+    fn chain(n: u32) -> Graph {
+        let nodes = (0..n).map(|i| node(i * 10)).collect();
+        let edges = (0..n - 1)
+            .map(|i| weighted_edge(i * 10, (i + 1) * 10, 1.0))
+            .collect();
+        Graph::new(nodes, edges)
+    }
+
+    #[test]
+    fn shortest_path_chain() {
+        let g = chain(5);
+        assert_eq!(shortest_path(&g, 0, 40), Some(vec![0, 1, 2, 3, 4]));
+        // Undirected, so the reverse works too
+        assert_eq!(shortest_path(&g, 30, 10), Some(vec![3, 2, 1]));
+    }
+
+    #[test]
+    fn shortest_path_unreachable() {
+        let nodes = vec![node(1), node(2), node(3)];
+        let g = Graph::new(nodes, vec![weighted_edge(1, 2, 1.0)]);
+        assert!(shortest_path(&g, 1, 3).is_none());
+        assert!(shortest_path(&g, 1, 99).is_none());
+        assert!(shortest_path(&g, 99, 1).is_none());
+    }
+
+    #[test]
+    fn shortest_path_self() {
+        let g = chain(3);
+        assert_eq!(shortest_path(&g, 10, 10), Some(vec![1]));
+    }
+
+    #[test]
+    fn shortest_path_prefers_lighter_route() {
+        // 1 -> 2 -> 3 costs 2.0; the direct 1 -> 3 edge costs 5.0
+        let nodes = vec![node(1), node(2), node(3)];
+        let edges = vec![
+            weighted_edge(1, 2, 1.0),
+            weighted_edge(2, 3, 1.0),
+            weighted_edge(1, 3, 5.0),
+        ];
+        let g = Graph::new(nodes, edges);
+        assert_eq!(shortest_path(&g, 1, 3), Some(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn bfs_distances_disconnected_node() {
+        let nodes = vec![node(1), node(2), node(3), node(4)];
+        let edges = vec![weighted_edge(1, 2, 1.0), weighted_edge(2, 3, 1.0)];
+        let g = Graph::new(nodes, edges);
+        assert_eq!(bfs_distances(&g, 1), vec![Some(0), Some(1), Some(2), None]);
+        assert_eq!(bfs_distances(&g, 99), vec![None; 4]);
+    }
+
+    #[test]
+    fn bfs_distances_cycle() {
+        // 6-cycle: the opposite node is 3 hops away either way round
+        let nodes = (0..6).map(node).collect();
+        let edges = (0..6).map(|i| weighted_edge(i, (i + 1) % 6, 1.0)).collect();
+        let g = Graph::new(nodes, edges);
+        assert_eq!(
+            bfs_distances(&g, 0),
+            vec![Some(0), Some(1), Some(2), Some(3), Some(2), Some(1)]
+        );
+    }
+
+    #[test]
+    fn bfs_path_ignores_weights() {
+        // Dijkstra takes the two light hops; BFS takes the single heavy one
+        let nodes = vec![node(1), node(2), node(3)];
+        let edges = vec![
+            weighted_edge(1, 2, 1.0),
+            weighted_edge(2, 3, 1.0),
+            weighted_edge(1, 3, 5.0),
+        ];
+        let g = Graph::new(nodes, edges);
+        assert_eq!(bfs_path(&g, 1, 3), Some(vec![0, 2]));
+        assert_eq!(bfs_path(&g, 2, 2), Some(vec![1]));
+    }
+
+    #[test]
+    fn bfs_path_unreachable() {
+        let nodes = vec![node(1), node(2)];
+        let g = Graph::new(nodes, vec![]);
+        assert!(bfs_path(&g, 1, 2).is_none());
+        assert!(bfs_path(&g, 1, 42).is_none());
+    }
+
+    #[test]
+    fn path_graph_diameter_is_its_length() {
+        let g = chain(5);
+        assert_eq!(
+            eccentricity(&g),
+            vec![Some(4), Some(3), Some(2), Some(3), Some(4)]
+        );
+        assert_eq!(diameter(&g), Some(4));
+    }
+
+    #[test]
+    fn disconnected_graph_measures_per_component() {
+        // Path 1-2-3, edge 4-5 and isolated 6
+        let nodes = (1..=6).map(node).collect();
+        let edges = vec![
+            weighted_edge(1, 2, 1.0),
+            weighted_edge(2, 3, 1.0),
+            weighted_edge(4, 5, 1.0),
+        ];
+        let g = Graph::new(nodes, edges);
+        assert_eq!(
+            eccentricity(&g),
+            vec![Some(2), Some(1), Some(2), Some(1), Some(1), Some(0)]
+        );
+        assert_eq!(diameter(&g), Some(2));
+    }
+
+    #[test]
+    fn empty_graph_has_no_diameter() {
+        let g = Graph::new(vec![], vec![]);
+        assert!(eccentricity(&g).is_empty());
+        assert_eq!(diameter(&g), None);
+    }
+
+    /// `side x side` unit grid with 4-neighbour edges; node `r * side + c`
+    /// sits at `(c, r)`.
+    fn grid(side: u32) -> Graph {
+        let nodes = (0..side * side)
+            .map(|i| {
+                let mut node = node(i);
+                node.x = (i % side) as f32;
+                node.y = (i / side) as f32;
+                node
+            })
+            .collect();
+        let mut edges = Vec::new();
+        for i in 0..side * side {
+            if i % side + 1 < side {
+                edges.push(weighted_edge(i, i + 1, 1.0));
+            }
+            if i + side < side * side {
+                edges.push(weighted_edge(i, i + side, 1.0));
+            }
+        }
+        Graph::new(nodes, edges)
+    }
+
+    #[test]
+    fn astar_matches_dijkstra_with_fewer_expansions() {
+        let g = grid(15);
+        // Middle of the left edge to middle of the right edge
+        let (source, target) = (7 * 15, 7 * 15 + 14);
+        let (path, stats) = astar_path_with_stats(&g, source, target);
+        let path = path.unwrap();
+        let (dijkstra, dijkstra_stats) = best_first(&g, source as usize, target as usize, |_| 0.0);
+        assert_eq!(path.len(), dijkstra.unwrap().len());
+        assert_eq!(path.len(), 15);
+        assert!(
+            stats.expanded < dijkstra_stats.expanded,
+            "A* expanded {} vs Dijkstra {}",
+            stats.expanded,
+            dijkstra_stats.expanded
+        );
+    }
+
+    #[test]
+    fn astar_without_positions_is_dijkstra() {
+        // All positions zero: no heuristic, same search as Dijkstra
+        let g = chain(6);
+        assert_eq!(heuristic_scale(&g), 0.0);
+        let (path, stats) = astar_path_with_stats(&g, 0, 50);
+        assert_eq!(path, Some(vec![0, 1, 2, 3, 4, 5]));
+        assert_eq!(stats.expanded, 6);
+        assert_eq!(astar_path(&g, 0, 99), None);
+    }
+
+    #[test]
+    fn astar_stays_exact_when_weights_undercut_distance() {
+        // Two routes from 0 to 3; the geometrically longer one is cheaper.
+        // An unscaled heuristic would overestimate and take 0-3 directly.
+        let mut nodes: Vec<Node> = (0..4).map(node).collect();
+        for (node, (x, y)) in
+            nodes
+                .iter_mut()
+                .zip([(0.0, 0.0), (0.0, 50.0), (100.0, 50.0), (100.0, 0.0)])
+        {
+            node.x = x;
+            node.y = y;
+        }
+        let edges = vec![
+            weighted_edge(0, 3, 10.0),
+            weighted_edge(0, 1, 1.0),
+            weighted_edge(1, 2, 1.0),
+            weighted_edge(2, 3, 1.0),
+        ];
+        let g = Graph::new(nodes, edges);
+        assert_eq!(astar_path(&g, 0, 3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(astar_path(&g, 0, 3), shortest_path(&g, 0, 3));
+    }
+
+    #[test]
+    fn all_pairs_on_tiny_graph() {
+        // Path 0-10-20, plus an isolated node 30
+        let mut g = chain(3);
+        g.add_node(node(30)).unwrap();
+        let dist = all_pairs_shortest_paths(&g, DEFAULT_ALL_PAIRS_MAX_NODES).unwrap();
+        assert_eq!(
+            dist,
+            vec![
+                vec![Some(0), Some(1), Some(2), None],
+                vec![Some(1), Some(0), Some(1), None],
+                vec![Some(2), Some(1), Some(0), None],
+                vec![None, None, None, Some(0)],
+            ]
+        );
+        assert!(
+            all_pairs_shortest_paths(&Graph::new(vec![], vec![]), 0)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn all_pairs_methods_agree() {
+        // Sparse ring with chords, then the same nodes nearly complete
+        let ring: Vec<Edge> = (0..30)
+            .flat_map(|i| {
+                [
+                    weighted_edge(i, (i + 1) % 30, 1.0),
+                    weighted_edge(i, (i + 7) % 30, 1.0),
+                ]
+            })
+            .collect();
+        let dense: Vec<Edge> = (0..30)
+            .flat_map(|i| {
+                (i + 1..30)
+                    .filter(move |j| (i + j) % 3 != 0)
+                    .map(move |j| weighted_edge(i, j, 1.0))
+            })
+            .collect();
+        for edges in [ring, dense] {
+            let g = Graph::new((0..30).map(node).collect(), edges);
+            let bfs: Vec<_> = (0..30).map(|s| hop_distances(&g, s)).collect();
+            assert_eq!(floyd_warshall(&g), bfs);
+            assert_eq!(all_pairs_shortest_paths(&g, 30).unwrap(), bfs);
+        }
+    }
+
+    #[test]
+    fn all_pairs_refuses_graphs_over_the_cap() {
+        let g = chain(5);
+        let err = all_pairs_shortest_paths(&g, 4).unwrap_err();
+        assert!(err.contains("5 nodes"), "{err}");
+        assert!(all_pairs_shortest_paths(&g, 5).is_ok());
+    }
+}
@@ -0,0 +1,206 @@
+use crate::graph::types::Graph;
+use crate::rng::SplitMix64;
+use std::collections::VecDeque;
+
+/// Chance that forest fire burns one more neighbour of a burning node, so
+/// each node ignites on average `p / (1 - p)` (about 2.3) of them.
+const FOREST_FIRE_BURN: f32 = 0.7;
+
+/// How `sample_subgraph` picks its nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleStrategy {
+    /// The nodes with the highest stored `Node::pagerank`, ties broken by
+    /// node order. Ignores the seed.
+    TopByPageRank,
+    /// Nodes drawn uniformly at random.
+    RandomNodes,
+    /// Forest fire (Leskovec & Faloutsos, 2006): from a random node, burn
+    /// a random number of its unburnt neighbours, then theirs, restarting
+    /// at a fresh random node whenever the fire dies out. Keeps local
+    /// structure, so samples look like the clusters they came from.
+    ForestFire,
+}
+
+/// Induced subgraph on at most `max_nodes` nodes chosen by `strategy`:
+/// the chosen nodes, in their original order, and every edge between
+/// two of them. The graph kind is kept. The same `seed` always gives the
+/// same sample.
+pub fn sample_subgraph(
+    graph: &Graph,
+    max_nodes: usize,
+    strategy: SampleStrategy,
+    seed: u64,
+) -> Graph {
+    let n = graph.node_count();
+    let keep = if max_nodes >= n {
+        vec![true; n]
+    } else {
+        let mut rng = SplitMix64(seed);
+        match strategy {
+            SampleStrategy::TopByPageRank => top_by_pagerank(graph, max_nodes),
+            SampleStrategy::RandomNodes => random_nodes(n, max_nodes, &mut rng),
+            SampleStrategy::ForestFire => forest_fire(graph, max_nodes, &mut rng),
+        }
+    };
+
+    let nodes: Vec<_> = graph
+        .nodes()
+        .iter()
+        .zip(&keep)
+        .filter(|&(_, &k)| k)
+        .map(|(node, _)| node.clone())
+        .collect();
+    let kept = |id| graph.node_index(id).is_some_and(|i| keep[i]);
+    let edges = graph
+        .edges()
+        .iter()
+        .filter(|e| kept(e.source) && kept(e.target))
+        .cloned()
+        .collect();
+    Graph::with_kind(nodes, edges, graph.kind())
+}
+
+fn top_by_pagerank(graph: &Graph, max_nodes: usize) -> Vec<bool> {
+    let nodes = graph.nodes();
+    let mut order: Vec<usize> = (0..nodes.len()).collect();
+    // Stable, so equal ranks keep node order
+    order.sort_by(|&a, &b| nodes[b].pagerank.total_cmp(&nodes[a].pagerank));
+    let mut keep = vec![false; nodes.len()];
+    for &i in &order[..max_nodes] {
+        keep[i] = true;
+    }
+    keep
+}
+
+fn random_nodes(n: usize, max_nodes: usize, rng: &mut SplitMix64) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..n).collect();
+    rng.shuffle(&mut order);
+    let mut keep = vec![false; n];
+    for &i in &order[..max_nodes] {
+        keep[i] = true;
+    }
+    keep
+}
+
+fn forest_fire(graph: &Graph, max_nodes: usize, rng: &mut SplitMix64) -> Vec<bool> {
+    let n = graph.node_count();
+    let mut burnt = vec![false; n];
+    let mut count = 0;
+    // Ignition points, used in order and skipped once burnt
+    let mut sparks: Vec<usize> = (0..n).collect();
+    rng.shuffle(&mut sparks);
+    let mut sparks = sparks.into_iter();
+    let mut fire = VecDeque::new();
+
+    while count < max_nodes {
+        let Some(i) = fire.pop_front() else {
+            // The fire died out; relight it somewhere new
+            let Some(spark) = sparks.find(|&s| !burnt[s]) else {
+                break;
+            };
+            burnt[spark] = true;
+            count += 1;
+            fire.push_back(spark);
+            continue;
+        };
+
+        let mut fuel: Vec<usize> = graph
+            .neighbor_indices(i)
+            .iter()
+            .copied()
+            .filter(|&j| !burnt[j])
+            .collect();
+        fuel.sort_unstable();
+        fuel.dedup();
+        rng.shuffle(&mut fuel);
+        let mut spread = 0;
+        while rng.next_unit() < FOREST_FIRE_BURN {
+            spread += 1;
+        }
+        for j in fuel.into_iter().take(spread) {
+            if count == max_nodes {
+                break;
+            }
+            burnt[j] = true;
+            count += 1;
+            fire.push_back(j);
+        }
+    }
+    burnt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::Node;
+    use crate::test_utils::edge;
+
+    fn make_node(id: u32, pagerank: f32) -> Node {
+        Node {
+            id,
+            pagerank,
+            ..Node::default()
+        }
+    }
+
+    /// Ring of 40 nodes with chords, pagerank rising with id.
+    fn ring() -> Graph {
+        let nodes = (0..40).map(|i| make_node(i, i as f32 / 40.0)).collect();
+        let edges = (0..40)
+            .flat_map(|i| [edge(i, (i + 1) % 40), edge(i, (i + 7) % 40)])
+            .collect();
+        Graph::new(nodes, edges)
+    }
+
+    const STRATEGIES: [SampleStrategy; 3] = [
+        SampleStrategy::TopByPageRank,
+        SampleStrategy::RandomNodes,
+        SampleStrategy::ForestFire,
+    ];
+
+    #[test]
+    fn top_by_pagerank_keeps_highest() {
+        let g = ring();
+        let sample = sample_subgraph(&g, 5, SampleStrategy::TopByPageRank, 0);
+        let ids: Vec<u32> = sample.nodes().iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![35, 36, 37, 38, 39]);
+        // Only edges between kept nodes survive: the ring links 35-39
+        assert_eq!(sample.edge_count(), 4);
+        assert!(sample.has_edge(38, 39));
+    }
+
+    #[test]
+    fn samples_never_exceed_max_nodes() {
+        let g = ring();
+        for strategy in STRATEGIES {
+            for max_nodes in [0, 1, 7, 39] {
+                for seed in 0..5 {
+                    let sample = sample_subgraph(&g, max_nodes, strategy, seed);
+                    assert!(sample.node_count() <= max_nodes, "{strategy:?} {max_nodes}");
+                    // Induced: every edge joins two sampled nodes
+                    assert!(sample.validate().is_empty());
+                    for e in sample.edges() {
+                        assert!(g.has_edge(e.source, e.target));
+                    }
+                }
+            }
+            assert_eq!(sample_subgraph(&g, 100, strategy, 1).node_count(), 40);
+        }
+    }
+
+    #[test]
+    fn random_strategies_fill_the_budget_deterministically() {
+        let g = ring();
+        for strategy in [SampleStrategy::RandomNodes, SampleStrategy::ForestFire] {
+            let a = sample_subgraph(&g, 12, strategy, 9);
+            let b = sample_subgraph(&g, 12, strategy, 9);
+            assert_eq!(a.node_count(), 12);
+            assert_eq!(a.nodes(), b.nodes());
+            assert_eq!(a.edges(), b.edges());
+        }
+        // Relighting keeps forest fire going across components
+        let scattered = Graph::new((0..10).map(|i| make_node(i, 0.0)).collect(), vec![]);
+        let sample = sample_subgraph(&scattered, 6, SampleStrategy::ForestFire, 3);
+        assert_eq!(sample.node_count(), 6);
+    }
+}
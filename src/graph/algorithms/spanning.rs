@@ -0,0 +1,106 @@
+use super::components::DisjointSet;
+use crate::graph::types::Graph;
+
+/// Minimum spanning forest by Kruskal's algorithm, treating edges as
+/// undirected and weighted by `Edge::weight` (1.0 on unweighted graphs).
+/// Returns indices into `graph.edges()`, in the order Kruskal accepts
+/// them: ascending weight, ties broken by lower edge index. A disconnected
+/// graph yields one tree per component. Self-loops and edges with an
+/// unknown endpoint are never chosen.
+pub fn minimum_spanning_tree(graph: &Graph) -> Vec<usize> {
+    let edges = graph.edges();
+    let mut candidates: Vec<(usize, usize, usize)> = edges
+        .iter()
+        .enumerate()
+        .filter_map(|(k, edge)| {
+            let i = graph.node_index(edge.source)?;
+            let j = graph.node_index(edge.target)?;
+            (i != j).then_some((k, i, j))
+        })
+        .collect();
+    // Stable, so equal weights keep edge order
+    candidates.sort_by(|a, b| edges[a.0].weight.total_cmp(&edges[b.0].weight));
+
+    let mut sets = DisjointSet::new(graph.node_count());
+    let mut tree = Vec::new();
+    for (k, i, j) in candidates {
+        if sets.union(i, j) {
+            tree.push(k);
+            if tree.len() + 1 == graph.node_count() {
+                break;
+            }
+        }
+    }
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{node, weighted_edge};
+
+    fn total_weight(graph: &Graph, tree: &[usize]) -> f32 {
+        tree.iter().map(|&k| graph.edges()[k].weight).sum()
+    }
+
+    #[test]
+    fn known_weighted_tree() {
+        //   0 --1-- 1
+        //   | \     |
+        //   4   3   2
+        //   |     \ |
+        //   3 --5-- 2
+        let nodes = (0..4).map(node).collect();
+        let edges = vec![
+            weighted_edge(0, 1, 1.0),
+            weighted_edge(1, 2, 2.0),
+            weighted_edge(0, 2, 3.0),
+            weighted_edge(0, 3, 4.0),
+            weighted_edge(3, 2, 5.0),
+        ];
+        let g = Graph::new(nodes, edges);
+        let tree = minimum_spanning_tree(&g);
+        assert_eq!(tree, vec![0, 1, 3]);
+        assert_eq!(total_weight(&g, &tree), 7.0);
+    }
+
+    #[test]
+    fn ties_break_by_edge_index() {
+        // A unit-weight 4-cycle: any three edges span it; the first three win
+        let nodes = (0..4).map(node).collect();
+        let edges = vec![
+            weighted_edge(0, 1, 1.0),
+            weighted_edge(1, 2, 1.0),
+            weighted_edge(2, 3, 1.0),
+            weighted_edge(3, 0, 1.0),
+        ];
+        let g = Graph::new(nodes, edges);
+        assert_eq!(minimum_spanning_tree(&g), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn disconnected_graph_gives_forest() {
+        // Triangle 0-1-2 and edge 3-4, plus isolated 5, a self-loop and a
+        // dangling edge
+        let nodes = (0..6).map(node).collect();
+        let edges = vec![
+            weighted_edge(0, 1, 2.0),
+            weighted_edge(1, 2, 1.0),
+            weighted_edge(2, 0, 3.0),
+            weighted_edge(3, 4, 7.0),
+            weighted_edge(5, 5, 0.5),
+            weighted_edge(4, 9, 0.1),
+        ];
+        let g = Graph::new(nodes, edges);
+        let tree = minimum_spanning_tree(&g);
+        assert_eq!(tree, vec![1, 0, 3]);
+        // n - components edges: 6 nodes, 3 components
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn empty_graph() {
+        assert!(minimum_spanning_tree(&Graph::new(vec![], vec![])).is_empty());
+    }
+}
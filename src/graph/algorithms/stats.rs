@@ -0,0 +1,93 @@
+use super::components::connected_components;
+use super::degree::{average_degree, max_degree};
+use crate::graph::types::Graph;
+use wasm_bindgen::prelude::*;
+
+/// Summary metrics for a stats panel, all over the undirected
+/// interpretation of the graph.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphStats {
+    #[wasm_bindgen(readonly)]
+    pub node_count: usize,
+    #[wasm_bindgen(readonly)]
+    pub edge_count: usize,
+    /// Distinct linked node pairs over possible pairs, in `[0, 1]`. Self-
+    /// loops and parallel edges don't count; 0 below two nodes.
+    #[wasm_bindgen(readonly)]
+    pub density: f32,
+    #[wasm_bindgen(readonly)]
+    pub average_degree: f32,
+    /// 0 for an empty graph.
+    #[wasm_bindgen(readonly)]
+    pub max_degree: u16,
+    #[wasm_bindgen(readonly)]
+    pub component_count: usize,
+    /// Exactly one component; false for an empty graph.
+    #[wasm_bindgen(readonly)]
+    pub is_connected: bool,
+}
+
+impl GraphStats {
+    pub fn compute(graph: &Graph) -> GraphStats {
+        let n = graph.node_count();
+        let component_count = connected_components(graph)
+            .into_iter()
+            .max()
+            .map_or(0, |c| c + 1);
+        GraphStats {
+            node_count: n,
+            edge_count: graph.edge_count(),
+            density: graph.density(),
+            average_degree: average_degree(graph),
+            max_degree: max_degree(graph).unwrap_or(0),
+            component_count,
+            is_connected: component_count == 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{edge, node};
+
+    #[test]
+    fn known_graph() {
+        // Triangle 0-1-2 (one edge doubled, plus a self-loop) and pair 3-4
+        let nodes = (0..5).map(node).collect();
+        let edges = vec![
+            edge(0, 1),
+            edge(1, 2),
+            edge(2, 0),
+            edge(1, 0),
+            edge(2, 2),
+            edge(3, 4),
+        ];
+        let stats = GraphStats::compute(&Graph::new(nodes, edges));
+        assert_eq!(stats.node_count, 5);
+        assert_eq!(stats.edge_count, 6);
+        // 4 distinct pairs out of C(5, 2) = 10
+        assert!((stats.density - 0.4).abs() < 1e-6);
+        assert_eq!(stats.component_count, 2);
+        assert!(!stats.is_connected);
+        assert_eq!(stats.max_degree, 3);
+    }
+
+    #[test]
+    fn small_graphs() {
+        let empty = GraphStats::compute(&Graph::new(vec![], vec![]));
+        assert_eq!(empty.density, 0.0);
+        assert_eq!(empty.component_count, 0);
+        assert!(!empty.is_connected);
+        assert_eq!(empty.average_degree, 0.0);
+
+        let single = GraphStats::compute(&Graph::new(vec![node(1)], vec![edge(1, 1)]));
+        assert_eq!(single.density, 0.0);
+        assert!(single.is_connected);
+
+        let pair = GraphStats::compute(&Graph::new(vec![node(1), node(2)], vec![edge(1, 2)]));
+        assert_eq!(pair.density, 1.0);
+    }
+}
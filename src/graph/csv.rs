@@ -0,0 +1,184 @@
+//! Two- or three-column CSV edge lists: `source,target[,weight]`.
+//!
+//! Fields are split on commas and trimmed; quoting is not supported. Blank
+//! lines are skipped. An endpoint that parses as a `u32` is used as the node
+//! id directly; anything else is a label, and each distinct label gets the
+//! next id not taken by a numeric endpoint.
+
+use crate::graph::types::{Edge, Graph, Node};
+use std::collections::{HashMap, HashSet};
+
+/// An endpoint as written in the file.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Endpoint<'a> {
+    Id(u32),
+    Label(&'a str),
+}
+
+/// Builds an undirected graph from an edge list, creating nodes in order of
+/// first appearance. A missing or empty weight is 1.0. Degrees are computed
+/// from the parsed edges. Errors name the 1-based line of the first
+/// malformed row.
+pub fn from_edge_list(csv: &str, has_header: bool) -> Result<Graph, String> {
+    let mut rows = Vec::new();
+    for (number, line) in csv.lines().enumerate().skip(usize::from(has_header)) {
+        let line_number = number + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let (source, target, weight) = match fields[..] {
+            [source, target] => (source, target, ""),
+            [source, target, weight] => (source, target, weight),
+            _ => {
+                return Err(format!(
+                    "line {line_number}: expected 2 or 3 columns, found {}",
+                    fields.len()
+                ));
+            }
+        };
+        let weight = if weight.is_empty() {
+            1.0
+        } else {
+            weight
+                .parse::<f32>()
+                .map_err(|_| format!("line {line_number}: invalid weight {weight:?}"))?
+        };
+        rows.push((
+            endpoint(source, line_number)?,
+            endpoint(target, line_number)?,
+            weight,
+        ));
+    }
+
+    let taken: HashSet<u32> = rows
+        .iter()
+        .flat_map(|&(s, t, _)| [s, t])
+        .filter_map(|e| match e {
+            Endpoint::Id(id) => Some(id),
+            Endpoint::Label(_) => None,
+        })
+        .collect();
+    let mut next_free = 0u32;
+    let mut ids: HashMap<Endpoint, u32> = HashMap::new();
+    let mut nodes = Vec::new();
+    let mut edges = Vec::with_capacity(rows.len());
+    for (source, target, weight) in rows {
+        let mut id_of = |e| -> Result<u32, String> {
+            if let Some(&id) = ids.get(&e) {
+                return Ok(id);
+            }
+            let (id, label) = match e {
+                Endpoint::Id(id) => (id, String::new()),
+                Endpoint::Label(label) => {
+                    while taken.contains(&next_free) {
+                        next_free = next_free
+                            .checked_add(1)
+                            .ok_or("ran out of node ids for labels")?;
+                    }
+                    let id = next_free;
+                    next_free = next_free.wrapping_add(1);
+                    (id, label.to_string())
+                }
+            };
+            ids.insert(e, id);
+            nodes.push(Node {
+                id,
+                label,
+                pagerank: 0.0,
+                degree: 0,
+                x: 0.0,
+                y: 0.0,
+                community: None,
+                attributes: Default::default(),
+            });
+            Ok(id)
+        };
+        let edge = Edge {
+            source: id_of(source)?,
+            target: id_of(target)?,
+            weight,
+        };
+        edges.push(edge);
+    }
+
+    let mut graph = Graph::new(nodes, edges);
+    graph.recompute_degrees();
+    Ok(graph)
+}
+
+fn endpoint(field: &str, line_number: usize) -> Result<Endpoint<'_>, String> {
+    if field.is_empty() {
+        return Err(format!("line {line_number}: empty node field"));
+    }
+    Ok(field
+        .parse::<u32>()
+        .map_or(Endpoint::Label(field), Endpoint::Id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(graph: &Graph) -> Vec<(u32, u32, f32)> {
+        graph
+            .edges()
+            .iter()
+            .map(|e| (e.source, e.target, e.weight))
+            .collect()
+    }
+
+    #[test]
+    fn numeric_ids() {
+        let g = from_edge_list("10,20\n20,30\n\n30, 10\n", false).unwrap();
+        let ids: Vec<u32> = g.nodes().iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![10, 20, 30]);
+        assert_eq!(pairs(&g), vec![(10, 20, 1.0), (20, 30, 1.0), (30, 10, 1.0)]);
+        assert!(
+            g.nodes()
+                .iter()
+                .all(|n| n.degree == 2 && n.label.is_empty())
+        );
+    }
+
+    #[test]
+    fn string_labels_get_ids() {
+        let csv = "from,to\nalice,bob\nbob,carol\n1,alice\n";
+        let g = from_edge_list(csv, true).unwrap();
+        let labels: Vec<(u32, &str)> = g.nodes().iter().map(|n| (n.id, n.label.as_str())).collect();
+        // Id 1 is used by a numeric endpoint, so labels skip it
+        assert_eq!(
+            labels,
+            vec![(0, "alice"), (2, "bob"), (3, "carol"), (1, "")]
+        );
+        assert_eq!(pairs(&g), vec![(0, 2, 1.0), (2, 3, 1.0), (1, 0, 1.0)]);
+        assert_eq!(g.node_by_id(0).unwrap().degree, 2);
+    }
+
+    #[test]
+    fn optional_weight_column() {
+        let g = from_edge_list("source,target,weight\n1,2,0.5\n2,3,\n3,1,4\n", true).unwrap();
+        assert_eq!(pairs(&g), vec![(1, 2, 0.5), (2, 3, 1.0), (3, 1, 4.0)]);
+    }
+
+    #[test]
+    fn malformed_rows_report_line() {
+        assert_eq!(
+            from_edge_list("1,2\n3\n", false).unwrap_err(),
+            "line 2: expected 2 or 3 columns, found 1"
+        );
+        assert_eq!(
+            from_edge_list("s,t,w\n1,2,heavy\n", true).unwrap_err(),
+            "line 2: invalid weight \"heavy\""
+        );
+        assert_eq!(
+            from_edge_list("1,2,3,4\n", false).unwrap_err(),
+            "line 1: expected 2 or 3 columns, found 4"
+        );
+        assert_eq!(
+            from_edge_list("1,\n", false).unwrap_err(),
+            "line 1: empty node field"
+        );
+        assert_eq!(from_edge_list("", true).unwrap().node_count(), 0);
+    }
+}
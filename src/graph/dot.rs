@@ -0,0 +1,96 @@
+use crate::graph::types::Graph;
+use std::fmt::Write;
+
+impl Graph {
+    /// GraphViz source for the graph: a `digraph` with `->` edges when
+    /// directed, otherwise a `graph` with `--`. Nodes are keyed by id, with
+    /// their label (escaped) when they have one and their PageRank as a
+    /// trailing comment. Edges with an unknown endpoint are left out, so
+    /// the output never invents nodes.
+    pub fn to_dot(&self) -> String {
+        let (keyword, arrow) = if self.is_directed() {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut out = format!("{keyword} {{\n");
+        // Writing to a String can't fail
+        for node in self.nodes() {
+            if node.label.is_empty() {
+                let _ = write!(out, "  {}", node.id);
+            } else {
+                let _ = write!(out, "  {} [label=\"{}\"]", node.id, escape(&node.label));
+            }
+            let _ = writeln!(out, "; // pagerank {}", node.pagerank);
+        }
+        for edge in self.edges() {
+            if self.node_index(edge.source).is_some() && self.node_index(edge.target).is_some() {
+                let _ = writeln!(out, "  {} {arrow} {};", edge.source, edge.target);
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Escapes a string for a double-quoted DOT id.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::types::{Graph, GraphKind, Node};
+    use crate::test_utils::edge;
+
+    fn make_node(id: u32, label: &str) -> Node {
+        Node {
+            id,
+            label: label.to_string(),
+            pagerank: 0.5,
+            ..Node::default()
+        }
+    }
+
+    #[test]
+    fn undirected_output() {
+        let nodes = vec![make_node(1, "Alpha"), make_node(2, "")];
+        let g = Graph::new(nodes, vec![edge(1, 2), edge(2, 9)]);
+        assert_eq!(
+            g.to_dot(),
+            "graph {\n  \
+             1 [label=\"Alpha\"]; // pagerank 0.5\n  \
+             2; // pagerank 0.5\n  \
+             1 -- 2;\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn directed_output() {
+        let nodes = vec![make_node(1, ""), make_node(2, "")];
+        let g = Graph::with_kind(nodes, vec![edge(2, 1)], GraphKind::Directed);
+        let dot = g.to_dot();
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.lines().any(|l| l == "  2 -> 1;"));
+        assert!(!dot.contains("--"));
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        let g = Graph::new(vec![make_node(7, "say \"hi\"\\\nbye")], vec![]);
+        let dot = g.to_dot();
+        assert!(dot.contains(r#"7 [label="say \"hi\"\\\nbye"];"#), "{dot}");
+        assert_eq!(dot.lines().count(), 3);
+    }
+}
@@ -0,0 +1,192 @@
+//! Node-link JSON:
+//!
+//! ```json
+//! {
+//!   "directed": false,
+//!   "nodes": [{ "id": 1, "label": "a", "pagerank": 0.5, "degree": 1, "x": 0, "y": 0 }],
+//!   "edges": [{ "source": 1, "target": 2, "weight": 1.0 }]
+//! }
+//! ```
+//!
+//! Only `id`, `source` and `target` are required. `directed` defaults to
+//! false, `label` to empty, `pagerank` and positions to 0, `weight` to 1,
+//! and a missing `degree` is computed from the edges.
+
+use crate::graph::types::{Edge, Graph, GraphKind, Node};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct JsonGraph {
+    #[serde(default)]
+    directed: bool,
+    nodes: Vec<JsonNode>,
+    #[serde(default)]
+    edges: Vec<JsonEdge>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonNode {
+    id: u32,
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    pagerank: f32,
+    degree: Option<u16>,
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonEdge {
+    source: u32,
+    target: u32,
+    #[serde(default = "unit_weight")]
+    weight: f32,
+}
+
+fn unit_weight() -> f32 {
+    1.0
+}
+
+/// Parses a node-link JSON document. Malformed JSON, a missing required
+/// field, a duplicate node id or an edge to an unknown node is an error.
+pub fn from_json(s: &str) -> Result<Graph, String> {
+    let parsed: JsonGraph =
+        serde_json::from_str(s).map_err(|e| format!("invalid graph JSON: {e}"))?;
+
+    let kind = if parsed.directed {
+        GraphKind::Directed
+    } else {
+        GraphKind::Undirected
+    };
+    let degrees: Vec<Option<u16>> = parsed.nodes.iter().map(|n| n.degree).collect();
+    let mut graph = Graph::with_kind(Vec::new(), Vec::new(), kind);
+    for n in parsed.nodes {
+        graph.add_node(Node {
+            id: n.id,
+            label: n.label,
+            pagerank: n.pagerank,
+            degree: n.degree.unwrap_or(0),
+            x: n.x,
+            y: n.y,
+            community: None,
+            attributes: Default::default(),
+        })?;
+    }
+    for e in parsed.edges {
+        graph.add_edge(Edge {
+            source: e.source,
+            target: e.target,
+            weight: e.weight,
+        })?;
+    }
+
+    for (i, degree) in degrees.into_iter().enumerate() {
+        if degree.is_none() {
+            let id = graph.nodes()[i].id;
+            graph.nodes_mut()[i].degree = graph.compute_degree(id);
+        }
+    }
+    Ok(graph)
+}
+
+/// Serializes every node and edge, with all fields present, in the format
+/// `from_json` reads.
+pub fn to_json(graph: &Graph) -> String {
+    let doc = JsonGraph {
+        directed: graph.is_directed(),
+        nodes: graph
+            .nodes()
+            .iter()
+            .map(|n| JsonNode {
+                id: n.id,
+                label: n.label.clone(),
+                pagerank: n.pagerank,
+                degree: Some(n.degree),
+                x: n.x,
+                y: n.y,
+            })
+            .collect(),
+        edges: graph
+            .edges()
+            .iter()
+            .map(|e| JsonEdge {
+                source: e.source,
+                target: e.target,
+                weight: e.weight,
+            })
+            .collect(),
+    };
+    // Plain structs of numbers and strings always serialize
+    serde_json::to_string(&doc).expect("graph serializes to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_node(id: u32, label: &str) -> Node {
+        Node {
+            id,
+            label: label.to_string(),
+            pagerank: 0.25,
+            degree: 1,
+            x: id as f32,
+            y: -(id as f32),
+            ..Node::default()
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let nodes = vec![make_node(1, "a \"quoted\" label"), make_node(2, "")];
+        let edges = vec![Edge {
+            source: 1,
+            target: 2,
+            weight: 2.5,
+        }];
+        for kind in [GraphKind::Undirected, GraphKind::Directed] {
+            let g = Graph::with_kind(nodes.clone(), edges.clone(), kind);
+            let back = from_json(&to_json(&g)).unwrap();
+            assert_eq!(back.kind(), kind);
+            assert_eq!(back.nodes(), g.nodes());
+            assert_eq!(back.edges(), g.edges());
+        }
+    }
+
+    #[test]
+    fn optional_fields_default() {
+        let g = from_json(
+            r#"{"nodes": [{"id": 1}, {"id": 2, "degree": 7}, {"id": 3}],
+                "edges": [{"source": 1, "target": 2}, {"source": 1, "target": 3}]}"#,
+        )
+        .unwrap();
+        assert!(!g.is_directed());
+        let first = &g.nodes()[0];
+        assert_eq!(
+            (first.label.as_str(), first.pagerank, first.x),
+            ("", 0.0, 0.0)
+        );
+        let degrees: Vec<u16> = g.nodes().iter().map(|n| n.degree).collect();
+        // Missing degrees are computed; a given one is kept as is
+        assert_eq!(degrees, vec![2, 7, 1]);
+        assert!(g.edges().iter().all(|e| e.weight == 1.0));
+
+        let empty = from_json(r#"{"nodes": []}"#).unwrap();
+        assert_eq!(empty.node_count(), 0);
+    }
+
+    #[test]
+    fn malformed_json_is_an_error() {
+        assert!(from_json("{").is_err());
+        assert!(from_json("[]").is_err());
+        assert!(from_json(r#"{"nodes": [{"label": "no id"}]}"#).is_err());
+        assert!(from_json(r#"{"nodes": [{"id": -1}]}"#).is_err());
+        let dup = r#"{"nodes": [{"id": 1}, {"id": 1}]}"#;
+        assert_eq!(from_json(dup).unwrap_err(), "duplicate node id 1");
+        let dangling = r#"{"nodes": [{"id": 1}], "edges": [{"source": 1, "target": 2}]}"#;
+        assert!(from_json(dangling).unwrap_err().contains("unknown node"));
+    }
+}
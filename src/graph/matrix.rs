@@ -0,0 +1,156 @@
+use crate::graph::types::{Edge, Graph, GraphKind, Node};
+use std::collections::HashSet;
+
+/// Largest graph `to_adjacency_matrix` will build. The matrix is dense,
+/// O(V²): 4096 nodes is already 64 MiB of `f32`s.
+pub const MAX_MATRIX_NODES: usize = 4096;
+
+impl Graph {
+    /// Dense weighted adjacency matrix, rows and columns in node-index
+    /// order. Entry `[i][j]` is the summed weight of edges from `i` to `j`
+    /// (0.0 where there are none); undirected graphs fill both `[i][j]` and
+    /// `[j][i]`. Edges with an unknown endpoint are left out. Errors above
+    /// `MAX_MATRIX_NODES` rather than allocate O(V²) memory.
+    pub fn to_adjacency_matrix(&self) -> Result<Vec<Vec<f32>>, String> {
+        let n = self.node_count();
+        if n > MAX_MATRIX_NODES {
+            return Err(format!(
+                "{n} nodes is too many for a dense matrix (limit {MAX_MATRIX_NODES})"
+            ));
+        }
+        let mut matrix = vec![vec![0.0; n]; n];
+        for edge in self.edges() {
+            if let (Some(i), Some(j)) = (self.node_index(edge.source), self.node_index(edge.target))
+            {
+                matrix[i][j] += edge.weight;
+                if !self.is_directed() && i != j {
+                    matrix[j][i] += edge.weight;
+                }
+            }
+        }
+        Ok(matrix)
+    }
+
+    /// Builds a graph from a square matrix whose row/column `i` is the node
+    /// `ids[i]`. Each non-zero entry becomes one edge with that weight. A
+    /// symmetric matrix gives an undirected graph (reading the upper
+    /// triangle), anything else a directed one. Labels are empty, PageRank
+    /// 0, and degrees are computed from the edges.
+    pub fn from_adjacency_matrix(matrix: &[Vec<f32>], ids: &[u32]) -> Result<Graph, String> {
+        let n = ids.len();
+        if matrix.len() != n {
+            return Err(format!("matrix has {} rows for {n} ids", matrix.len()));
+        }
+        if let Some((i, row)) = matrix.iter().enumerate().find(|(_, row)| row.len() != n) {
+            return Err(format!("row {i} has {} columns, expected {n}", row.len()));
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(id) = ids.iter().find(|&&id| !seen.insert(id)) {
+            return Err(format!("duplicate node id {id}"));
+        }
+
+        let symmetric = (0..n).all(|i| (i + 1..n).all(|j| matrix[i][j] == matrix[j][i]));
+        let mut edges = Vec::new();
+        for (i, row) in matrix.iter().enumerate() {
+            let start = if symmetric { i } else { 0 };
+            for (j, &weight) in row.iter().enumerate().skip(start) {
+                if weight != 0.0 {
+                    edges.push(Edge {
+                        source: ids[i],
+                        target: ids[j],
+                        weight,
+                    });
+                }
+            }
+        }
+
+        let nodes = ids
+            .iter()
+            .map(|&id| Node {
+                id,
+                label: String::new(),
+                pagerank: 0.0,
+                degree: 0,
+                x: 0.0,
+                y: 0.0,
+                community: None,
+                attributes: Default::default(),
+            })
+            .collect();
+        let kind = if symmetric {
+            GraphKind::Undirected
+        } else {
+            GraphKind::Directed
+        };
+        let mut graph = Graph::with_kind(nodes, edges, kind);
+        graph.recompute_degrees();
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{node, weighted_edge};
+
+    #[test]
+    fn weighted_round_trip() {
+        let nodes = [10, 20, 30, 40].map(node).to_vec();
+        let edges = vec![
+            weighted_edge(10, 20, 0.5),
+            weighted_edge(20, 30, 2.0),
+            weighted_edge(30, 40, 1.5),
+            weighted_edge(40, 10, 3.0),
+            weighted_edge(20, 20, 4.0),
+        ];
+        let g = Graph::new(nodes, edges);
+        let matrix = g.to_adjacency_matrix().unwrap();
+        assert_eq!(
+            matrix,
+            vec![
+                vec![0.0, 0.5, 0.0, 3.0],
+                vec![0.5, 4.0, 2.0, 0.0],
+                vec![0.0, 2.0, 0.0, 1.5],
+                vec![3.0, 0.0, 1.5, 0.0],
+            ]
+        );
+
+        let back = Graph::from_adjacency_matrix(&matrix, &[10, 20, 30, 40]).unwrap();
+        assert!(!back.is_directed());
+        assert_eq!(back.edge_count(), 5);
+        assert_eq!(back.to_adjacency_matrix().unwrap(), matrix);
+        let degrees: Vec<u16> = back.nodes().iter().map(|n| n.degree).collect();
+        assert_eq!(degrees, vec![2, 3, 2, 2]);
+    }
+
+    #[test]
+    fn directed_round_trip() {
+        let nodes = [1, 2, 3].map(node).to_vec();
+        let edges = vec![
+            weighted_edge(1, 2, 1.0),
+            weighted_edge(2, 3, 1.0),
+            weighted_edge(1, 2, 2.0),
+        ];
+        let g = Graph::with_kind(nodes, edges, GraphKind::Directed);
+        let matrix = g.to_adjacency_matrix().unwrap();
+        // Parallel edges sum
+        assert_eq!(matrix[0], vec![0.0, 3.0, 0.0]);
+        assert_eq!(matrix[1][0], 0.0);
+
+        let back = Graph::from_adjacency_matrix(&matrix, &[1, 2, 3]).unwrap();
+        assert!(back.is_directed());
+        assert_eq!(back.out_neighbors(1), vec![2]);
+        assert_eq!(back.to_adjacency_matrix().unwrap(), matrix);
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert!(Graph::from_adjacency_matrix(&[vec![0.0]], &[1, 2]).is_err());
+        assert!(Graph::from_adjacency_matrix(&[vec![0.0], vec![0.0, 0.0]], &[1, 2]).is_err());
+        assert!(Graph::from_adjacency_matrix(&[vec![0.0; 2], vec![0.0; 2]], &[1, 1]).is_err());
+
+        let nodes = (0..MAX_MATRIX_NODES as u32 + 1).map(node).collect();
+        assert!(Graph::new(nodes, vec![]).to_adjacency_matrix().is_err());
+    }
+}
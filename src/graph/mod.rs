@@ -1,6 +1,16 @@
 pub mod algorithms;
+pub mod csv;
+pub mod dot;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod matrix;
+mod search;
 pub mod spatial;
 pub mod types;
+pub mod validate;
+pub mod view;
 
-pub use spatial::{AABB, Quadtree};
-pub use types::{Edge, Graph, Node};
+pub use spatial::{AABB, Quadtree, SpatialHash};
+pub use types::{DedupReport, Edge, Graph, GraphKind, Node};
+pub use validate::GraphIssue;
+pub use view::GraphView;
@@ -0,0 +1,73 @@
+use crate::graph::types::Node;
+use std::collections::HashMap;
+
+/// Label lookups for `Graph::nodes_by_label` and `Graph::search_labels`.
+/// Built on first use and dropped whenever labels may have changed.
+/// Nodes with an empty label aren't indexed.
+#[derive(Debug)]
+pub(crate) struct LabelIndex {
+    /// Node indices per exact label, in table order.
+    exact: HashMap<String, Vec<usize>>,
+    /// Lowercased label and node index per labelled node, sorted, so
+    /// prefix matches are one contiguous run.
+    folded: Vec<(String, usize)>,
+}
+
+impl LabelIndex {
+    pub(crate) fn build(nodes: &[Node]) -> Self {
+        let mut exact: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut folded = Vec::new();
+        for (i, node) in nodes.iter().enumerate() {
+            if node.label.is_empty() {
+                continue;
+            }
+            exact.entry(node.label.clone()).or_default().push(i);
+            folded.push((node.label.to_lowercase(), i));
+        }
+        folded.sort_unstable();
+        Self { exact, folded }
+    }
+
+    pub(crate) fn exact(&self, label: &str) -> &[usize] {
+        self.exact.get(label).map_or(&[], |v| v)
+    }
+
+    /// Node indices whose label contains `query`, ignoring case, best
+    /// first: prefix matches, then other substring matches. Prefix matches
+    /// rank shorter labels first; substring matches rank earlier match
+    /// positions first, then shorter labels. Remaining ties go by node
+    /// index. An empty query matches nothing.
+    pub(crate) fn search(&self, query: &str, limit: usize) -> Vec<usize> {
+        let query = query.to_lowercase();
+        if query.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let start = self
+            .folded
+            .partition_point(|(label, _)| label.as_str() < query.as_str());
+        let mut prefix: Vec<(usize, usize)> = self.folded[start..]
+            .iter()
+            .take_while(|(label, _)| label.starts_with(&query))
+            .map(|(label, i)| (label.len(), *i))
+            .collect();
+        prefix.sort_unstable();
+
+        let mut rest: Vec<(usize, usize, usize)> = self
+            .folded
+            .iter()
+            .filter_map(|(label, i)| match label.find(&query) {
+                Some(pos) if pos > 0 => Some((pos, label.len(), *i)),
+                _ => None,
+            })
+            .collect();
+        rest.sort_unstable();
+
+        prefix
+            .into_iter()
+            .map(|(_, i)| i)
+            .chain(rest.into_iter().map(|(_, _, i)| i))
+            .take(limit)
+            .collect()
+    }
+}
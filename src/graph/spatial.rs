@@ -1,83 +1,523 @@
 use crate::graph::types::Node;
 pub use crate::spatial::AABB;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Points closer than this to the query are treated as the query itself.
+const SELF_EPSILON: f32 = 0.01;
+/// Distances are clamped to this before the inverse-square falloff, so
+/// near-coincident points don't produce unbounded forces.
+const MIN_FORCE_DIST: f32 = 1.0;
+
+/// Subdivision limit used by `Quadtree::new`. Deep enough that a
+/// 10 000-unit world still resolves cells well under a unit wide, shallow
+/// enough that coincident points stop splitting quickly.
+pub const DEFAULT_MAX_DEPTH: u32 = 16;
+
+/// Leaf capacity used by `Quadtree::with_default_capacity`. Small
+/// capacities give deep trees whose queries spend their time descending
+/// cells; large ones give flat leaves scanned point by point. Around 16 a
+/// leaf's scan costs about as much as one level of descent, and fewer than
+/// 17 coincident points never split a cell at all. Larger stacks still
+/// split, but only down to `DEFAULT_MAX_DEPTH`, so each costs at most 64
+/// cells.
+pub const DEFAULT_CAPACITY: usize = 16;
 
 pub struct Quadtree {
     bounds: AABB,
     capacity: usize,
-    nodes: Vec<usize>,
+    /// Leaves at this depth keep every point they receive, ignoring
+    /// `capacity`, so coincident points can't subdivide forever.
+    max_depth: u32,
+    depth: u32,
+    nodes: Vec<Entry>,
     children: Option<Box<[Quadtree; 4]>>,
+    /// Unit-mass summary of every point in this cell and its descendants.
+    center_of_mass: (f32, f32),
+    total_mass: f32,
+}
+
+/// A node index together with the position it was inserted at.
+#[derive(Clone, Copy)]
+struct Entry {
+    idx: usize,
+    x: f32,
+    y: f32,
 }
 
 impl Quadtree {
     pub fn new(bounds: AABB, capacity: usize) -> Self {
+        Self::with_max_depth(bounds, capacity, DEFAULT_MAX_DEPTH)
+    }
+
+    /// A tree with `DEFAULT_CAPACITY` points per leaf, right for most
+    /// hit-testing workloads.
+    pub fn with_default_capacity(bounds: AABB) -> Self {
+        Self::new(bounds, DEFAULT_CAPACITY)
+    }
+
+    pub fn with_max_depth(bounds: AABB, capacity: usize, max_depth: u32) -> Self {
+        Self::cell(bounds, capacity, max_depth, 0)
+    }
+
+    fn cell(bounds: AABB, capacity: usize, max_depth: u32, depth: u32) -> Self {
         Self {
             bounds,
             capacity,
+            max_depth,
+            depth,
             nodes: Vec::new(),
             children: None,
+            center_of_mass: (0.0, 0.0),
+            total_mass: 0.0,
         }
     }
 
+    /// Builds a tree over `nodes` (indexed by slice position) with bounds
+    /// fitted to their positions. The bounds are padded by 5% plus one unit
+    /// so extreme nodes sit strictly inside; an empty slice gets a
+    /// 200-unit square around the origin.
+    pub fn from_nodes(nodes: &[Node], capacity: usize) -> Self {
+        let mut fit = AABB::empty();
+        for node in nodes {
+            fit.expand_to_include(node.x, node.y);
+        }
+        if fit.is_empty() {
+            let bounds = AABB {
+                min_x: -100.0,
+                min_y: -100.0,
+                max_x: 100.0,
+                max_y: 100.0,
+            };
+            return Self::new(bounds, capacity);
+        }
+
+        let (w, h) = fit.dimensions();
+        let pad_x = w * 0.05 + 1.0;
+        let pad_y = h * 0.05 + 1.0;
+        let bounds = AABB {
+            min_x: fit.min_x - pad_x,
+            min_y: fit.min_y - pad_y,
+            max_x: fit.max_x + pad_x,
+            max_y: fit.max_y + pad_y,
+        };
+
+        let mut tree = Self::new(bounds, capacity);
+        for (i, node) in nodes.iter().enumerate() {
+            tree.insert(i, node);
+        }
+        tree
+    }
+
     pub fn insert(&mut self, node_idx: usize, node: &Node) -> bool {
-        if !self.bounds.contains(node.x, node.y) {
+        let entry = Entry {
+            idx: node_idx,
+            x: node.x,
+            y: node.y,
+        };
+        self.insert_entry(entry)
+    }
+
+    fn insert_entry(&mut self, entry: Entry) -> bool {
+        if !self.bounds.contains(entry.x, entry.y) {
             return false;
         }
-        if self.nodes.len() < self.capacity && self.children.is_none() {
-            self.nodes.push(node_idx);
-            return true;
+        let is_leaf = self.children.is_none();
+        let has_room = self.nodes.len() < self.capacity || self.depth >= self.max_depth;
+        let inserted = if is_leaf && has_room {
+            self.nodes.push(entry);
+            true
+        } else {
+            if self.children.is_none() {
+                self.subdivide();
+            }
+            self.children
+                .as_mut()
+                .is_some_and(|children| children.iter_mut().any(|c| c.insert_entry(entry)))
+        };
+        if inserted {
+            self.add_mass(entry.x, entry.y);
         }
-        if self.children.is_none() {
-            self.subdivide();
+        inserted
+    }
+
+    /// Removes `node_idx`, which must have been inserted at `node`'s current
+    /// position. Cells left holding no more than `capacity` points are
+    /// merged back into a leaf. Returns false if the node wasn't found.
+    pub fn remove(&mut self, node_idx: usize, node: &Node) -> bool {
+        self.remove_entry(node_idx, node.x, node.y).is_some()
+    }
+
+    /// Moves `node_idx` from `old`'s position to `new`'s. Returns false if
+    /// the node wasn't found or `new` falls outside the tree's bounds (in
+    /// which case it is no longer stored).
+    pub fn relocate(&mut self, node_idx: usize, old: &Node, new: &Node) -> bool {
+        self.remove(node_idx, old) && self.insert(node_idx, new)
+    }
+
+    fn remove_entry(&mut self, idx: usize, x: f32, y: f32) -> Option<Entry> {
+        if !self.bounds.contains(x, y) {
+            return None;
         }
-        if let Some(ref mut children) = self.children {
-            for child in children.iter_mut() {
-                if child.insert(node_idx, node) {
-                    return true;
-                }
+        let removed = match self.nodes.iter().position(|e| e.idx == idx) {
+            Some(pos) => Some(self.nodes.swap_remove(pos)),
+            None => self
+                .children
+                .as_mut()?
+                .iter_mut()
+                .find_map(|c| c.remove_entry(idx, x, y)),
+        }?;
+        self.remove_mass(removed.x, removed.y);
+        if self.children.is_some() && self.total_mass as usize <= self.capacity {
+            self.collapse();
+        }
+        Some(removed)
+    }
+
+    /// Pulls every descendant point up into this cell and drops the children.
+    fn collapse(&mut self) {
+        if let Some(children) = self.children.take() {
+            for mut child in *children {
+                child.collapse();
+                self.nodes.append(&mut child.nodes);
             }
         }
-        false
     }
 
+    /// Nodes whose inserted position lies within `radius` of `(x, y)`
+    /// (boundary inclusive).
     pub fn query_point(&self, x: f32, y: f32, radius: f32) -> Vec<usize> {
         let mut result = Vec::new();
+        self.collect_within(x, y, radius, &mut result);
+        result
+    }
 
+    fn collect_within(&self, x: f32, y: f32, radius: f32, out: &mut Vec<usize>) {
         if !self.bounds.intersects_circle(x, y, radius) {
-            return result;
+            return;
         }
 
-        result.extend_from_slice(&self.nodes);
+        let r2 = radius * radius;
+        out.extend(
+            self.nodes
+                .iter()
+                .filter(|e| (e.x - x).powi(2) + (e.y - y).powi(2) <= r2)
+                .map(|e| e.idx),
+        );
 
         if let Some(ref children) = self.children {
             for child in children.iter() {
-                result.extend(child.query_point(x, y, radius));
+                child.collect_within(x, y, radius, out);
             }
         }
+    }
 
+    /// Nodes whose inserted position lies inside `rect` (edges inclusive),
+    /// descending only into cells that overlap it.
+    pub fn query_rect(&self, rect: &AABB) -> Vec<usize> {
+        let mut result = Vec::new();
+        self.collect_in_rect(rect, &mut result);
         result
     }
 
+    fn collect_in_rect(&self, rect: &AABB, out: &mut Vec<usize>) {
+        if !self.bounds.intersects(rect) {
+            return;
+        }
+        out.extend(
+            self.nodes
+                .iter()
+                .filter(|e| rect.contains(e.x, e.y))
+                .map(|e| e.idx),
+        );
+        if let Some(ref children) = self.children {
+            for child in children.iter() {
+                child.collect_in_rect(rect, out);
+            }
+        }
+    }
+
+    /// Closest node to `(x, y)`, ties going to the lower index.
+    pub fn nearest(&self, x: f32, y: f32) -> Option<usize> {
+        self.k_nearest(x, y, 1).first().copied()
+    }
+
+    /// Up to `k` nodes closest to `(x, y)`, nearest first, ties going to the
+    /// lower index. Cells are visited best-first by their distance to the
+    /// query and the search stops once no unvisited cell can beat the
+    /// current k-th candidate.
+    pub fn k_nearest(&self, x: f32, y: f32, k: usize) -> Vec<usize> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        // Max-heap of the best k so far: the top is the one to evict
+        let mut best: BinaryHeap<Candidate> = BinaryHeap::with_capacity(k + 1);
+        let mut cells = BinaryHeap::new();
+        cells.push(CellEntry {
+            dist2: self.bounds.distance_squared(x, y),
+            cell: self,
+        });
+
+        while let Some(CellEntry { dist2, cell }) = cells.pop() {
+            if best.len() == k && best.peek().is_some_and(|worst| dist2 > worst.dist2) {
+                break;
+            }
+            for e in &cell.nodes {
+                let candidate = Candidate {
+                    dist2: (e.x - x).powi(2) + (e.y - y).powi(2),
+                    idx: e.idx,
+                };
+                if best.len() < k {
+                    best.push(candidate);
+                } else if best.peek().is_some_and(|worst| candidate < *worst) {
+                    best.pop();
+                    best.push(candidate);
+                }
+            }
+            if let Some(ref children) = cell.children {
+                for child in children.iter().filter(|c| c.total_mass > 0.0) {
+                    cells.push(CellEntry {
+                        dist2: child.bounds.distance_squared(x, y),
+                        cell: child,
+                    });
+                }
+            }
+        }
+
+        best.into_sorted_vec().into_iter().map(|c| c.idx).collect()
+    }
+
+    /// Number of points in this cell and its descendants.
+    pub fn total_mass(&self) -> f32 {
+        self.total_mass
+    }
+
+    pub fn center_of_mass(&self) -> (f32, f32) {
+        self.center_of_mass
+    }
+
+    /// Barnes-Hut approximation of the inverse-square repulsion that every
+    /// stored point exerts on `(x, y)`, with unit mass and unit strength
+    /// (callers scale the result). A cell is summarised by its center of
+    /// mass once `size / distance < theta`; `theta = 0` is exact.
+    pub fn approximate_force(&self, x: f32, y: f32, theta: f32) -> (f32, f32) {
+        if self.total_mass == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let (cx, cy) = self.center_of_mass;
+        let dist = ((x - cx).powi(2) + (y - cy).powi(2)).sqrt();
+        let size = self.bounds.width().max(self.bounds.height());
+        if dist > SELF_EPSILON && size / dist < theta {
+            return repulsion_from(x, y, cx, cy, self.total_mass);
+        }
+
+        let mut force = (0.0, 0.0);
+        for e in &self.nodes {
+            let (fx, fy) = repulsion_from(x, y, e.x, e.y, 1.0);
+            force.0 += fx;
+            force.1 += fy;
+        }
+        if let Some(ref children) = self.children {
+            for child in children.iter() {
+                let (fx, fy) = child.approximate_force(x, y, theta);
+                force.0 += fx;
+                force.1 += fy;
+            }
+        }
+        force
+    }
+
+    fn add_mass(&mut self, x: f32, y: f32) {
+        let total = self.total_mass + 1.0;
+        self.center_of_mass = (
+            (self.center_of_mass.0 * self.total_mass + x) / total,
+            (self.center_of_mass.1 * self.total_mass + y) / total,
+        );
+        self.total_mass = total;
+    }
+
+    fn remove_mass(&mut self, x: f32, y: f32) {
+        let total = self.total_mass - 1.0;
+        self.center_of_mass = if total > 0.0 {
+            (
+                (self.center_of_mass.0 * self.total_mass - x) / total,
+                (self.center_of_mass.1 * self.total_mass - y) / total,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        self.total_mass = total;
+    }
+
     fn subdivide(&mut self) {
         let quads = self.bounds.subdivide();
-        self.children = Some(Box::new(quads.map(|b| Quadtree::new(b, self.capacity))));
+        let (capacity, max_depth, depth) = (self.capacity, self.max_depth, self.depth + 1);
+        self.children = Some(Box::new(
+            quads.map(|b| Quadtree::cell(b, capacity, max_depth, depth)),
+        ));
+    }
+}
+
+/// Uniform-grid index: each node goes in the square cell of side
+/// `cell_size` containing its position. For near-uniform layouts this
+/// beats the quadtree on both build and query, with no bounds to fix up
+/// front; clustered layouts pile into few cells and should prefer the
+/// quadtree.
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    /// Non-positive or non-finite sizes fall back to one unit per cell.
+    pub fn new(cell_size: f32) -> Self {
+        let cell_size = if cell_size.is_finite() && cell_size > 0.0 {
+            cell_size
+        } else {
+            1.0
+        };
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn from_nodes(nodes: &[Node], cell_size: f32) -> Self {
+        let mut hash = Self::new(cell_size);
+        for (i, node) in nodes.iter().enumerate() {
+            hash.insert(i, node);
+        }
+        hash
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn insert(&mut self, node_idx: usize, node: &Node) {
+        let key = self.key(node.x, node.y);
+        self.cells.entry(key).or_default().push(node_idx);
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Nodes whose position in `nodes` lies within `radius` of `(x, y)`
+    /// (boundary inclusive), as `Quadtree::query_point`. Only cells are
+    /// stored, so `nodes` must be the slice the indices were inserted
+    /// from, with no node moved since.
+    pub fn query_point(&self, x: f32, y: f32, radius: f32, nodes: &[Node]) -> Vec<usize> {
+        let mut result = Vec::new();
+        if radius.is_nan() || radius < 0.0 {
+            return result;
+        }
+        let (min_cx, min_cy) = self.key(x - radius, y - radius);
+        let (max_cx, max_cy) = self.key(x + radius, y + radius);
+        let r2 = radius * radius;
+        // Wide queries over a sparse grid visit the occupied cells rather
+        // than every cell in range
+        let span = (max_cx as i64 - min_cx as i64 + 1) * (max_cy as i64 - min_cy as i64 + 1);
+        let within = |&idx: &usize| {
+            let node = &nodes[idx];
+            (node.x - x).powi(2) + (node.y - y).powi(2) <= r2
+        };
+        if span > self.cells.len() as i64 {
+            for (&(cx, cy), bucket) in &self.cells {
+                if (min_cx..=max_cx).contains(&cx) && (min_cy..=max_cy).contains(&cy) {
+                    result.extend(bucket.iter().filter(|i| within(i)));
+                }
+            }
+        } else {
+            for cx in min_cx..=max_cx {
+                for cy in min_cy..=max_cy {
+                    if let Some(bucket) = self.cells.get(&(cx, cy)) {
+                        result.extend(bucket.iter().filter(|i| within(i)));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn key(&self, x: f32, y: f32) -> (i32, i32) {
+        // `as` saturates, so far-off points share the edge cells
+        (
+            (x / self.cell_size).floor() as i32,
+            (y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+/// A k-nearest result, ordered by distance then index.
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate {
+    dist2: f32,
+    idx: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist2
+            .total_cmp(&other.dist2)
+            .then_with(|| self.idx.cmp(&other.idx))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Pending cell in the best-first search. Ordering is reversed so the
+/// max-heap pops the closest cell first.
+struct CellEntry<'a> {
+    dist2: f32,
+    cell: &'a Quadtree,
+}
+
+impl PartialEq for CellEntry<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for CellEntry<'_> {}
+
+impl Ord for CellEntry<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.dist2.total_cmp(&self.dist2)
+    }
+}
+
+impl PartialOrd for CellEntry<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Force pushing `(x, y)` away from a body of `mass` at `(bx, by)`.
+fn repulsion_from(x: f32, y: f32, bx: f32, by: f32, mass: f32) -> (f32, f32) {
+    let (dx, dy) = (x - bx, y - by);
+    let len = (dx * dx + dy * dy).sqrt();
+    if len < SELF_EPSILON {
+        return (0.0, 0.0); // self-interaction
     }
+    let dist = len.max(MIN_FORCE_DIST);
+    let magnitude = mass / (dist * dist);
+    (dx / len * magnitude, dy / len * magnitude)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::graph::types::Node;
-
-    fn make_node(id: u32, x: f32, y: f32) -> Node {
-        Node {
-            id,
-            label: String::new(),
-            pagerank: 0.0,
-            degree: 0,
-            x,
-            y,
-        }
-    }
+    use crate::test_utils::node_at;
 
     fn world_bounds() -> AABB {
         AABB {
@@ -89,12 +529,12 @@ mod tests {
     }
 
     #[test]
-    fn insert_and_query_returns_candidates() {
+    fn insert_and_query_filters_by_distance() {
         let mut qt = Quadtree::new(world_bounds(), 4);
-        let nodes = vec![
-            make_node(0, 10.0, 10.0),
-            make_node(1, 90.0, 90.0),
-            make_node(2, 50.0, 50.0),
+        let nodes = [
+            node_at(0, 10.0, 10.0),
+            node_at(1, 90.0, 90.0),
+            node_at(2, 50.0, 50.0),
         ];
         for (i, n) in nodes.iter().enumerate() {
             assert!(qt.insert(i, n));
@@ -104,17 +544,27 @@ mod tests {
         let all = qt.query_point(50.0, 50.0, 200.0);
         assert_eq!(all.len(), 3);
 
-        // Small radius far from any node — no cell intersects
-        let none = qt.query_point(0.0, 100.0, 0.1);
-        // Candidates come from cells whose AABB intersects the circle;
-        // exact distance filtering is the caller's responsibility
-        assert!(none.is_empty() || none.iter().all(|&i| i < 3));
+        // The root cell intersects, but no node is within the radius
+        assert!(qt.query_point(0.0, 100.0, 0.1).is_empty());
+        assert_eq!(qt.query_point(12.0, 12.0, 5.0), vec![0]);
+    }
+
+    #[test]
+    fn query_radius_boundary() {
+        let mut qt = Quadtree::new(world_bounds(), 4);
+        // Distances 3-4-5 triangle from (50, 50): exactly 5.0, then just past
+        qt.insert(0, &node_at(0, 53.0, 54.0));
+        qt.insert(1, &node_at(1, 53.0, 54.01));
+        qt.insert(2, &node_at(2, 50.0, 45.5));
+        let mut inside = qt.query_point(50.0, 50.0, 5.0);
+        inside.sort_unstable();
+        assert_eq!(inside, vec![0, 2]);
     }
 
     #[test]
     fn query_outside_bounds_returns_empty() {
         let mut qt = Quadtree::new(world_bounds(), 4);
-        let n = make_node(0, 50.0, 50.0);
+        let n = node_at(0, 50.0, 50.0);
         qt.insert(0, &n);
 
         // Query circle entirely outside the tree bounds
@@ -125,7 +575,7 @@ mod tests {
     #[test]
     fn insert_outside_bounds_fails() {
         let mut qt = Quadtree::new(world_bounds(), 4);
-        let n = make_node(0, 200.0, 200.0);
+        let n = node_at(0, 200.0, 200.0);
         assert!(!qt.insert(0, &n));
     }
 
@@ -138,10 +588,10 @@ mod tests {
     #[test]
     fn subdivide_on_overflow() {
         let mut qt = Quadtree::new(world_bounds(), 2);
-        let nodes = vec![
-            make_node(0, 10.0, 10.0),
-            make_node(1, 20.0, 20.0),
-            make_node(2, 30.0, 30.0),
+        let nodes = [
+            node_at(0, 10.0, 10.0),
+            node_at(1, 20.0, 20.0),
+            node_at(2, 30.0, 30.0),
         ];
         for (i, n) in nodes.iter().enumerate() {
             qt.insert(i, n);
@@ -149,4 +599,367 @@ mod tests {
         // After inserting 3 nodes with capacity 2, tree should have subdivided
         assert!(qt.children.is_some());
     }
+
+    #[test]
+    fn mass_summary() {
+        let mut qt = Quadtree::new(world_bounds(), 1);
+        for (i, (x, y)) in [(10.0, 10.0), (30.0, 10.0), (20.0, 40.0)]
+            .into_iter()
+            .enumerate()
+        {
+            qt.insert(i, &node_at(i as u32, x, y));
+        }
+        assert_eq!(qt.total_mass(), 3.0);
+        let (cx, cy) = qt.center_of_mass();
+        assert!((cx - 20.0).abs() < 1e-4 && (cy - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn approximate_force_matches_brute_force() {
+        let mut state = 7u32;
+        let mut next = || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 24) as f32 * 100.0
+        };
+        let points: Vec<(f32, f32)> = (0..200).map(|_| (next(), next())).collect();
+        let mut qt = Quadtree::new(world_bounds(), 4);
+        for (i, &(x, y)) in points.iter().enumerate() {
+            qt.insert(i, &node_at(i as u32, x, y));
+        }
+
+        for &(x, y) in points.iter().take(20) {
+            let (mut bx, mut by) = (0.0f32, 0.0f32);
+            for &(px, py) in &points {
+                let (fx, fy) = repulsion_from(x, y, px, py, 1.0);
+                bx += fx;
+                by += fy;
+            }
+
+            let exact = qt.approximate_force(x, y, 0.0);
+            assert!((exact.0 - bx).abs() < 1e-3 && (exact.1 - by).abs() < 1e-3);
+
+            let (ax, ay) = qt.approximate_force(x, y, 0.3);
+            let error = ((ax - bx).powi(2) + (ay - by).powi(2)).sqrt();
+            let magnitude = (bx * bx + by * by).sqrt();
+            assert!(
+                error <= 0.05 * magnitude + 1e-3,
+                "error {error} vs magnitude {magnitude}"
+            );
+        }
+    }
+
+    #[test]
+    fn approximate_force_empty_tree() {
+        let qt = Quadtree::new(world_bounds(), 4);
+        assert_eq!(qt.approximate_force(50.0, 50.0, 0.5), (0.0, 0.0));
+    }
+
+    #[test]
+    fn remove_and_relocate() {
+        let mut qt = Quadtree::new(world_bounds(), 2);
+        let a = node_at(0, 10.0, 10.0);
+        let b = node_at(1, 20.0, 20.0);
+        let c = node_at(2, 30.0, 30.0);
+        for (i, n) in [&a, &b, &c].into_iter().enumerate() {
+            qt.insert(i, n);
+        }
+
+        let moved = node_at(2, 90.0, 90.0);
+        assert!(qt.relocate(2, &c, &moved));
+        assert!(qt.query_point(30.0, 30.0, 1.0).iter().all(|&i| i != 2));
+        assert!(qt.query_point(90.0, 90.0, 1.0).contains(&2));
+
+        assert!(qt.remove(0, &a));
+        assert!(!qt.remove(0, &a));
+        let mut remaining = qt.query_point(50.0, 50.0, 200.0);
+        remaining.sort_unstable();
+        assert_eq!(remaining, vec![1, 2]);
+        assert_eq!(qt.total_mass(), 2.0);
+        let (cx, cy) = qt.center_of_mass();
+        assert!((cx - 55.0).abs() < 1e-4 && (cy - 55.0).abs() < 1e-4);
+        // Two points fit in one leaf again, so the subdivision is gone
+        assert!(qt.children.is_none());
+    }
+
+    #[test]
+    fn remove_last_node_leaves_empty_leaf() {
+        let mut qt = Quadtree::new(world_bounds(), 1);
+        let nodes: Vec<Node> = (0..8)
+            .map(|i| node_at(i, 5.0 + i as f32 * 10.0, 5.0 + i as f32 * 3.0))
+            .collect();
+        for (i, n) in nodes.iter().enumerate() {
+            qt.insert(i, n);
+        }
+        assert!(qt.children.is_some());
+        for (i, n) in nodes.iter().enumerate() {
+            assert!(qt.remove(i, n));
+        }
+        assert!(qt.children.is_none());
+        assert_eq!(qt.total_mass(), 0.0);
+        assert!(qt.query_point(50.0, 50.0, 200.0).is_empty());
+    }
+
+    #[test]
+    fn repeated_moves_do_not_grow_tree() {
+        let mut qt = Quadtree::new(world_bounds(), 4);
+        let mut nodes: Vec<Node> = (0..16)
+            .map(|i| node_at(i, (i * 6) as f32, (i * 5) as f32))
+            .collect();
+        for (i, n) in nodes.iter().enumerate() {
+            qt.insert(i, n);
+        }
+        for frame in 0..50 {
+            for (i, node) in nodes.iter_mut().enumerate() {
+                let mut next = node.clone();
+                next.x = (node.x + 7.0 + frame as f32) % 100.0;
+                next.y = (node.y + 3.0 * i as f32) % 100.0;
+                assert!(qt.relocate(i, node, &next));
+                *node = next;
+            }
+        }
+        assert_eq!(qt.query_point(50.0, 50.0, 200.0).len(), 16);
+        assert_eq!(qt.total_mass(), 16.0);
+        let fresh = {
+            let mut t = Quadtree::new(world_bounds(), 4);
+            for (i, n) in nodes.iter().enumerate() {
+                t.insert(i, n);
+            }
+            t
+        };
+        assert!(cell_count(&qt) <= 2 * cell_count(&fresh));
+    }
+
+    fn cell_count(qt: &Quadtree) -> usize {
+        1 + qt
+            .children
+            .as_ref()
+            .map_or(0, |c| c.iter().map(cell_count).sum())
+    }
+
+    #[test]
+    fn coincident_points_stop_at_max_depth() {
+        let mut qt = Quadtree::new(world_bounds(), 4);
+        let node = node_at(0, 0.0, 0.0);
+        for i in 0..1000 {
+            assert!(qt.insert(i, &node));
+        }
+        assert_eq!(qt.query_point(0.0, 0.0, 1.0).len(), 1000);
+        assert_eq!(depth_of(&qt), DEFAULT_MAX_DEPTH);
+    }
+
+    #[test]
+    fn default_capacity_point_queries_match_brute_force() {
+        let points = random_points(10_000, 42);
+        let mut qt = Quadtree::with_default_capacity(world_bounds());
+        for (i, &(x, y)) in points.iter().enumerate() {
+            assert!(qt.insert(i, &node_at(i as u32, x, y)));
+        }
+
+        for (qi, &(qx, qy)) in random_points(40, 7).iter().enumerate() {
+            let radius = [0.0, 0.5, 2.0, 8.0][qi % 4];
+            let expected: Vec<usize> = points
+                .iter()
+                .enumerate()
+                .filter(|&(_, &(x, y))| (x - qx).powi(2) + (y - qy).powi(2) <= radius * radius)
+                .map(|(i, _)| i)
+                .collect();
+            let mut got = qt.query_point(qx, qy, radius);
+            got.sort_unstable();
+            assert_eq!(got, expected, "query {qi}");
+        }
+        // Every stored point finds itself
+        for (i, &(x, y)) in points.iter().enumerate().step_by(97) {
+            assert!(qt.query_point(x, y, 0.0).contains(&i));
+        }
+    }
+
+    #[test]
+    fn custom_max_depth() {
+        let mut qt = Quadtree::with_max_depth(world_bounds(), 1, 2);
+        let node = node_at(0, 60.0, 60.0);
+        for i in 0..10 {
+            qt.insert(i, &node);
+        }
+        assert_eq!(depth_of(&qt), 2);
+        assert_eq!(qt.query_point(60.0, 60.0, 1.0).len(), 10);
+    }
+
+    fn depth_of(qt: &Quadtree) -> u32 {
+        qt.children
+            .as_ref()
+            .map_or(0, |c| 1 + c.iter().map(depth_of).max().unwrap_or(0))
+    }
+
+    fn random_points(count: usize, seed: u32) -> Vec<(f32, f32)> {
+        let mut state = seed;
+        let mut next = || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 24) as f32 * 100.0
+        };
+        (0..count).map(|_| (next(), next())).collect()
+    }
+
+    #[test]
+    fn k_nearest_matches_brute_force() {
+        let points = random_points(300, 99);
+        let mut qt = Quadtree::new(world_bounds(), 4);
+        for (i, &(x, y)) in points.iter().enumerate() {
+            qt.insert(i, &node_at(i as u32, x, y));
+        }
+
+        for &(qx, qy) in &random_points(25, 5) {
+            let mut brute: Vec<(f32, usize)> = points
+                .iter()
+                .enumerate()
+                .map(|(i, &(x, y))| ((x - qx).powi(2) + (y - qy).powi(2), i))
+                .collect();
+            brute.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            let expected: Vec<usize> = brute.iter().take(7).map(|&(_, i)| i).collect();
+
+            assert_eq!(qt.k_nearest(qx, qy, 7), expected);
+            assert_eq!(qt.nearest(qx, qy), Some(expected[0]));
+        }
+    }
+
+    #[test]
+    fn k_nearest_ties_and_limits() {
+        let mut qt = Quadtree::new(world_bounds(), 4);
+        // Four nodes equidistant from the centre, inserted out of order
+        for (i, (x, y)) in [(60.0, 50.0), (40.0, 50.0), (50.0, 60.0), (50.0, 40.0)]
+            .into_iter()
+            .enumerate()
+            .rev()
+        {
+            qt.insert(i, &node_at(i as u32, x, y));
+        }
+        assert_eq!(qt.k_nearest(50.0, 50.0, 2), vec![0, 1]);
+        assert_eq!(qt.k_nearest(50.0, 50.0, 10).len(), 4);
+        assert!(qt.k_nearest(50.0, 50.0, 0).is_empty());
+        assert!(Quadtree::new(world_bounds(), 4).nearest(1.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn query_rect_selects_corner_of_grid() {
+        let mut qt = Quadtree::new(world_bounds(), 4);
+        // 10x10 grid at 5, 15, ..., 95
+        for i in 0..100 {
+            let (col, row) = (i % 10, i / 10);
+            let node = node_at(i as u32, 5.0 + col as f32 * 10.0, 5.0 + row as f32 * 10.0);
+            qt.insert(i, &node);
+        }
+        let corner = AABB {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 25.0,
+            max_y: 15.0,
+        };
+        let mut selected = qt.query_rect(&corner);
+        selected.sort_unstable();
+        // Columns 0-2 of rows 0-1; the row at y = 15 sits on the edge
+        assert_eq!(selected, vec![0, 1, 2, 10, 11, 12]);
+
+        let outside = AABB {
+            min_x: 200.0,
+            min_y: 200.0,
+            max_x: 300.0,
+            max_y: 300.0,
+        };
+        assert!(qt.query_rect(&outside).is_empty());
+    }
+
+    #[test]
+    fn from_nodes_holds_every_node() {
+        let nodes: Vec<Node> = random_points(50, 3)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (x, y))| node_at(i as u32, x * 4.0 - 200.0, y - 50.0))
+            .collect();
+        let qt = Quadtree::from_nodes(&nodes, 4);
+        assert_eq!(qt.total_mass(), 50.0);
+
+        let covering = AABB {
+            min_x: -1000.0,
+            min_y: -1000.0,
+            max_x: 1000.0,
+            max_y: 1000.0,
+        };
+        let mut all = qt.query_rect(&covering);
+        all.sort_unstable();
+        assert_eq!(all, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn from_nodes_degenerate_inputs() {
+        let empty = Quadtree::from_nodes(&[], 4);
+        assert_eq!(empty.total_mass(), 0.0);
+        assert!(empty.bounds.contains(0.0, 0.0));
+
+        // A single point still gets a non-zero box
+        let single = Quadtree::from_nodes(&[node_at(0, 7.0, -3.0)], 4);
+        assert_eq!(single.nearest(7.0, -3.0), Some(0));
+        assert!(single.bounds.width() > 0.0);
+    }
+
+    #[test]
+    fn spatial_hash_matches_brute_force() {
+        let points = random_points(400, 17);
+        let nodes: Vec<Node> = points
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| node_at(i as u32, x, y))
+            .collect();
+
+        for cell_size in [3.0, 10.0, 250.0] {
+            let hash = SpatialHash::from_nodes(&nodes, cell_size);
+            for (qi, &(qx, qy)) in random_points(30, 8).iter().enumerate() {
+                let radius = [0.0, 2.5, 9.0, 40.0, 500.0][qi % 5];
+                let expected: Vec<usize> = (0..nodes.len())
+                    .filter(|&i| {
+                        (nodes[i].x - qx).powi(2) + (nodes[i].y - qy).powi(2) <= radius * radius
+                    })
+                    .collect();
+                let mut got = hash.query_point(qx, qy, radius, &nodes);
+                got.sort_unstable();
+                assert_eq!(got, expected, "cell {cell_size}, query {qi}");
+            }
+        }
+    }
+
+    #[test]
+    fn spatial_hash_agrees_with_quadtree() {
+        let points = random_points(200, 3);
+        let nodes: Vec<Node> = points
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| node_at(i as u32, x, y))
+            .collect();
+        let qt = Quadtree::from_nodes(&nodes, 4);
+        let hash = SpatialHash::from_nodes(&nodes, 7.0);
+        for &(qx, qy) in &random_points(20, 41) {
+            let mut a = qt.query_point(qx, qy, 12.0);
+            let mut b = hash.query_point(qx, qy, 12.0, &nodes);
+            a.sort_unstable();
+            b.sort_unstable();
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn spatial_hash_boundary_negative_coords_and_clear() {
+        let nodes = [node_at(1, -5.0, -5.0), node_at(2, 5.0, 0.0)];
+        let mut hash = SpatialHash::new(4.0);
+        for (i, n) in nodes.iter().enumerate() {
+            hash.insert(i, n);
+        }
+        // Exactly on the radius counts
+        assert_eq!(hash.query_point(0.0, 0.0, 5.0, &nodes), vec![1]);
+        assert_eq!(hash.query_point(-4.0, -4.0, 2.0, &nodes), vec![0]);
+        assert!(hash.query_point(0.0, 0.0, -1.0, &nodes).is_empty());
+
+        hash.clear();
+        assert!(hash.query_point(0.0, 0.0, 50.0, &nodes).is_empty());
+        // Degenerate cell sizes fall back rather than dividing by zero
+        assert_eq!(SpatialHash::new(0.0).cell_size(), 1.0);
+        assert_eq!(SpatialHash::new(f32::NAN).cell_size(), 1.0);
+    }
 }
@@ -1,6 +1,8 @@
-use std::collections::HashMap;
+use crate::graph::search::LabelIndex;
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Node {
     pub id: u32,
     pub label: String,
@@ -8,12 +10,129 @@ pub struct Node {
     pub degree: u16,
     pub x: f32,
     pub y: f32,
+    /// Community id, e.g. from `louvain`. Persisted by the `HasCommunities`
+    /// format flag so partitions survive a reload; None when unassigned.
+    pub community: Option<u32>,
+    /// Free-form domain data (type, timestamp, group, ...). Empty unless
+    /// set by the caller; an empty map doesn't allocate, so decoding is
+    /// no slower for it.
+    pub attributes: HashMap<String, AttributeValue>,
 }
 
-#[derive(Debug, Clone)]
+/// A typed node attribute value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Float(f64),
+    Int(i64),
+    Str(String),
+    Bool(bool),
+}
+
+impl AttributeValue {
+    /// Numeric value of a `Float` or `Int`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Float(v) => Some(v),
+            Self::Int(v) => Some(v as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Int(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl From<f64> for AttributeValue {
+    fn from(v: f64) -> Self {
+        Self::Float(v)
+    }
+}
+
+impl From<i64> for AttributeValue {
+    fn from(v: i64) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(v: &str) -> Self {
+        Self::Str(v.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(v: String) -> Self {
+        Self::Str(v)
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl Node {
+    pub fn get_attr(&self, key: &str) -> Option<&AttributeValue> {
+        self.attributes.get(key)
+    }
+
+    /// Sets `key`, returning the value it replaced.
+    pub fn set_attr(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<AttributeValue>,
+    ) -> Option<AttributeValue> {
+        self.attributes.insert(key.into(), value.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Edge {
     pub source: u32,
     pub target: u32,
+    /// Defaults to `1.0` for unweighted graphs.
+    pub weight: f32,
+}
+
+/// Whether edges are read as `source -> target` or as unordered pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphKind {
+    /// The default, and how every graph was treated before direction existed.
+    #[default]
+    Undirected,
+    Directed,
+}
+
+/// What `Graph::dedup_edges` removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DedupReport {
+    pub duplicates: usize,
+    pub self_loops: usize,
+}
+
+impl DedupReport {
+    pub fn removed(&self) -> usize {
+        self.duplicates + self.self_loops
+    }
 }
 
 #[derive(Debug)]
@@ -21,22 +140,110 @@ pub struct Graph {
     nodes: Vec<Node>,
     edges: Vec<Edge>,
     id_to_index: HashMap<u32, usize>,
+    kind: GraphKind,
+    /// Undirected neighbor indices per node, in edge order. Edges whose
+    /// endpoints aren't in the node table are left out.
+    adjacency: Vec<Vec<usize>>,
+    /// Indices into `edges` touching each node, in edge order. A self-loop
+    /// is listed once.
+    incident: Vec<Vec<usize>>,
+    /// Directed successor/predecessor indices. Only built for directed
+    /// graphs; undirected graphs answer both from `adjacency`.
+    out_adjacency: Vec<Vec<usize>>,
+    in_adjacency: Vec<Vec<usize>>,
+    /// Built by the first label lookup; reset by anything that can change
+    /// a label.
+    label_index: OnceCell<LabelIndex>,
+    /// First edge index per endpoint pair, keyed by `edge_key`. Built by
+    /// the first edge lookup; kept current by `add_edge`, reset by
+    /// `reindex`.
+    edge_index: OnceCell<HashMap<(u32, u32), usize>>,
+    /// Undirected neighbor count per node, clamped to `u16::MAX`. Built by
+    /// the first `degree` call; kept current by `add_node` and `add_edge`,
+    /// reset by `reindex`.
+    degrees: OnceCell<Vec<u16>>,
 }
 
 impl Graph {
+    /// Builds an undirected graph.
     pub fn new(nodes: Vec<Node>, edges: Vec<Edge>) -> Self {
-        let id_to_index = nodes
+        Self::with_kind(nodes, edges, GraphKind::Undirected)
+    }
+
+    pub fn with_kind(nodes: Vec<Node>, edges: Vec<Edge>, kind: GraphKind) -> Self {
+        let mut graph = Self {
+            nodes,
+            edges,
+            id_to_index: HashMap::new(),
+            kind,
+            adjacency: Vec::new(),
+            incident: Vec::new(),
+            out_adjacency: Vec::new(),
+            in_adjacency: Vec::new(),
+            label_index: OnceCell::new(),
+            edge_index: OnceCell::new(),
+            degrees: OnceCell::new(),
+        };
+        graph.reindex();
+        graph
+    }
+
+    /// Undirected graph from `(source, target)` id pairs, each edge with
+    /// weight 1.0. Every id mentioned gets a node (empty label, zero rank
+    /// and position), in order of first appearance, and degrees are
+    /// computed. The quickest way to spin up a graph for tests or from
+    /// another in-memory structure.
+    pub fn from_edges(edges: &[(u32, u32)]) -> Self {
+        let mut seen = HashSet::new();
+        let nodes = edges
+            .iter()
+            .flat_map(|&(source, target)| [source, target])
+            .filter(|&id| seen.insert(id))
+            .map(|id| Node {
+                id,
+                ..Node::default()
+            })
+            .collect();
+        let edges = edges
+            .iter()
+            .map(|&(source, target)| Edge {
+                source,
+                target,
+                weight: 1.0,
+            })
+            .collect();
+        let mut graph = Self::new(nodes, edges);
+        graph.recompute_degrees();
+        graph
+    }
+
+    /// Rebuilds `id_to_index` and the adjacency lists from scratch.
+    fn reindex(&mut self) {
+        self.id_to_index = self
+            .nodes
             .iter()
             .enumerate()
             .map(|(i, node)| (node.id, i))
             .collect();
-        Self {
-            nodes,
-            edges,
-            id_to_index,
-        }
+        (self.adjacency, self.incident) =
+            build_adjacency(self.nodes.len(), &self.edges, &self.id_to_index);
+        (self.out_adjacency, self.in_adjacency) = match self.kind {
+            GraphKind::Undirected => (Vec::new(), Vec::new()),
+            GraphKind::Directed => {
+                build_directed_adjacency(self.nodes.len(), &self.edges, &self.id_to_index)
+            }
+        };
+        self.label_index.take();
+        self.edge_index.take();
+        self.degrees.take();
     }
 
+    pub fn kind(&self) -> GraphKind {
+        self.kind
+    }
+    pub fn is_directed(&self) -> bool {
+        self.kind == GraphKind::Directed
+    }
     pub fn node_count(&self) -> usize {
         self.nodes.len()
     }
@@ -46,59 +253,402 @@ impl Graph {
     pub fn nodes(&self) -> &[Node] {
         &self.nodes
     }
+    /// Drops the label index, since callers may rename nodes.
     pub fn nodes_mut(&mut self) -> &mut [Node] {
+        self.label_index.take();
         &mut self.nodes
     }
     pub fn edges(&self) -> &[Edge] {
         &self.edges
     }
+    /// `(source, target)` id pairs in edge order, dropping weights; the
+    /// counterpart of `from_edges`.
+    pub fn edge_list(&self) -> Vec<(u32, u32)> {
+        self.edges.iter().map(|e| (e.source, e.target)).collect()
+    }
     pub fn node_by_id(&self, id: u32) -> Option<&Node> {
         self.id_to_index.get(&id).map(|&i| &self.nodes[i])
     }
     pub fn node_index(&self, id: u32) -> Option<usize> {
         self.id_to_index.get(&id).copied()
     }
+    /// Neighbors in either direction, in edge order.
     pub fn neighbors(&self, node_id: u32) -> Vec<u32> {
-        self.edges
-            .iter()
-            .filter_map(|e| {
-                if e.source == node_id {
-                    Some(e.target)
-                } else if e.target == node_id {
-                    Some(e.source)
-                } else {
-                    None
+        self.ids_of(node_id, Self::neighbor_indices)
+    }
+    /// Targets of edges leaving `node_id`. Same as `neighbors` when the
+    /// graph is undirected.
+    pub fn out_neighbors(&self, node_id: u32) -> Vec<u32> {
+        self.ids_of(node_id, Self::out_neighbor_indices)
+    }
+    /// Sources of edges entering `node_id`. Same as `neighbors` when the
+    /// graph is undirected.
+    pub fn in_neighbors(&self, node_id: u32) -> Vec<u32> {
+        self.ids_of(node_id, Self::in_neighbor_indices)
+    }
+    /// Undirected neighbors of the node at `node_index`, as node indices.
+    /// O(1); empty for an out-of-range index.
+    pub fn neighbor_indices(&self, node_index: usize) -> &[usize] {
+        self.adjacency.get(node_index).map_or(&[], |v| v)
+    }
+    /// Indices into `edges()` of the edges touching the node at
+    /// `node_index`, in edge order (a self-loop once). O(1); empty for an
+    /// out-of-range index.
+    pub fn incident_edge_indices(&self, node_index: usize) -> &[usize] {
+        self.incident.get(node_index).map_or(&[], |v| v)
+    }
+    pub fn out_neighbor_indices(&self, node_index: usize) -> &[usize] {
+        match self.kind {
+            GraphKind::Undirected => self.neighbor_indices(node_index),
+            GraphKind::Directed => self.out_adjacency.get(node_index).map_or(&[], |v| v),
+        }
+    }
+    pub fn in_neighbor_indices(&self, node_index: usize) -> &[usize] {
+        match self.kind {
+            GraphKind::Undirected => self.neighbor_indices(node_index),
+            GraphKind::Directed => self.in_adjacency.get(node_index).map_or(&[], |v| v),
+        }
+    }
+
+    /// Edges with `id` at either end, whatever the graph's direction, in
+    /// edge order. Empty for an unknown id.
+    pub fn incident_edges(&self, id: u32) -> impl Iterator<Item = &Edge> {
+        let indices = self
+            .node_index(id)
+            .map_or(&[][..], |i| self.incident_edge_indices(i));
+        indices.iter().map(|&e| &self.edges[e])
+    }
+
+    /// Ids of nodes at most `hops` undirected steps from `id`, in BFS order
+    /// (nearer first), with `id` itself first when `include_source` is
+    /// set. Empty for an unknown id.
+    pub fn neighbors_within(&self, id: u32, hops: u32, include_source: bool) -> Vec<u32> {
+        let Some(start) = self.node_index(id) else {
+            return Vec::new();
+        };
+        let mut seen = vec![false; self.nodes.len()];
+        seen[start] = true;
+        let mut order = vec![start];
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((i, depth)) = queue.pop_front() {
+            if depth == hops {
+                continue;
+            }
+            for &j in self.neighbor_indices(i) {
+                if !seen[j] {
+                    seen[j] = true;
+                    order.push(j);
+                    queue.push_back((j, depth + 1));
                 }
-            })
+            }
+        }
+        let skip = usize::from(!include_source);
+        order[skip..].iter().map(|&i| self.nodes[i].id).collect()
+    }
+
+    /// Nodes whose label is exactly `label`, in table order. Empty labels
+    /// never match. The first label lookup builds an index in O(V); later
+    /// ones are O(1) until the nodes change.
+    pub fn nodes_by_label(&self, label: &str) -> Vec<&Node> {
+        self.labels()
+            .exact(label)
+            .iter()
+            .map(|&i| &self.nodes[i])
+            .collect()
+    }
+
+    /// Ids of up to `limit` nodes whose label contains `query`, ignoring
+    /// case: prefix matches first (shorter labels first), then other
+    /// substring matches (earlier matches first). An empty query matches
+    /// nothing. Meant for a search box; prefix lookup is O(log V) but the
+    /// substring pass scans every label.
+    pub fn search_labels(&self, query: &str, limit: usize) -> Vec<u32> {
+        self.labels()
+            .search(query, limit)
+            .into_iter()
+            .map(|i| self.nodes[i].id)
             .collect()
     }
+
+    fn labels(&self) -> &LabelIndex {
+        self.label_index
+            .get_or_init(|| LabelIndex::build(&self.nodes))
+    }
+
+    /// Whether an edge joins `source` to `target`: in that direction on a
+    /// directed graph, either way round otherwise. Edges with an unknown
+    /// endpoint don't count. O(1) after the first lookup builds an index.
+    pub fn has_edge(&self, source: u32, target: u32) -> bool {
+        self.edge_slot(source, target).is_some()
+    }
+
+    /// Weight of the edge `has_edge` would find, or None. With parallel
+    /// edges this is the first one's weight.
+    pub fn edge_weight(&self, source: u32, target: u32) -> Option<f32> {
+        self.edge_slot(source, target).map(|e| self.edges[e].weight)
+    }
+
+    fn edge_slot(&self, source: u32, target: u32) -> Option<usize> {
+        let index = self.edge_index.get_or_init(|| {
+            let mut index = HashMap::new();
+            for (e, edge) in self.edges.iter().enumerate() {
+                if self.node_index(edge.source).is_some() && self.node_index(edge.target).is_some()
+                {
+                    index
+                        .entry(self.edge_key(edge.source, edge.target))
+                        .or_insert(e);
+                }
+            }
+            index
+        });
+        index.get(&self.edge_key(source, target)).copied()
+    }
+
+    /// Orders an undirected pair so both orientations share a key.
+    fn edge_key(&self, source: u32, target: u32) -> (u32, u32) {
+        if self.is_directed() || source <= target {
+            (source, target)
+        } else {
+            (target, source)
+        }
+    }
+
+    /// Nodes satisfying `pred`, in table order.
+    pub fn nodes_where(&self, pred: impl Fn(&Node) -> bool) -> impl Iterator<Item = &Node> {
+        self.nodes.iter().filter(move |n| pred(n))
+    }
+
+    /// Undirected neighbor count of `id`, clamped to `u16::MAX`; 0 for an
+    /// unknown id. Reads the adjacency index, not the stored `degree`.
+    pub fn compute_degree(&self, id: u32) -> u16 {
+        self.node_index(id).map_or(0, |i| self.degree(i))
+    }
+
+    /// Undirected neighbor count of the node at `index` (a self-loop counts
+    /// once, parallel edges each time), clamped to `u16::MAX`. Unlike
+    /// `Node::degree`, which is whatever the file or builder stored, this
+    /// always matches the current edges. Cached for every node on first
+    /// use. Panics if `index` is out of range.
+    pub fn degree(&self, index: usize) -> u16 {
+        self.degrees.get_or_init(|| {
+            self.adjacency
+                .iter()
+                .map(|neighbors| clamp_degree(neighbors.len()))
+                .collect()
+        })[index]
+    }
+
+    /// Sum of the weights of edges touching `id`, whatever their
+    /// direction (a self-loop counts once); 0 for an unknown id. Equals
+    /// the degree on an unweighted graph.
+    pub fn node_strength(&self, id: u32) -> f32 {
+        self.incident_edges(id).map(|e| e.weight).sum()
+    }
+
+    /// Distinct linked node pairs over possible pairs, in `[0, 1]`, over
+    /// the undirected interpretation of the graph. Self-loops and parallel
+    /// edges don't count; 0 below two nodes. O(V + E log E).
+    pub fn density(&self) -> f32 {
+        let n = self.node_count();
+        if n < 2 {
+            return 0.0;
+        }
+        // Each distinct pair is seen once from each end
+        let mut ends = 0usize;
+        let mut neighbors: Vec<usize> = Vec::new();
+        for i in 0..n {
+            neighbors.clear();
+            neighbors.extend(self.neighbor_indices(i).iter().filter(|&&j| j != i));
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            ends += neighbors.len();
+        }
+        ends as f32 / (n * (n - 1)) as f32
+    }
+
+    /// Overwrites every node's `degree` with its undirected neighbor count.
+    /// Decoded degrees come from the sender and mutations don't touch
+    /// them, so call this after editing edges if degrees matter.
+    pub fn recompute_degrees(&mut self) {
+        for i in 0..self.nodes.len() {
+            self.nodes[i].degree = self.degree(i);
+        }
+    }
+
+    /// Appends a node. Rejects an id that is already present.
+    pub fn add_node(&mut self, node: Node) -> Result<(), String> {
+        if self.id_to_index.contains_key(&node.id) {
+            return Err(format!("duplicate node id {}", node.id));
+        }
+        self.id_to_index.insert(node.id, self.nodes.len());
+        self.nodes.push(node);
+        self.label_index.take();
+        self.adjacency.push(Vec::new());
+        self.incident.push(Vec::new());
+        if self.is_directed() {
+            self.out_adjacency.push(Vec::new());
+            self.in_adjacency.push(Vec::new());
+        }
+        if let Some(degrees) = self.degrees.get_mut() {
+            degrees.push(0);
+        }
+        Ok(())
+    }
+
+    /// Removes a node and every edge touching it, returning the node.
+    /// Later nodes shift down one index, so this is O(V + E).
+    pub fn remove_node(&mut self, id: u32) -> Option<Node> {
+        let index = self.node_index(id)?;
+        let node = self.nodes.remove(index);
+        self.edges.retain(|e| e.source != id && e.target != id);
+        self.reindex();
+        Some(node)
+    }
+
+    /// Appends an edge. Both endpoints must already be nodes.
+    pub fn add_edge(&mut self, edge: Edge) -> Result<(), String> {
+        let (Some(i), Some(j)) = (self.node_index(edge.source), self.node_index(edge.target))
+        else {
+            return Err(format!(
+                "edge {} -> {} references an unknown node",
+                edge.source, edge.target
+            ));
+        };
+        self.adjacency[i].push(j);
+        self.incident[i].push(self.edges.len());
+        if i != j {
+            self.adjacency[j].push(i);
+            self.incident[j].push(self.edges.len());
+        }
+        if self.is_directed() {
+            self.out_adjacency[i].push(j);
+            self.in_adjacency[j].push(i);
+        }
+        if let Some(degrees) = self.degrees.get_mut() {
+            degrees[i] = clamp_degree(self.adjacency[i].len());
+            degrees[j] = clamp_degree(self.adjacency[j].len());
+        }
+        let key = self.edge_key(edge.source, edge.target);
+        if let Some(index) = self.edge_index.get_mut() {
+            index.entry(key).or_insert(self.edges.len());
+        }
+        self.edges.push(edge);
+        Ok(())
+    }
+
+    /// Removes every edge from `source` to `target` (in either orientation
+    /// when undirected). Returns whether anything was removed. O(V + E).
+    pub fn remove_edge(&mut self, source: u32, target: u32) -> bool {
+        let directed = self.is_directed();
+        let before = self.edges.len();
+        self.edges.retain(|e| {
+            let forward = e.source == source && e.target == target;
+            let backward = !directed && e.source == target && e.target == source;
+            !(forward || backward)
+        });
+        if self.edges.len() == before {
+            return false;
+        }
+        self.reindex();
+        true
+    }
+
+    /// Collapses repeated edges to their first occurrence (keeping its
+    /// weight) and, if asked, drops self-loops. `a -> b` and `b -> a`
+    /// count as duplicates only when undirected. Stored degrees are left
+    /// alone; call `recompute_degrees` afterwards if they matter.
+    pub fn dedup_edges(&mut self, drop_self_loops: bool) -> DedupReport {
+        let directed = self.is_directed();
+        let mut report = DedupReport::default();
+        let mut seen = HashSet::new();
+        self.edges.retain(|e| {
+            if drop_self_loops && e.source == e.target {
+                report.self_loops += 1;
+                return false;
+            }
+            let key = if directed || e.source <= e.target {
+                (e.source, e.target)
+            } else {
+                (e.target, e.source)
+            };
+            if !seen.insert(key) {
+                report.duplicates += 1;
+                return false;
+            }
+            true
+        });
+        if report.removed() > 0 {
+            self.reindex();
+        }
+        report
+    }
+
+    fn ids_of(&self, node_id: u32, indices: fn(&Self, usize) -> &[usize]) -> Vec<u32> {
+        self.node_index(node_id)
+            .map(|i| indices(self, i).iter().map(|&j| self.nodes[j].id).collect())
+            .unwrap_or_default()
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn clamp_degree(count: usize) -> u16 {
+    count.min(u16::MAX as usize) as u16
+}
 
-    fn make_node(id: u32) -> Node {
-        Node {
-            id,
-            label: String::new(),
-            pagerank: 0.0,
-            degree: 0,
-            x: 0.0,
-            y: 0.0,
+/// Undirected neighbor indices and incident edge indices per node.
+fn build_adjacency(
+    node_count: usize,
+    edges: &[Edge],
+    id_to_index: &HashMap<u32, usize>,
+) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+    let mut adjacency = vec![Vec::new(); node_count];
+    let mut incident = vec![Vec::new(); node_count];
+    for (k, e) in edges.iter().enumerate() {
+        if let (Some(&i), Some(&j)) = (id_to_index.get(&e.source), id_to_index.get(&e.target)) {
+            adjacency[i].push(j);
+            incident[i].push(k);
+            // A self-loop lists the node once, as the edge scan did
+            if i != j {
+                adjacency[j].push(i);
+                incident[j].push(k);
+            }
         }
     }
+    (adjacency, incident)
+}
+
+fn build_directed_adjacency(
+    node_count: usize,
+    edges: &[Edge],
+    id_to_index: &HashMap<u32, usize>,
+) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+    let mut outgoing = vec![Vec::new(); node_count];
+    let mut incoming = vec![Vec::new(); node_count];
+    for e in edges {
+        if let (Some(&i), Some(&j)) = (id_to_index.get(&e.source), id_to_index.get(&e.target)) {
+            outgoing[i].push(j);
+            incoming[j].push(i);
+        }
+    }
+    (outgoing, incoming)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{edge, node};
 
     fn sample_graph() -> Graph {
-        let nodes = vec![make_node(10), make_node(20), make_node(30)];
+        let nodes = vec![node(10), node(20), node(30)];
         let edges = vec![
             Edge {
                 source: 10,
                 target: 20,
+                weight: 1.0,
             },
             Edge {
                 source: 20,
                 target: 30,
+                weight: 1.0,
             },
         ];
         Graph::new(nodes, edges)
@@ -163,4 +713,553 @@ mod tests {
         assert_eq!(g.nodes()[0].x, 5.0);
         assert_eq!(g.nodes()[0].y, 10.0);
     }
+
+    #[test]
+    fn neighbor_indices() {
+        let g = sample_graph();
+        assert_eq!(g.neighbor_indices(1), &[0, 2]);
+        assert_eq!(g.neighbor_indices(0), &[1]);
+        assert!(g.neighbor_indices(99).is_empty());
+    }
+
+    #[test]
+    fn adjacency_matches_edge_scan() {
+        let n = 200u32;
+        let nodes = (0..n).map(node).collect();
+        let mut state = 12345u32;
+        let mut next = || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            state >> 8
+        };
+        let edges: Vec<Edge> = (0..1000)
+            .map(|_| Edge {
+                source: next() % n,
+                target: next() % n,
+                weight: 1.0,
+            })
+            .collect();
+        let g = Graph::new(nodes, edges.clone());
+
+        for id in 0..n {
+            let scanned: Vec<u32> = edges
+                .iter()
+                .filter_map(|e| {
+                    if e.source == id {
+                        Some(e.target)
+                    } else if e.target == id {
+                        Some(e.source)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            assert_eq!(g.neighbors(id), scanned, "node {id}");
+        }
+    }
+
+    fn three_cycle(kind: GraphKind) -> Graph {
+        let nodes = vec![node(1), node(2), node(3)];
+        let edges = [(1, 2), (2, 3), (3, 1)]
+            .into_iter()
+            .map(|(source, target)| Edge {
+                source,
+                target,
+                weight: 1.0,
+            })
+            .collect();
+        Graph::with_kind(nodes, edges, kind)
+    }
+
+    #[test]
+    fn directed_three_cycle() {
+        let g = three_cycle(GraphKind::Directed);
+        assert!(g.is_directed());
+        assert_eq!(g.out_neighbors(1), vec![2]);
+        assert_eq!(g.in_neighbors(1), vec![3]);
+        // The undirected union is still available
+        assert_eq!(g.neighbors(1), vec![2, 3]);
+        assert!(g.out_neighbors(99).is_empty());
+    }
+
+    #[test]
+    fn undirected_three_cycle() {
+        let g = three_cycle(GraphKind::default());
+        assert!(!g.is_directed());
+        assert_eq!(g.out_neighbors(1), vec![2, 3]);
+        assert_eq!(g.in_neighbors(1), vec![2, 3]);
+        assert_eq!(g.neighbors(1), vec![2, 3]);
+    }
+
+    #[test]
+    fn add_node_and_edge() {
+        let mut g = sample_graph();
+        g.add_node(node(40)).unwrap();
+        assert!(g.add_node(node(40)).is_err());
+        assert_eq!(g.node_index(40), Some(3));
+
+        g.add_edge(Edge {
+            source: 40,
+            target: 10,
+            weight: 1.0,
+        })
+        .unwrap();
+        assert_eq!(g.neighbors(40), vec![10]);
+        assert_eq!(g.neighbors(10), vec![20, 40]);
+
+        let dangling = Edge {
+            source: 40,
+            target: 99,
+            weight: 1.0,
+        };
+        assert!(g.add_edge(dangling).is_err());
+        assert_eq!(g.edge_count(), 3);
+    }
+
+    #[test]
+    fn remove_node_drops_incident_edges() {
+        let mut g = three_cycle(GraphKind::Directed);
+        let removed = g.remove_node(2).unwrap();
+        assert_eq!(removed.id, 2);
+        assert!(g.remove_node(2).is_none());
+
+        assert_eq!(g.node_count(), 2);
+        assert_eq!(g.node_index(3), Some(1));
+        // Only 3 -> 1 survives
+        assert_eq!(g.edge_count(), 1);
+        assert_eq!(g.out_neighbors(1), Vec::<u32>::new());
+        assert_eq!(g.in_neighbors(1), vec![3]);
+        assert_eq!(g.out_neighbors(3), vec![1]);
+        assert!(g.in_neighbors(3).is_empty());
+    }
+
+    #[test]
+    fn remove_edge_respects_direction() {
+        let mut directed = three_cycle(GraphKind::Directed);
+        assert!(!directed.remove_edge(2, 1));
+        assert!(directed.remove_edge(1, 2));
+        assert!(directed.out_neighbors(1).is_empty());
+        assert!(directed.in_neighbors(2).is_empty());
+
+        let mut undirected = three_cycle(GraphKind::Undirected);
+        assert!(undirected.remove_edge(2, 1));
+        assert_eq!(undirected.neighbors(1), vec![3]);
+        assert_eq!(undirected.neighbors(2), vec![3]);
+    }
+
+    #[test]
+    fn edits_match_fresh_build() {
+        let mut g = Graph::with_kind(vec![], vec![], GraphKind::Directed);
+        for id in 0..4 {
+            g.add_node(node(id)).unwrap();
+        }
+        for (source, target) in [(0, 1), (1, 2), (2, 0), (3, 3)] {
+            g.add_edge(Edge {
+                source,
+                target,
+                weight: 1.0,
+            })
+            .unwrap();
+        }
+        let fresh = Graph::with_kind(g.nodes().to_vec(), g.edges().to_vec(), GraphKind::Directed);
+        for id in 0..4 {
+            assert_eq!(g.neighbors(id), fresh.neighbors(id));
+            assert_eq!(g.out_neighbors(id), fresh.out_neighbors(id));
+            assert_eq!(g.in_neighbors(id), fresh.in_neighbors(id));
+        }
+    }
+
+    #[test]
+    fn recompute_degrees_fixes_stale_values() {
+        let mut g = sample_graph();
+        for (node, wrong) in g.nodes_mut().iter_mut().zip([7, 0, 3]) {
+            node.degree = wrong;
+        }
+        assert_eq!(g.compute_degree(20), 2);
+        assert_eq!(g.compute_degree(99), 0);
+
+        g.recompute_degrees();
+        let degrees: Vec<u16> = g.nodes().iter().map(|n| n.degree).collect();
+        assert_eq!(degrees, vec![1, 2, 1]);
+
+        // Stale again after a mutation until recomputed
+        g.add_edge(Edge {
+            source: 30,
+            target: 10,
+            weight: 1.0,
+        })
+        .unwrap();
+        assert_eq!(g.nodes()[0].degree, 1);
+        g.recompute_degrees();
+        assert_eq!(g.nodes()[0].degree, 2);
+    }
+
+    #[test]
+    fn degree_clamps_to_u16() {
+        let n = u16::MAX as u32 + 2;
+        let nodes = (0..n).map(node).collect();
+        let edges = (1..n)
+            .map(|target| Edge {
+                source: 0,
+                target,
+                weight: 1.0,
+            })
+            .collect();
+        let mut g = Graph::new(nodes, edges);
+        g.recompute_degrees();
+        assert_eq!(g.nodes()[0].degree, u16::MAX);
+        assert_eq!(g.compute_degree(0), u16::MAX);
+        assert_eq!(g.nodes()[1].degree, 1);
+    }
+
+    #[test]
+    fn dedup_edges_counts_and_rebuilds() {
+        let edges = vec![
+            edge(10, 20),
+            edge(20, 10),
+            edge(10, 20),
+            edge(20, 30),
+            edge(30, 30),
+            edge(30, 30),
+        ];
+        let mut g = Graph::new(vec![node(10), node(20), node(30)], edges.clone());
+        let report = g.dedup_edges(true);
+        assert_eq!(
+            report,
+            DedupReport {
+                duplicates: 2,
+                self_loops: 2,
+            }
+        );
+        assert_eq!(report.removed(), 4);
+        assert_eq!(g.edges(), &[edge(10, 20), edge(20, 30)]);
+        assert_eq!(g.neighbors(20), vec![10, 30]);
+        g.recompute_degrees();
+        assert_eq!(g.nodes()[1].degree, 2);
+
+        // Keeping self-loops still collapses the repeated one; direction
+        // makes 20 -> 10 distinct from 10 -> 20
+        let mut d = Graph::with_kind(
+            vec![node(10), node(20), node(30)],
+            edges,
+            GraphKind::Directed,
+        );
+        let report = d.dedup_edges(false);
+        assert_eq!(
+            report,
+            DedupReport {
+                duplicates: 2,
+                self_loops: 0,
+            }
+        );
+        assert_eq!(d.edge_count(), 4);
+        assert_eq!(d.out_neighbors(30), vec![30]);
+
+        // Nothing left to remove
+        assert_eq!(d.dedup_edges(false).removed(), 0);
+    }
+
+    #[test]
+    fn incident_edges_cover_both_ends() {
+        let mut g = sample_graph();
+        let touching: Vec<&Edge> = g.incident_edges(20).collect();
+        assert_eq!(touching, vec![&edge(10, 20), &edge(20, 30)]);
+        assert_eq!(g.incident_edges(10).count(), 1);
+        assert_eq!(g.incident_edges(99).count(), 0);
+
+        // Kept in step with edits; a self-loop appears once
+        g.add_edge(edge(30, 30)).unwrap();
+        assert_eq!(
+            g.incident_edges(30).collect::<Vec<_>>(),
+            vec![&edge(20, 30), &edge(30, 30)]
+        );
+        g.remove_node(10);
+        assert_eq!(
+            g.incident_edges(20).collect::<Vec<_>>(),
+            vec![&edge(20, 30)]
+        );
+
+        // Directed graphs report in- and out-edges alike
+        let d = three_cycle(GraphKind::Directed);
+        let ends: Vec<(u32, u32)> = d.incident_edges(1).map(|e| (e.source, e.target)).collect();
+        assert_eq!(ends, vec![(1, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn nodes_where_filters_in_order() {
+        let g = sample_graph();
+        let ids: Vec<u32> = g.nodes_where(|n| n.id >= 20).map(|n| n.id).collect();
+        assert_eq!(ids, vec![20, 30]);
+        assert_eq!(g.nodes_where(|_| false).count(), 0);
+    }
+
+    #[test]
+    fn neighbors_within_hops() {
+        // Path 1 - 2 - 3 - 4 - 5 plus a separate 6
+        let nodes = (1..=6).map(node).collect();
+        let edges = (1..5).map(|i| edge(i, i + 1)).collect();
+        let g = Graph::new(nodes, edges);
+
+        assert_eq!(g.neighbors_within(3, 0, true), vec![3]);
+        assert!(g.neighbors_within(3, 0, false).is_empty());
+
+        let mut direct = g.neighbors_within(3, 1, false);
+        direct.sort();
+        assert_eq!(direct, vec![2, 4]);
+
+        let whole = g.neighbors_within(1, 100, true);
+        assert_eq!(whole, vec![1, 2, 3, 4, 5]);
+        assert_eq!(g.neighbors_within(6, 3, true), vec![6]);
+        assert!(g.neighbors_within(99, 3, true).is_empty());
+    }
+
+    #[test]
+    fn typed_attributes() {
+        let mut node = node(1);
+        assert!(node.attributes.is_empty());
+        assert_eq!(node.get_attr("kind"), None);
+
+        assert_eq!(node.set_attr("kind", "person"), None);
+        node.set_attr("born", 1984i64);
+        node.set_attr("score", 0.75);
+        node.set_attr("active", true);
+
+        assert_eq!(
+            node.get_attr("kind").and_then(|v| v.as_str()),
+            Some("person")
+        );
+        assert_eq!(node.get_attr("born").and_then(|v| v.as_i64()), Some(1984));
+        assert_eq!(node.get_attr("born").and_then(|v| v.as_f64()), Some(1984.0));
+        assert_eq!(node.get_attr("score").and_then(|v| v.as_f64()), Some(0.75));
+        assert_eq!(
+            node.get_attr("active").and_then(|v| v.as_bool()),
+            Some(true)
+        );
+        // Wrong type reads as absent
+        assert_eq!(node.get_attr("kind").and_then(|v| v.as_f64()), None);
+
+        let old = node.set_attr("kind", "robot");
+        assert_eq!(old, Some(AttributeValue::Str("person".to_string())));
+    }
+
+    #[test]
+    fn filter_nodes_by_attribute() {
+        let mut nodes: Vec<Node> = (1..=4).map(node).collect();
+        for (node, group) in nodes.iter_mut().zip(["a", "b", "a", "c"]) {
+            node.set_attr("group", group);
+        }
+        nodes[3].set_attr("active", false);
+        let g = Graph::new(nodes, vec![]);
+
+        let in_a: Vec<u32> = g
+            .nodes_where(|n| n.get_attr("group").and_then(|v| v.as_str()) == Some("a"))
+            .map(|n| n.id)
+            .collect();
+        assert_eq!(in_a, vec![1, 3]);
+        let inactive = g.nodes_where(|n| n.get_attr("active") == Some(&false.into()));
+        assert_eq!(inactive.map(|n| n.id).collect::<Vec<_>>(), vec![4]);
+    }
+
+    fn labelled(labels: &[&str]) -> Graph {
+        let nodes = labels
+            .iter()
+            .zip(1..)
+            .map(|(&label, id)| Node {
+                label: label.to_string(),
+                ..node(id)
+            })
+            .collect();
+        Graph::new(nodes, vec![])
+    }
+
+    #[test]
+    fn nodes_by_exact_label() {
+        let g = labelled(&["Alice", "Bob", "alice", "Alice", ""]);
+        let ids = |label| {
+            g.nodes_by_label(label)
+                .iter()
+                .map(|n| n.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids("Alice"), vec![1, 4]);
+        assert_eq!(ids("alice"), vec![3]);
+        assert!(ids("Carol").is_empty());
+        // Unlabelled nodes aren't findable by the empty label
+        assert!(ids("").is_empty());
+    }
+
+    #[test]
+    fn search_ranks_prefix_before_substring() {
+        let g = labelled(&["Mallory", "Al", "Alice", "", "Sal", "Alan Turing", "Carol"]);
+        // Prefix matches, shortest first, then substrings by match position
+        assert_eq!(g.search_labels("al", 10), vec![2, 3, 6, 5, 1]);
+        assert_eq!(g.search_labels("AL", 2), vec![2, 3]);
+        assert_eq!(g.search_labels("uring", 10), vec![6]);
+        assert_eq!(g.search_labels("ROL", 10), vec![7]);
+        assert!(g.search_labels("zed", 10).is_empty());
+        // The empty query would match every label, so it matches none
+        assert!(g.search_labels("", 10).is_empty());
+        assert!(g.search_labels("al", 0).is_empty());
+    }
+
+    #[test]
+    fn label_index_follows_mutations() {
+        let mut g = labelled(&["Alice"]);
+        assert_eq!(g.search_labels("ali", 5), vec![1]);
+
+        g.nodes_mut()[0].label = "Bob".to_string();
+        assert!(g.search_labels("ali", 5).is_empty());
+        assert_eq!(g.nodes_by_label("Bob").len(), 1);
+
+        g.add_node(Node {
+            label: "Alicia".to_string(),
+            ..node(2)
+        })
+        .unwrap();
+        assert_eq!(g.search_labels("ali", 5), vec![2]);
+        g.remove_node(2);
+        assert!(g.nodes_by_label("Alicia").is_empty());
+    }
+
+    #[test]
+    fn edge_lookup_undirected() {
+        let mut g = sample_graph();
+        assert!(g.has_edge(10, 20));
+        assert!(g.has_edge(20, 10));
+        assert!(g.has_edge(30, 20));
+        assert!(!g.has_edge(10, 30));
+        assert!(!g.has_edge(10, 99));
+        assert_eq!(g.edge_weight(20, 10), Some(1.0));
+        assert_eq!(g.edge_weight(10, 30), None);
+
+        // The built index follows additions and removals
+        g.add_edge(Edge {
+            source: 30,
+            target: 10,
+            weight: 2.5,
+        })
+        .unwrap();
+        assert_eq!(g.edge_weight(10, 30), Some(2.5));
+        assert!(g.remove_edge(20, 10));
+        assert!(!g.has_edge(10, 20));
+        assert!(g.has_edge(20, 30));
+    }
+
+    #[test]
+    fn edge_lookup_directed_and_parallel() {
+        let edges = vec![
+            Edge {
+                source: 10,
+                target: 20,
+                weight: 1.5,
+            },
+            Edge {
+                source: 10,
+                target: 20,
+                weight: 4.0,
+            },
+            // Dangling: 99 isn't a node
+            Edge {
+                source: 20,
+                target: 99,
+                weight: 1.0,
+            },
+        ];
+        let nodes = vec![node(10), node(20), node(30)];
+        let g = Graph::with_kind(nodes, edges, GraphKind::Directed);
+        assert!(g.has_edge(10, 20));
+        assert!(!g.has_edge(20, 10));
+        // The first parallel edge wins
+        assert_eq!(g.edge_weight(10, 20), Some(1.5));
+        assert!(!g.has_edge(20, 99));
+    }
+
+    #[test]
+    fn density_counts_distinct_pairs() {
+        // 10-20 twice and both ways, 20-30, a self-loop: 2 of 3 pairs
+        let edges = vec![
+            Edge {
+                source: 10,
+                target: 20,
+                weight: 1.0,
+            },
+            Edge {
+                source: 20,
+                target: 10,
+                weight: 1.0,
+            },
+            Edge {
+                source: 20,
+                target: 30,
+                weight: 1.0,
+            },
+            Edge {
+                source: 30,
+                target: 30,
+                weight: 1.0,
+            },
+        ];
+        let nodes = vec![node(10), node(20), node(30)];
+        let g = Graph::with_kind(nodes, edges, GraphKind::Directed);
+        assert!((g.density() - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(Graph::new(vec![node(1)], vec![]).density(), 0.0);
+        assert_eq!(Graph::new(vec![], vec![]).density(), 0.0);
+    }
+
+    #[test]
+    fn from_edges_creates_nodes_with_degrees() {
+        // A star on 5 with a tail 3-7 and a doubled 5-1
+        let pairs = [(5, 1), (5, 2), (3, 5), (3, 7), (1, 5)];
+        let g = Graph::from_edges(&pairs);
+        assert!(!g.is_directed());
+        let ids: Vec<u32> = g.nodes().iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![5, 1, 2, 3, 7]);
+        assert!(g.nodes().iter().all(|n| n.label.is_empty()));
+        for node in g.nodes() {
+            assert_eq!(node.degree, g.compute_degree(node.id));
+        }
+        let degrees: Vec<u16> = g.nodes().iter().map(|n| n.degree).collect();
+        assert_eq!(degrees, vec![4, 2, 1, 2, 1]);
+        assert!(g.edges().iter().all(|e| e.weight == 1.0));
+        assert!(g.validate().is_empty());
+
+        assert_eq!(g.edge_list(), pairs);
+        assert_eq!(Graph::from_edges(&g.edge_list()).edges(), g.edges());
+        assert_eq!(Graph::from_edges(&[]).node_count(), 0);
+    }
+
+    #[test]
+    fn degree_cache_tracks_edges() {
+        // Built without stored degrees, as programmatic graphs usually are
+        let mut g = sample_graph();
+        g.add_edge(Edge {
+            source: 20,
+            target: 20,
+            weight: 1.0,
+        })
+        .unwrap();
+        assert!(g.nodes().iter().all(|n| n.degree == 0));
+        for i in 0..g.node_count() {
+            assert_eq!(g.degree(i) as usize, g.neighbor_indices(i).len());
+        }
+        // 20 has 10, 30 and its self-loop
+        assert_eq!([g.degree(0), g.degree(1), g.degree(2)], [1, 3, 1]);
+
+        // Mutations after the cache is built keep it current
+        g.add_node(node(40)).unwrap();
+        assert_eq!(g.degree(3), 0);
+        g.add_edge(Edge {
+            source: 40,
+            target: 10,
+            weight: 1.0,
+        })
+        .unwrap();
+        assert_eq!(g.degree(0), 2);
+        assert_eq!(g.degree(3), 1);
+        assert!(g.remove_edge(10, 20));
+        assert_eq!(g.degree(0), 1);
+        assert_eq!(g.degree(1), 2);
+        g.remove_node(30);
+        assert_eq!(g.compute_degree(20), 1);
+        assert_eq!(g.compute_degree(99), 0);
+    }
 }
@@ -0,0 +1,211 @@
+use crate::graph::types::Graph;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A structural problem found by `Graph::validate`. Indices are positions
+/// in `nodes()` / `edges()` at the time of the check.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphIssue {
+    /// A node reuses the id of an earlier node at `first_index`.
+    DuplicateNodeId {
+        id: u32,
+        index: usize,
+        first_index: usize,
+    },
+    /// An edge endpoint that isn't in the node table.
+    DanglingEdge {
+        edge_index: usize,
+        id: u32,
+    },
+    SelfLoop {
+        edge_index: usize,
+        id: u32,
+    },
+    NanPosition {
+        index: usize,
+        id: u32,
+    },
+}
+
+impl fmt::Display for GraphIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphIssue::DuplicateNodeId {
+                id,
+                index,
+                first_index,
+            } => write!(
+                f,
+                "Node {} reuses id {} from node {}",
+                index, id, first_index
+            ),
+            GraphIssue::DanglingEdge { edge_index, id } => {
+                write!(f, "Edge {} references unknown node {}", edge_index, id)
+            }
+            GraphIssue::SelfLoop { edge_index, id } => {
+                write!(f, "Edge {} is a self-loop on node {}", edge_index, id)
+            }
+            GraphIssue::NanPosition { index, id } => {
+                write!(f, "Node {} (id {}) has a NaN position", index, id)
+            }
+        }
+    }
+}
+
+impl Graph {
+    /// Every issue in the graph, nodes first, each group in index order.
+    /// An empty result means the graph is clean.
+    pub fn validate(&self) -> Vec<GraphIssue> {
+        let mut issues = Vec::new();
+        let mut first_seen = HashMap::new();
+        for (index, node) in self.nodes().iter().enumerate() {
+            if let Some(&first_index) = first_seen.get(&node.id) {
+                issues.push(GraphIssue::DuplicateNodeId {
+                    id: node.id,
+                    index,
+                    first_index,
+                });
+            } else {
+                first_seen.insert(node.id, index);
+            }
+            if node.x.is_nan() || node.y.is_nan() {
+                issues.push(GraphIssue::NanPosition { index, id: node.id });
+            }
+        }
+
+        for (edge_index, edge) in self.edges().iter().enumerate() {
+            for id in [edge.source, edge.target] {
+                if self.node_index(id).is_none() {
+                    issues.push(GraphIssue::DanglingEdge { edge_index, id });
+                    // Report a doubly dangling self-loop once
+                    if edge.source == edge.target {
+                        break;
+                    }
+                }
+            }
+            if edge.source == edge.target {
+                issues.push(GraphIssue::SelfLoop {
+                    edge_index,
+                    id: edge.source,
+                });
+            }
+        }
+        issues
+    }
+
+    /// Drops later nodes that reuse an id (keeping the first) and edges
+    /// with an unknown endpoint, then rebuilds the indexes. Self-loops and
+    /// NaN positions are left alone; `dedup_edges` can drop the former.
+    pub fn repair(&mut self) {
+        let mut seen = HashSet::new();
+        let nodes: Vec<_> = self
+            .nodes()
+            .iter()
+            .filter(|n| seen.insert(n.id))
+            .cloned()
+            .collect();
+        let edges: Vec<_> = self
+            .edges()
+            .iter()
+            .filter(|e| seen.contains(&e.source) && seen.contains(&e.target))
+            .cloned()
+            .collect();
+        *self = Graph::with_kind(nodes, edges, self.kind());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::{GraphKind, Node};
+    use crate::test_utils::edge;
+
+    fn make_node(id: u32, label: &str, x: f32) -> Node {
+        Node {
+            id,
+            label: label.to_string(),
+            x,
+            ..Node::default()
+        }
+    }
+
+    #[test]
+    fn clean_graph_has_no_issues() {
+        let g = Graph::new(
+            vec![make_node(1, "", 0.0), make_node(2, "", 1.0)],
+            vec![edge(1, 2)],
+        );
+        assert!(g.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_reports_each_issue() {
+        let g = Graph::new(
+            vec![
+                make_node(1, "first", 0.0),
+                make_node(2, "", f32::NAN),
+                make_node(1, "second", 0.0),
+            ],
+            vec![edge(1, 2), edge(2, 9), edge(2, 2), edge(7, 7)],
+        );
+        assert_eq!(
+            g.validate(),
+            vec![
+                GraphIssue::NanPosition { index: 1, id: 2 },
+                GraphIssue::DuplicateNodeId {
+                    id: 1,
+                    index: 2,
+                    first_index: 0,
+                },
+                GraphIssue::DanglingEdge {
+                    edge_index: 1,
+                    id: 9,
+                },
+                GraphIssue::SelfLoop {
+                    edge_index: 2,
+                    id: 2,
+                },
+                GraphIssue::DanglingEdge {
+                    edge_index: 3,
+                    id: 7,
+                },
+                GraphIssue::SelfLoop {
+                    edge_index: 3,
+                    id: 7,
+                },
+            ]
+        );
+        assert_eq!(
+            g.validate()[1].to_string(),
+            "Node 2 reuses id 1 from node 0"
+        );
+    }
+
+    #[test]
+    fn repair_drops_duplicates_and_dangling_edges() {
+        let mut g = Graph::with_kind(
+            vec![
+                make_node(1, "first", 0.0),
+                make_node(2, "", 0.0),
+                make_node(1, "second", 0.0),
+            ],
+            vec![edge(1, 2), edge(2, 9), edge(2, 2)],
+            GraphKind::Directed,
+        );
+        g.repair();
+
+        assert_eq!(g.node_count(), 2);
+        assert_eq!(g.node_by_id(1).unwrap().label, "first");
+        assert_eq!(g.edges(), &[edge(1, 2), edge(2, 2)]);
+        assert!(g.is_directed());
+        assert_eq!(g.out_neighbors(1), vec![2]);
+        // Only the self-loop, which repair leaves alone, remains
+        assert_eq!(
+            g.validate(),
+            vec![GraphIssue::SelfLoop {
+                edge_index: 1,
+                id: 2,
+            }]
+        );
+    }
+}
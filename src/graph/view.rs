@@ -0,0 +1,156 @@
+use crate::graph::types::{Edge, Graph, Node};
+
+/// A filtered window onto a `Graph`: hiding a node hides it and every edge
+/// touching it, without copying or touching the graph itself. All nodes
+/// start visible.
+pub struct GraphView<'a> {
+    graph: &'a Graph,
+    /// Per node index.
+    visible: Vec<bool>,
+}
+
+impl<'a> GraphView<'a> {
+    pub fn new(graph: &'a Graph) -> Self {
+        Self {
+            graph,
+            visible: vec![true; graph.node_count()],
+        }
+    }
+
+    pub fn graph(&self) -> &'a Graph {
+        self.graph
+    }
+
+    /// Shows or hides node `id`. Returns false for an unknown id.
+    pub fn set_visible(&mut self, id: u32, visible: bool) -> bool {
+        match self.graph.node_index(id) {
+            Some(i) => {
+                self.visible[i] = visible;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes exactly the nodes matching `pred` visible, e.g. everything
+    /// above a PageRank threshold.
+    pub fn set_visible_where(&mut self, pred: impl Fn(&Node) -> bool) {
+        for (flag, node) in self.visible.iter_mut().zip(self.graph.nodes()) {
+            *flag = pred(node);
+        }
+    }
+
+    pub fn show_all(&mut self) {
+        self.visible.fill(true);
+    }
+
+    /// False for hidden and unknown ids.
+    pub fn is_visible(&self, id: u32) -> bool {
+        self.graph.node_index(id).is_some_and(|i| self.visible[i])
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.visible.iter().filter(|&&v| v).count()
+    }
+
+    /// Visible nodes, in graph order.
+    pub fn visible_nodes(&self) -> impl Iterator<Item = &'a Node> + '_ {
+        self.graph
+            .nodes()
+            .iter()
+            .zip(&self.visible)
+            .filter(|&(_, &v)| v)
+            .map(|(node, _)| node)
+    }
+
+    /// Edges whose endpoints are both visible, in graph order. Dangling
+    /// edges never show.
+    pub fn visible_edges(&self) -> impl Iterator<Item = &'a Edge> + '_ {
+        self.graph
+            .edges()
+            .iter()
+            .filter(|e| self.is_visible(e.source) && self.is_visible(e.target))
+    }
+
+    /// Visible neighbours of `id` in either direction, in edge order.
+    /// Empty when `id` itself is hidden or unknown.
+    pub fn visible_neighbors(&self, id: u32) -> Vec<u32> {
+        let Some(i) = self.graph.node_index(id).filter(|&i| self.visible[i]) else {
+            return Vec::new();
+        };
+        let nodes = self.graph.nodes();
+        self.graph
+            .neighbor_indices(i)
+            .iter()
+            .filter(|&&j| self.visible[j])
+            .map(|&j| nodes[j].id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::edge;
+
+    fn make_node(id: u32, pagerank: f32) -> Node {
+        Node {
+            id,
+            pagerank,
+            ..Node::default()
+        }
+    }
+
+    /// Star around 1 plus the edge 2-3.
+    fn sample_graph() -> Graph {
+        Graph::new(
+            vec![
+                make_node(1, 0.4),
+                make_node(2, 0.3),
+                make_node(3, 0.2),
+                make_node(4, 0.1),
+            ],
+            vec![edge(1, 2), edge(1, 3), edge(1, 4), edge(2, 3)],
+        )
+    }
+
+    fn ids<'a>(nodes: impl Iterator<Item = &'a Node>) -> Vec<u32> {
+        nodes.map(|n| n.id).collect()
+    }
+
+    #[test]
+    fn hiding_a_node_hides_its_edges() {
+        let graph = sample_graph();
+        let mut view = GraphView::new(&graph);
+        assert_eq!(view.visible_count(), 4);
+        assert_eq!(view.visible_edges().count(), 4);
+
+        assert!(view.set_visible(1, false));
+        assert!(!view.set_visible(99, false));
+        assert_eq!(ids(view.visible_nodes()), vec![2, 3, 4]);
+        assert_eq!(view.visible_edges().collect::<Vec<_>>(), vec![&edge(2, 3)]);
+        assert_eq!(view.visible_neighbors(2), vec![3]);
+        assert!(view.visible_neighbors(4).is_empty());
+        assert!(view.visible_neighbors(1).is_empty());
+
+        // The graph underneath is untouched
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(graph.neighbors(2), vec![1, 3]);
+
+        view.show_all();
+        assert_eq!(view.visible_edges().count(), 4);
+    }
+
+    #[test]
+    fn visibility_by_predicate() {
+        let graph = sample_graph();
+        let mut view = GraphView::new(&graph);
+        view.set_visible_where(|n| n.pagerank >= 0.25);
+        assert_eq!(ids(view.visible_nodes()), vec![1, 2]);
+        assert_eq!(view.visible_edges().collect::<Vec<_>>(), vec![&edge(1, 2)]);
+        assert!(view.is_visible(2));
+        assert!(!view.is_visible(3));
+        assert!(!view.is_visible(99));
+    }
+}
@@ -81,7 +81,15 @@ impl QuadNode {
         }
     }
 
-    fn compute_force(&self, pos: Vec2, theta: f32, repulsion: f32, bounds: &AABB) -> Vec2 {
+    /// Sums `law(dist) * mass` along the direction away from each body.
+    /// `law` receives the distance already clamped to `MIN_DIST`.
+    fn compute_force<F: Fn(f32) -> f32>(
+        &self,
+        pos: Vec2,
+        theta: f32,
+        bounds: &AABB,
+        law: &F,
+    ) -> Vec2 {
         match self {
             QuadNode::Empty => Vec2::ZERO,
             QuadNode::Leaf {
@@ -93,8 +101,7 @@ impl QuadNode {
                 if dist < EPSILON {
                     return Vec2::ZERO; // self-interaction
                 }
-                let dist = dist.max(MIN_DIST);
-                delta.normalize() * (repulsion * *mass / (dist * dist))
+                delta.normalize() * (*mass * law(dist.max(MIN_DIST)))
             }
             QuadNode::Internal {
                 center_of_mass,
@@ -107,14 +114,13 @@ impl QuadNode {
 
                 if s / dist < theta {
                     // Treat as single body
-                    let dist = dist.max(MIN_DIST);
-                    delta.normalize() * (repulsion * *total_mass / (dist * dist))
+                    delta.normalize() * (*total_mass * law(dist.max(MIN_DIST)))
                 } else {
                     // Recurse into children
                     let sub_bounds = bounds.subdivide();
                     let mut force = Vec2::ZERO;
                     for (i, child) in children.iter().enumerate() {
-                        force += child.compute_force(pos, theta, repulsion, &sub_bounds[i]);
+                        force += child.compute_force(pos, theta, &sub_bounds[i], law);
                     }
                     force
                 }
@@ -140,6 +146,13 @@ pub struct BarnesHutTree {
 
 impl BarnesHutTree {
     pub fn build(nodes: &[Node]) -> Self {
+        Self::build_with_masses(nodes, &vec![1.0; nodes.len()])
+    }
+
+    /// Builds the tree with a per-node mass (index-aligned with `nodes`)
+    /// instead of unit masses, e.g. `degree + 1` for ForceAtlas2.
+    pub fn build_with_masses(nodes: &[Node], masses: &[f32]) -> Self {
+        debug_assert_eq!(nodes.len(), masses.len());
         if nodes.is_empty() {
             return Self {
                 root: QuadNode::Empty,
@@ -152,33 +165,25 @@ impl BarnesHutTree {
             };
         }
 
-        let mut min_x = f32::MAX;
-        let mut min_y = f32::MAX;
-        let mut max_x = f32::MIN;
-        let mut max_y = f32::MIN;
-
+        let mut fit = AABB::empty();
         for n in nodes {
-            min_x = min_x.min(n.x);
-            min_y = min_y.min(n.y);
-            max_x = max_x.max(n.x);
-            max_y = max_y.max(n.y);
+            fit.expand_to_include(n.x, n.y);
         }
 
         // 5% padding + minimum size
-        let w = (max_x - min_x).max(1.0);
-        let h = (max_y - min_y).max(1.0);
-        let pad_x = w * 0.05;
-        let pad_y = h * 0.05;
+        let (w, h) = fit.dimensions();
+        let pad_x = w.max(1.0) * 0.05;
+        let pad_y = h.max(1.0) * 0.05;
         let bounds = AABB {
-            min_x: min_x - pad_x,
-            min_y: min_y - pad_y,
-            max_x: max_x + pad_x,
-            max_y: max_y + pad_y,
+            min_x: fit.min_x - pad_x,
+            min_y: fit.min_y - pad_y,
+            max_x: fit.max_x + pad_x,
+            max_y: fit.max_y + pad_y,
         };
 
         let mut root = QuadNode::Empty;
-        for n in nodes {
-            root.insert(Vec2::new(n.x, n.y), 1.0, &bounds, 0);
+        for (n, &mass) in nodes.iter().zip(masses) {
+            root.insert(Vec2::new(n.x, n.y), mass, &bounds, 0);
         }
 
         Self { root, bounds }
@@ -190,30 +195,33 @@ impl BarnesHutTree {
         nodes: &[Node],
         repulsion: f32,
         theta: f32,
+    ) -> Vec2 {
+        self.compute_force_with(node_idx, nodes, theta, |dist| repulsion / (dist * dist))
+    }
+
+    /// Like `compute_repulsion`, but with a caller-supplied force law:
+    /// `law(dist)` is the magnitude a unit mass exerts at distance `dist`.
+    /// Lets layouts with other falloffs (e.g. `k² / d`) share the tree.
+    pub fn compute_force_with<F: Fn(f32) -> f32>(
+        &self,
+        node_idx: usize,
+        nodes: &[Node],
+        theta: f32,
+        law: F,
     ) -> Vec2 {
         let pos = Vec2::new(nodes[node_idx].x, nodes[node_idx].y);
-        self.root.compute_force(pos, theta, repulsion, &self.bounds)
+        self.root.compute_force(pos, theta, &self.bounds, &law)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    fn make_node(id: u32, x: f32, y: f32) -> Node {
-        Node {
-            id,
-            label: String::new(),
-            pagerank: 0.0,
-            degree: 0,
-            x,
-            y,
-        }
-    }
+    use crate::test_utils::node_at;
 
     #[test]
     fn insert_single_node() {
-        let nodes = vec![make_node(1, 5.0, 5.0)];
+        let nodes = vec![node_at(1, 5.0, 5.0)];
         let tree = BarnesHutTree::build(&nodes);
         assert!(matches!(tree.root, QuadNode::Leaf { .. }));
     }
@@ -221,11 +229,11 @@ mod tests {
     #[test]
     fn insert_multiple_nodes() {
         let nodes = vec![
-            make_node(1, 1.0, 1.0),
-            make_node(2, 9.0, 9.0),
-            make_node(3, 1.0, 9.0),
-            make_node(4, 9.0, 1.0),
-            make_node(5, 5.0, 5.0),
+            node_at(1, 1.0, 1.0),
+            node_at(2, 9.0, 9.0),
+            node_at(3, 1.0, 9.0),
+            node_at(4, 9.0, 1.0),
+            node_at(5, 5.0, 5.0),
         ];
         let tree = BarnesHutTree::build(&nodes);
         assert!(matches!(tree.root, QuadNode::Internal { .. }));
@@ -233,7 +241,7 @@ mod tests {
 
     #[test]
     fn repulsion_pushes_apart() {
-        let nodes = vec![make_node(1, 0.0, 0.0), make_node(2, 2.0, 0.0)];
+        let nodes = vec![node_at(1, 0.0, 0.0), node_at(2, 2.0, 0.0)];
         let tree = BarnesHutTree::build(&nodes);
 
         let f0 = tree.compute_repulsion(0, &nodes, 100.0, 0.7);
@@ -247,8 +255,8 @@ mod tests {
 
     #[test]
     fn closer_nodes_repel_more() {
-        let close = vec![make_node(1, 0.0, 0.0), make_node(2, 1.0, 0.0)];
-        let far = vec![make_node(1, 0.0, 0.0), make_node(2, 10.0, 0.0)];
+        let close = vec![node_at(1, 0.0, 0.0), node_at(2, 1.0, 0.0)];
+        let far = vec![node_at(1, 0.0, 0.0), node_at(2, 10.0, 0.0)];
 
         let tree_close = BarnesHutTree::build(&close);
         let tree_far = BarnesHutTree::build(&far);
@@ -264,12 +272,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn masses_scale_force() {
+        let nodes = vec![node_at(1, 0.0, 0.0), node_at(2, 4.0, 0.0)];
+        let unit = BarnesHutTree::build(&nodes);
+        let heavy = BarnesHutTree::build_with_masses(&nodes, &[1.0, 3.0]);
+
+        let f_unit = unit.compute_repulsion(0, &nodes, 100.0, 0.7);
+        let f_heavy = heavy.compute_repulsion(0, &nodes, 100.0, 0.7);
+        assert!((f_heavy.x - 3.0 * f_unit.x).abs() < 1e-4);
+
+        // Custom law: 1/d instead of 1/d²
+        let linear = unit.compute_force_with(0, &nodes, 0.7, |d| 1.0 / d);
+        assert!((linear.x + 0.25).abs() < 1e-6);
+    }
+
     #[test]
     fn force_approximation_accuracy() {
         let nodes: Vec<Node> = (0..10)
             .map(|i| {
                 let angle = i as f32 * std::f32::consts::TAU / 10.0;
-                make_node(i, angle.cos() * 10.0, angle.sin() * 10.0)
+                node_at(i, angle.cos() * 10.0, angle.sin() * 10.0)
             })
             .collect();
 
@@ -283,11 +306,11 @@ mod tests {
             // Compute naive O(n²) force
             let pi = Vec2::new(nodes[i].x, nodes[i].y);
             let mut naive_force = Vec2::ZERO;
-            for j in 0..nodes.len() {
+            for (j, other) in nodes.iter().enumerate() {
                 if i == j {
                     continue;
                 }
-                let pj = Vec2::new(nodes[j].x, nodes[j].y);
+                let pj = Vec2::new(other.x, other.y);
                 let delta = pi - pj;
                 let dist = delta.length().max(MIN_DIST);
                 naive_force += delta.normalize() * (repulsion / (dist * dist));
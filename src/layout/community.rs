@@ -0,0 +1,145 @@
+use crate::graph::Graph;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+/// Arc length between neighbouring members on a community's ring.
+const NODE_SPACING: f32 = 10.0;
+/// Extra arc left between neighbouring communities on the meta-circle.
+const COMMUNITY_GAP: f32 = 4.0 * NODE_SPACING;
+
+/// Groups nodes by community: each community's centroid sits on a
+/// meta-circle around the origin and its members sit on a ring around that
+/// centroid, in node order.
+///
+/// `communities` is index-aligned with graph.nodes() (e.g. the output of
+/// `louvain`); IDs need not be dense. Ring and meta-circle radii grow with
+/// community size and count so nothing overlaps, and a single-member
+/// community sits exactly on its centroid. Nodes past the end of
+/// `communities` are left where they are. Meant as a starting point for a
+/// force pass rather than a final layout.
+pub fn community_layout(graph: &mut Graph, communities: &[usize]) {
+    let n = graph.node_count().min(communities.len());
+    if n == 0 {
+        return;
+    }
+
+    // Dense community index per node, in order of first appearance
+    let mut dense_of = HashMap::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    for (i, &c) in communities[..n].iter().enumerate() {
+        let d = *dense_of.entry(c).or_insert_with(|| {
+            members.push(Vec::new());
+            members.len() - 1
+        });
+        members[d].push(i);
+    }
+
+    let ring_radius: Vec<f32> = members
+        .iter()
+        .map(|m| {
+            if m.len() < 2 {
+                0.0
+            } else {
+                m.len() as f32 * NODE_SPACING / TAU
+            }
+        })
+        .collect();
+
+    let centroids: Vec<(f32, f32)> = if members.len() == 1 {
+        vec![(0.0, 0.0)]
+    } else {
+        // Give each community an arc proportional to its diameter
+        let arcs: Vec<f32> = ring_radius
+            .iter()
+            .map(|r| 2.0 * r + COMMUNITY_GAP)
+            .collect();
+        let circumference: f32 = arcs.iter().sum();
+        let meta_radius = circumference / TAU;
+        let mut travelled = 0.0;
+        arcs.iter()
+            .map(|arc| {
+                let angle = (travelled + arc / 2.0) / meta_radius;
+                travelled += arc;
+                (meta_radius * angle.cos(), meta_radius * angle.sin())
+            })
+            .collect()
+    };
+
+    let nodes = graph.nodes_mut();
+    for ((group, &(cx, cy)), &r) in members.iter().zip(&centroids).zip(&ring_radius) {
+        for (k, &i) in group.iter().enumerate() {
+            let angle = k as f32 * TAU / group.len() as f32;
+            nodes[i].x = cx + r * angle.cos();
+            nodes[i].y = cy + r * angle.sin();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+
+    fn make_graph(n: u32) -> Graph {
+        let nodes = (0..n)
+            .map(|id| Node {
+                id,
+                ..Node::default()
+            })
+            .collect();
+        Graph::new(nodes, vec![])
+    }
+
+    fn distance(g: &Graph, a: usize, b: usize) -> f32 {
+        let (p, q) = (&g.nodes()[a], &g.nodes()[b]);
+        ((p.x - q.x).powi(2) + (p.y - q.y).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn members_cluster_together() {
+        // Three communities with sparse IDs and interleaved members
+        let communities = [7, 3, 7, 9, 3, 7, 9, 3, 7, 7];
+        let mut g = make_graph(communities.len() as u32);
+        community_layout(&mut g, &communities);
+
+        let (mut intra, mut inter) = (Vec::new(), Vec::new());
+        for a in 0..communities.len() {
+            for b in a + 1..communities.len() {
+                let d = distance(&g, a, b);
+                if communities[a] == communities[b] {
+                    intra.push(d);
+                } else {
+                    inter.push(d);
+                }
+            }
+        }
+        let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+        assert!(mean(&intra) < mean(&inter), "{intra:?} vs {inter:?}");
+        // No two nodes land on the same spot
+        assert!(intra.iter().chain(&inter).all(|&d| d > 1.0));
+    }
+
+    #[test]
+    fn singleton_community_sits_on_centroid() {
+        let mut g = make_graph(3);
+        community_layout(&mut g, &[0, 0, 1]);
+        let lone = &g.nodes()[2];
+        let pair_mid_x = (g.nodes()[0].x + g.nodes()[1].x) / 2.0;
+        assert!(lone.x.is_finite() && lone.y.is_finite());
+        assert!((lone.x - pair_mid_x).abs() > 1.0);
+
+        // One community of one: straight to the origin
+        let mut single = make_graph(1);
+        community_layout(&mut single, &[5]);
+        assert_eq!((single.nodes()[0].x, single.nodes()[0].y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn empty_communities_leave_positions() {
+        let mut g = make_graph(2);
+        g.nodes_mut()[0].x = 12.0;
+        community_layout(&mut g, &[]);
+        assert_eq!(g.nodes()[0].x, 12.0);
+        community_layout(&mut make_graph(0), &[1, 2]);
+    }
+}
@@ -2,6 +2,11 @@ use crate::graph::Graph;
 use crate::layout::barnes_hut::BarnesHutTree;
 use glam::Vec2;
 
+/// Default cap, in world units, on how far one node moves in one step.
+/// Far above what a healthy layout moves, so it only bites when forces
+/// blow up (e.g. strong repulsion on a dense, tightly packed graph).
+pub const DEFAULT_MAX_DISPLACEMENT: f32 = 200.0;
+
 pub struct ForceParams {
     pub attraction: f32,
     pub repulsion: f32,
@@ -25,6 +30,8 @@ impl Default for ForceParams {
 pub struct ForceLayout {
     pub params: ForceParams,
     velocities: Vec<Vec2>,
+    max_displacement: f32,
+    last_movement: f32,
 }
 
 impl ForceLayout {
@@ -32,11 +39,45 @@ impl ForceLayout {
         Self {
             params,
             velocities: vec![Vec2::ZERO; node_count],
+            max_displacement: DEFAULT_MAX_DISPLACEMENT,
+            last_movement: f32::INFINITY,
         }
     }
 
-    pub fn step(&mut self, graph: &mut Graph) {
+    /// Caps how far any node moves in one step, in world units. Keeps a
+    /// diverging layout bounded instead of flinging nodes to infinity.
+    /// Negative and NaN caps count as 0, which holds every node still.
+    pub fn set_max_displacement(&mut self, px: f32) {
+        // `max` also maps NaN to 0
+        self.max_displacement = px.max(0.0);
+    }
+
+    pub fn max_displacement(&self) -> f32 {
+        self.max_displacement
+    }
+
+    /// True once the last `step` moved nodes less than `threshold` in
+    /// total. False before the first step.
+    pub fn is_settled(&self, threshold: f32) -> bool {
+        self.last_movement < threshold
+    }
+
+    /// Runs one iteration and returns the total distance nodes moved.
+    /// Repulsion always goes through a Barnes-Hut tree, so a step costs
+    /// O(n log n + m) however small the graph; stop once `is_settled`
+    /// reports true. Nodes move at
+    /// most `max_displacement` per step, and any with a non-finite
+    /// position are reset to the origin first, as one NaN would poison
+    /// the Barnes-Hut tree and with it every other node.
+    pub fn step(&mut self, graph: &mut Graph) -> f32 {
         let nodes = graph.nodes_mut();
+        for (node, velocity) in nodes.iter_mut().zip(&mut self.velocities) {
+            if !(node.x.is_finite() && node.y.is_finite()) {
+                node.x = 0.0;
+                node.y = 0.0;
+                *velocity = Vec2::ZERO;
+            }
+        }
         let mut forces = vec![Vec2::ZERO; nodes.len()];
 
         // repulsion via Barnes-Hut
@@ -68,11 +109,22 @@ impl ForceLayout {
         }
 
         // integrate
+        let mut movement = 0.0;
         for (i, node) in graph.nodes_mut().iter_mut().enumerate() {
-            self.velocities[i] = (self.velocities[i] + forces[i]) * self.params.damping;
+            let velocity = ((self.velocities[i] + forces[i]) * self.params.damping)
+                .clamp_length_max(self.max_displacement);
+            // Skip a node whose force overflowed rather than move it to NaN
+            self.velocities[i] = if velocity.is_finite() {
+                velocity
+            } else {
+                Vec2::ZERO
+            };
             node.x += self.velocities[i].x;
             node.y += self.velocities[i].y;
+            movement += self.velocities[i].length();
         }
+        self.last_movement = movement;
+        movement
     }
 }
 
@@ -80,23 +132,13 @@ impl ForceLayout {
 mod tests {
     use super::*;
     use crate::graph::{Edge, Node};
-
-    fn make_node(id: u32) -> Node {
-        Node {
-            id,
-            label: String::new(),
-            pagerank: 0.0,
-            degree: 0,
-            x: 0.0,
-            y: 0.0,
-        }
-    }
+    use crate::test_utils::node;
 
     #[test]
     fn layout_spreads_nodes() {
         let nodes: Vec<Node> = (0..5)
             .map(|i| {
-                let mut n = make_node(i);
+                let mut n = node(i);
                 // Small distinct offsets so forces aren't degenerate
                 n.x = (i as f32) * 0.1;
                 n.y = (i as f32) * 0.07;
@@ -107,18 +149,22 @@ mod tests {
             Edge {
                 source: 0,
                 target: 1,
+                weight: 1.0,
             },
             Edge {
                 source: 1,
                 target: 2,
+                weight: 1.0,
             },
             Edge {
                 source: 2,
                 target: 3,
+                weight: 1.0,
             },
             Edge {
                 source: 3,
                 target: 4,
+                weight: 1.0,
             },
         ];
         let mut graph = Graph::new(nodes, edges);
@@ -141,4 +187,93 @@ mod tests {
             avg_dist
         );
     }
+
+    #[test]
+    fn dense_graph_stays_finite_and_bounded() {
+        // Complete graph packed into a tiny disc, with extreme repulsion
+        // and attraction, plus a node that starts at NaN
+        let n = 60;
+        let nodes: Vec<Node> = (0..n)
+            .map(|i| {
+                let mut node = node(i);
+                let angle = i as f32 * 2.4;
+                node.x = angle.cos() * 1e-3;
+                node.y = angle.sin() * 1e-3;
+                node
+            })
+            .collect();
+        let mut edges = Vec::new();
+        for i in 0..n {
+            for j in i + 1..n {
+                edges.push(Edge {
+                    source: i,
+                    target: j,
+                    weight: 1.0,
+                });
+            }
+        }
+        let mut graph = Graph::new(nodes, edges);
+        graph.nodes_mut()[7].x = f32::NAN;
+        let params = ForceParams {
+            attraction: 50.0,
+            repulsion: 1e9,
+            damping: 1.0,
+            ..ForceParams::default()
+        };
+        let mut layout = ForceLayout::new(n as usize, params);
+        layout.set_max_displacement(25.0);
+
+        let steps = 200;
+        for _ in 0..steps {
+            let moved = layout.step(&mut graph);
+            assert!(moved <= 25.0 * n as f32 + 1e-3, "{moved}");
+        }
+        let bound = 25.0 * steps as f32;
+        for node in graph.nodes() {
+            assert!(node.x.is_finite() && node.y.is_finite(), "{node:?}");
+            assert!(node.x.abs() <= bound && node.y.abs() <= bound, "{node:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_max_displacement_holds_nodes_still() {
+        let mut a = node(0);
+        a.x = -1.0;
+        let mut b = node(1);
+        b.x = 1.0;
+        let mut graph = Graph::new(vec![a, b], vec![]);
+        let mut layout = ForceLayout::new(2, ForceParams::default());
+        for px in [-10.0, f32::NAN] {
+            layout.set_max_displacement(px);
+            assert_eq!(layout.max_displacement(), 0.0);
+            // A negative cap would otherwise push nodes against the force
+            assert_eq!(layout.step(&mut graph), 0.0);
+            assert_eq!((graph.nodes()[0].x, graph.nodes()[1].x), (-1.0, 1.0));
+        }
+        layout.set_max_displacement(f32::INFINITY);
+        assert!(layout.step(&mut graph) > 0.0);
+    }
+
+    #[test]
+    fn step_reports_movement_until_settled() {
+        let mut a = node(0);
+        a.x = -1.0;
+        let mut b = node(1);
+        b.x = 1.0;
+        let edges = vec![Edge {
+            source: 0,
+            target: 1,
+            weight: 1.0,
+        }];
+        let mut graph = Graph::new(vec![a, b], edges);
+        let mut layout = ForceLayout::new(2, ForceParams::default());
+        assert!(!layout.is_settled(0.01));
+
+        let first = layout.step(&mut graph);
+        assert!(first > 0.0);
+        for _ in 0..500 {
+            layout.step(&mut graph);
+        }
+        assert!(layout.is_settled(0.01));
+    }
 }
@@ -0,0 +1,454 @@
+use crate::graph::Graph;
+use crate::layout::barnes_hut::BarnesHutTree;
+use crate::layout::placement::seed_positions;
+use glam::Vec2;
+use std::collections::HashSet;
+
+/// Above this many nodes, repulsion switches from exact all-pairs to the
+/// Barnes-Hut approximation.
+const BARNES_HUT_THRESHOLD: usize = 500;
+/// Temperature multiplier per unit of step time.
+const COOLING: f32 = 0.95;
+/// Floor on pair distance, so coincident nodes get a large but finite push.
+const MIN_DIST: f32 = 0.01;
+
+/// Fruchterman-Reingold layout in a `width x height` frame centred on the
+/// origin.
+///
+/// Uses the classic ideal edge length `k = sqrt(area / n)`: nodes repel with
+/// `k² / d` and edge endpoints attract with `d² / k`. Each step moves a node
+/// at most the current temperature, which starts at a tenth of the frame
+/// width and decays geometrically. `step_dt` scales both the move and the
+/// decay by the time elapsed, so the schedule doesn't depend on frame rate.
+/// Optional gravity (`set_gravity`) pulls every node toward the origin,
+/// which keeps disconnected components from pushing each other out to the
+/// frame's edges.
+pub struct ForceDirectedLayout {
+    pub width: f32,
+    pub height: f32,
+    /// Barnes-Hut opening angle, used once the graph is large.
+    pub theta: f32,
+    /// When set, the first step scatters nodes over the frame with
+    /// `seed_positions` instead of starting from their current positions.
+    pub seed: Option<u64>,
+    temperature: f32,
+    gravity: f32,
+    last_movement: f32,
+    seeded: bool,
+    /// Node indices that keep their position. They still push and pull on
+    /// everything else.
+    pinned: HashSet<usize>,
+}
+
+impl ForceDirectedLayout {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            theta: 0.7,
+            seed: None,
+            temperature: width / 10.0,
+            gravity: 0.0,
+            last_movement: f32::INFINITY,
+            seeded: false,
+            pinned: HashSet::new(),
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Holds the node at `node_index` where it is, e.g. while it's dragged.
+    pub fn pin(&mut self, node_index: usize) {
+        self.pinned.insert(node_index);
+    }
+
+    pub fn unpin(&mut self, node_index: usize) {
+        self.pinned.remove(&node_index);
+    }
+
+    pub fn is_pinned(&self, node_index: usize) -> bool {
+        self.pinned.contains(&node_index)
+    }
+
+    /// Pulls each node toward the frame centre with a force of `strength`
+    /// times its distance from it. 0 (the default) turns gravity off.
+    /// Around 1, the pull on a node `k` from the centre matches the push
+    /// from one neighbour at distance `k`.
+    pub fn set_gravity(&mut self, strength: f32) {
+        self.gravity = strength;
+    }
+
+    pub fn gravity(&self) -> f32 {
+        self.gravity
+    }
+
+    pub fn temperature(&self) -> f32 {
+        self.temperature
+    }
+
+    /// Whether the last step moved nodes less than `threshold` in total.
+    /// Cooling shrinks every move, so this eventually holds. False before
+    /// any step has run.
+    pub fn is_settled(&self, threshold: f32) -> bool {
+        self.last_movement < threshold
+    }
+
+    pub fn run(&mut self, graph: &mut Graph, iterations: usize) {
+        for _ in 0..iterations {
+            self.step(graph);
+        }
+    }
+
+    /// Runs one iteration and returns the total distance nodes moved.
+    /// Repulsion is exact all-pairs, O(n²), up to `BARNES_HUT_THRESHOLD`
+    /// nodes and Barnes-Hut, O(n log n), above it; attraction adds O(m).
+    ///
+    /// No node moves further than `temperature()`, which falls every step,
+    /// so total movement stays under a shrinking `n * temperature()`. It
+    /// isn't itself monotonic: near equilibrium an uncapped move can
+    /// overshoot, and the step after it move further.
+    pub fn step(&mut self, graph: &mut Graph) -> f32 {
+        self.step_dt(graph, 1.0)
+    }
+
+    /// `step` advanced by `dt` units of time, where 1.0 is one fixed
+    /// `step`: nodes move `dt` times as far and the temperature cools by
+    /// `COOLING^dt`. Two steps at `dt = 0.5` approximate one at 1.0, so
+    /// driving this from the frame delta gives the same layout on fast and
+    /// slow displays.
+    pub fn step_dt(&mut self, graph: &mut Graph, dt: f32) -> f32 {
+        let n = graph.node_count();
+        if n == 0 {
+            self.last_movement = 0.0;
+            return 0.0;
+        }
+        if let Some(seed) = self.seed.filter(|_| !self.seeded) {
+            // Pinned nodes keep their positions through seeding too
+            let held: Vec<(usize, f32, f32)> = self
+                .pinned
+                .iter()
+                .filter_map(|&i| graph.nodes().get(i).map(|n| (i, n.x, n.y)))
+                .collect();
+            seed_positions(graph, seed, self.width, self.height);
+            for (i, x, y) in held {
+                let node = &mut graph.nodes_mut()[i];
+                node.x = x;
+                node.y = y;
+            }
+            self.seeded = true;
+        }
+        let k = (self.width * self.height / n as f32).sqrt();
+        let mut displacement = vec![Vec2::ZERO; n];
+
+        // repulsion
+        let nodes = graph.nodes();
+        if n > BARNES_HUT_THRESHOLD {
+            let tree = BarnesHutTree::build(nodes);
+            for (i, d) in displacement.iter_mut().enumerate() {
+                *d += tree.compute_force_with(i, nodes, self.theta, |dist| k * k / dist);
+            }
+        } else {
+            for i in 0..n {
+                let pi = Vec2::new(nodes[i].x, nodes[i].y);
+                for j in i + 1..n {
+                    let delta = pi - Vec2::new(nodes[j].x, nodes[j].y);
+                    let dir = direction(delta, i, j);
+                    let dist = delta.length().max(MIN_DIST);
+                    let f = dir * (k * k / dist);
+                    displacement[i] += f;
+                    displacement[j] -= f;
+                }
+            }
+        }
+
+        // attraction
+        for edge in graph.edges() {
+            if let (Some(i), Some(j)) =
+                (graph.node_index(edge.source), graph.node_index(edge.target))
+            {
+                if i == j {
+                    continue;
+                }
+                let delta = Vec2::new(nodes[i].x - nodes[j].x, nodes[i].y - nodes[j].y);
+                // d² / k along the unit vector is delta * d / k
+                let f = delta * (delta.length() / k);
+                displacement[i] -= f;
+                displacement[j] += f;
+            }
+        }
+
+        // gravity, toward the origin
+        if self.gravity > 0.0 {
+            for (node, d) in nodes.iter().zip(&mut displacement) {
+                *d -= Vec2::new(node.x, node.y) * self.gravity;
+            }
+        }
+
+        // move, capped by temperature, and keep inside the frame
+        let (half_w, half_h) = (self.width / 2.0, self.height / 2.0);
+        let mut movement = 0.0;
+        for (i, (node, d)) in graph.nodes_mut().iter_mut().zip(&displacement).enumerate() {
+            if self.pinned.contains(&i) {
+                continue;
+            }
+            let len = d.length();
+            if len > 0.0 {
+                let step = *d / len * len.min(self.temperature) * dt;
+                let (old_x, old_y) = (node.x, node.y);
+                node.x = (node.x + step.x).clamp(-half_w, half_w);
+                node.y = (node.y + step.y).clamp(-half_h, half_h);
+                movement += ((node.x - old_x).powi(2) + (node.y - old_y).powi(2)).sqrt();
+            }
+        }
+
+        self.temperature *= COOLING.powf(dt);
+        self.last_movement = movement;
+        movement
+    }
+}
+
+/// Unit vector along `delta`, with a fixed per-pair fallback so coincident
+/// nodes (e.g. everything still at the origin) still push apart.
+fn direction(delta: Vec2, i: usize, j: usize) -> Vec2 {
+    if delta.length_squared() > MIN_DIST * MIN_DIST {
+        return delta.normalize();
+    }
+    let angle = (i * 31 + j * 17) as f32;
+    Vec2::new(angle.cos(), angle.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+    use crate::test_utils::{edge, node_at};
+
+    fn distance(a: &Node, b: &Node) -> f32 {
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+    }
+
+    fn ring_start(n: u32) -> Vec<Node> {
+        (0..n)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / n as f32;
+                node_at(i, angle.cos() * 50.0, angle.sin() * 50.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn connected_nodes_end_up_closer() {
+        // Three disjoint pairs, scattered so no pair starts out adjacent
+        let mut state = 17u32;
+        let mut next = || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 24) as f32 * 200.0 - 100.0
+        };
+        let nodes = (0..6).map(|i| node_at(i, next(), next())).collect();
+        let edges = vec![edge(0, 3), edge(1, 4), edge(2, 5)];
+        let mut graph = Graph::new(nodes, edges);
+        let mut layout = ForceDirectedLayout::new(400.0, 400.0);
+        layout.run(&mut graph, 200);
+
+        let nodes = graph.nodes();
+        let mut linked = Vec::new();
+        let mut unlinked = Vec::new();
+        for i in 0..6 {
+            for j in i + 1..6 {
+                let d = distance(&nodes[i], &nodes[j]);
+                if j == i + 3 {
+                    linked.push(d);
+                } else {
+                    unlinked.push(d);
+                }
+            }
+        }
+        let max_linked = linked.iter().cloned().fold(0.0, f32::max);
+        let min_unlinked = unlinked.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(
+            max_linked < min_unlinked,
+            "linked {linked:?} vs unlinked {unlinked:?}"
+        );
+    }
+
+    #[test]
+    fn coincident_nodes_separate_and_stay_in_frame() {
+        let nodes = (0..4).map(|i| node_at(i, 0.0, 0.0)).collect();
+        let mut graph = Graph::new(nodes, vec![edge(0, 1)]);
+        let mut layout = ForceDirectedLayout::new(100.0, 60.0);
+        layout.run(&mut graph, 50);
+
+        let nodes = graph.nodes();
+        for n in nodes {
+            assert!(
+                n.x.abs() <= 50.0 && n.y.abs() <= 30.0,
+                "{n:?} left the frame"
+            );
+        }
+        assert!(distance(&nodes[2], &nodes[3]) > 1.0);
+    }
+
+    #[test]
+    fn temperature_cools() {
+        let mut graph = Graph::new(ring_start(3), vec![]);
+        let mut layout = ForceDirectedLayout::new(100.0, 100.0);
+        assert_eq!(layout.temperature(), 10.0);
+        layout.run(&mut graph, 10);
+        assert!(layout.temperature() < 10.0);
+        // Empty graphs are a no-op
+        layout.step(&mut Graph::new(vec![], vec![]));
+    }
+
+    #[test]
+    fn half_steps_approximate_full_steps() {
+        let run = |steps: usize, dt: f32| {
+            let edges = vec![edge(0, 1), edge(1, 2), edge(2, 3), edge(3, 0), edge(0, 4)];
+            let mut graph = Graph::new(ring_start(5), edges);
+            let mut layout = ForceDirectedLayout::new(300.0, 300.0);
+            for _ in 0..steps {
+                layout.step_dt(&mut graph, dt);
+            }
+            (graph, layout.temperature())
+        };
+        // Early frames differ (half steps overshoot less), but both
+        // schedules cool identically and settle to the same layout
+        let (full, full_temp) = run(60, 1.0);
+        let (half, half_temp) = run(120, 0.5);
+        assert!((full_temp - half_temp).abs() < 1e-3 * full_temp);
+        for (a, b) in full.nodes().iter().zip(half.nodes()) {
+            assert!(distance(a, b) < 5.0, "{a:?} vs {b:?}");
+        }
+    }
+
+    #[test]
+    fn gravity_keeps_components_near_the_centre() {
+        let run = |gravity: f32| {
+            let nodes = vec![
+                node_at(0, -12.0, 0.0),
+                node_at(1, -8.0, 4.0),
+                node_at(2, -8.0, -4.0),
+                node_at(3, 12.0, 0.0),
+                node_at(4, 8.0, 4.0),
+                node_at(5, 8.0, -4.0),
+            ];
+            let edges = vec![
+                edge(0, 1),
+                edge(1, 2),
+                edge(2, 0),
+                edge(3, 4),
+                edge(4, 5),
+                edge(5, 3),
+            ];
+            let mut graph = Graph::new(nodes, edges);
+            let mut layout = ForceDirectedLayout::new(1000.0, 1000.0);
+            layout.set_gravity(gravity);
+            layout.run(&mut graph, 300);
+            let nodes = graph.nodes();
+            let centroid = |range: std::ops::Range<usize>| {
+                let (x, y) = range.fold((0.0, 0.0), |(x, y), i| (x + nodes[i].x, y + nodes[i].y));
+                (x / 3.0f32).hypot(y / 3.0)
+            };
+            (centroid(0..3), centroid(3..6))
+        };
+        // Without gravity the triangles shove each other into the frame
+        // edges; with it they settle well inside
+        let (free_a, free_b) = run(0.0);
+        assert!(free_a > 450.0 && free_b > 450.0, "{free_a} {free_b}");
+        let (held_a, held_b) = run(4.0);
+        assert!(held_a < 300.0 && held_b < 300.0, "{held_a} {held_b}");
+    }
+
+    #[test]
+    fn seeded_runs_are_identical() {
+        let run = || {
+            let nodes = (0..10).map(|i| node_at(i, 0.0, 0.0)).collect();
+            let edges = (0..9).map(|i| edge(i, i + 1)).collect();
+            let mut graph = Graph::new(nodes, edges);
+            ForceDirectedLayout::new(200.0, 200.0)
+                .with_seed(3)
+                .run(&mut graph, 30);
+            graph
+                .nodes()
+                .iter()
+                .map(|n| (n.x.to_bits(), n.y.to_bits()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn pinned_nodes_stay_put() {
+        let nodes = vec![
+            node_at(0, -30.0, 0.0),
+            node_at(1, 30.0, 0.0),
+            node_at(2, 0.0, 5.0),
+            node_at(3, 0.0, -5.0),
+        ];
+        let edges = vec![edge(0, 2), edge(1, 3), edge(2, 3)];
+        let mut graph = Graph::new(nodes, edges);
+        let mut layout = ForceDirectedLayout::new(200.0, 200.0).with_seed(1);
+        layout.pin(0);
+        layout.pin(1);
+        assert!(layout.is_pinned(0) && !layout.is_pinned(2));
+
+        let start: Vec<(f32, f32)> = graph.nodes().iter().map(|n| (n.x, n.y)).collect();
+        layout.run(&mut graph, 40);
+        let nodes = graph.nodes();
+        assert_eq!((nodes[0].x, nodes[0].y), (-30.0, 0.0));
+        assert_eq!((nodes[1].x, nodes[1].y), (30.0, 0.0));
+        assert_ne!((nodes[2].x, nodes[2].y), start[2]);
+        assert_ne!((nodes[3].x, nodes[3].y), start[3]);
+
+        layout.unpin(0);
+        layout.step(&mut graph);
+        assert_ne!((graph.nodes()[0].x, graph.nodes()[0].y), (-30.0, 0.0));
+    }
+
+    #[test]
+    fn movement_shrinks_as_it_cools() {
+        let edges = (0..5).map(|i| edge(i, (i + 1) % 5)).collect();
+        let mut graph = Graph::new(ring_start(5), edges);
+        let mut layout = ForceDirectedLayout::new(300.0, 300.0);
+        assert!(!layout.is_settled(1.0));
+
+        let mut bounds = Vec::new();
+        let mut movements = Vec::new();
+        for _ in 0..60 {
+            bounds.push(5.0 * layout.temperature());
+            movements.push(layout.step(&mut graph));
+        }
+        // Every step stays under a strictly shrinking bound
+        assert!(bounds.windows(2).all(|w| w[1] < w[0]));
+        for (moved, bound) in movements.iter().zip(&bounds) {
+            assert!(*moved <= bound + 1e-3, "{moved} > {bound}");
+        }
+        // Single steps can rise again after an overshoot near equilibrium,
+        // but ten-step averages fall
+        let averages: Vec<f32> = movements
+            .chunks(10)
+            .map(|c| c.iter().sum::<f32>() / c.len() as f32)
+            .collect();
+        for pair in averages.windows(2) {
+            assert!(pair[1] < pair[0], "{movements:?}");
+        }
+        assert!(layout.is_settled(movements[59] + 1e-3));
+    }
+
+    #[test]
+    fn large_graph_uses_barnes_hut() {
+        let n = BARNES_HUT_THRESHOLD as u32 + 100;
+        let edges = (0..n).map(|i| edge(i, (i + 1) % n)).collect();
+        let mut graph = Graph::new(ring_start(n), edges);
+        let mut layout = ForceDirectedLayout::new(1000.0, 1000.0);
+        layout.run(&mut graph, 5);
+        assert!(
+            graph
+                .nodes()
+                .iter()
+                .all(|n| n.x.is_finite() && n.y.is_finite())
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use crate::graph::Graph;
+use crate::layout::barnes_hut::BarnesHutTree;
+use crate::layout::placement::seed_positions;
+use glam::Vec2;
+
+/// Cap on how fast the global speed may grow between steps.
+const MAX_SPEED_RISE: f32 = 1.5;
+
+pub struct ForceAtlas2Params {
+    /// Repulsion strength (`kr`). Larger values spread the graph out.
+    pub scaling: f32,
+    /// Pull toward the origin (`kg`).
+    pub gravity: f32,
+    /// Strong gravity grows with distance from the origin instead of
+    /// being constant, keeping disconnected components close.
+    pub strong_gravity: bool,
+    /// LinLog mode: attraction `log(1 + d)` instead of `d`, which
+    /// tightens clusters.
+    pub linlog: bool,
+    /// Barnes-Hut opening angle. 1.2 is Gephi's default.
+    pub theta: f32,
+    /// How much swinging is tolerated before the global speed drops.
+    pub jitter_tolerance: f32,
+    /// When set, the first step scatters nodes with `seed_positions` over a
+    /// square of side `20 * sqrt(n)` instead of using current positions.
+    pub seed: Option<u64>,
+}
+
+impl Default for ForceAtlas2Params {
+    fn default() -> Self {
+        Self {
+            scaling: 2.0,
+            gravity: 1.0,
+            strong_gravity: false,
+            linlog: false,
+            theta: 1.2,
+            jitter_tolerance: 1.0,
+            seed: None,
+        }
+    }
+}
+
+/// ForceAtlas2 (Jacomy et al., 2014).
+///
+/// Every node carries mass `degree + 1`. Repulsion between two nodes is
+/// `scaling * m_i * m_j / d`, approximated with the Barnes-Hut tree;
+/// attraction along edges is `weight * d` (or `weight * log(1 + d)` in
+/// LinLog mode); gravity pulls each node toward the origin with strength
+/// `gravity * m_i`. Step sizes come from FA2's adaptive speed, which slows
+/// nodes that oscillate between steps.
+pub struct ForceAtlas2 {
+    pub params: ForceAtlas2Params,
+    prev_forces: Vec<Vec2>,
+    speed: f32,
+    last_movement: f32,
+    seeded: bool,
+}
+
+impl ForceAtlas2 {
+    pub fn new(params: ForceAtlas2Params) -> Self {
+        Self {
+            params,
+            prev_forces: Vec::new(),
+            speed: 1.0,
+            last_movement: f32::INFINITY,
+            seeded: false,
+        }
+    }
+
+    /// Whether the last `step` moved nodes less than `threshold` in total.
+    /// Adaptive speed slows swinging nodes, so oscillation doesn't keep
+    /// this false forever. False before the first step.
+    pub fn is_settled(&self, threshold: f32) -> bool {
+        self.last_movement < threshold
+    }
+
+    /// Runs one iteration and returns the total distance nodes moved.
+    /// Mass-weighted Barnes-Hut repulsion makes it O(n log n), plus O(m)
+    /// for attraction and O(n) for gravity and the speed update.
+    pub fn step(&mut self, graph: &mut Graph) -> f32 {
+        let n = graph.node_count();
+        if n == 0 {
+            self.last_movement = 0.0;
+            return 0.0;
+        }
+        if let Some(seed) = self.params.seed.filter(|_| !self.seeded) {
+            let side = 20.0 * (n as f32).sqrt();
+            seed_positions(graph, seed, side, side);
+            self.seeded = true;
+        }
+        if self.prev_forces.len() != n {
+            self.prev_forces = vec![Vec2::ZERO; n];
+        }
+
+        let masses: Vec<f32> = (0..n)
+            .map(|i| graph.neighbor_indices(i).len() as f32 + 1.0)
+            .collect();
+        let nodes = graph.nodes();
+        let mut forces = vec![Vec2::ZERO; n];
+
+        // repulsion
+        let tree = BarnesHutTree::build_with_masses(nodes, &masses);
+        let scaling = self.params.scaling;
+        for (i, f) in forces.iter_mut().enumerate() {
+            *f += tree.compute_force_with(i, nodes, self.params.theta, |d| scaling / d) * masses[i];
+        }
+
+        // attraction
+        for edge in graph.edges() {
+            if let (Some(i), Some(j)) =
+                (graph.node_index(edge.source), graph.node_index(edge.target))
+            {
+                let delta = Vec2::new(nodes[j].x - nodes[i].x, nodes[j].y - nodes[i].y);
+                let dist = delta.length();
+                if dist <= 0.0 {
+                    continue;
+                }
+                let magnitude = if self.params.linlog {
+                    dist.ln_1p()
+                } else {
+                    dist
+                };
+                let f = delta / dist * (edge.weight * magnitude);
+                forces[i] += f;
+                forces[j] -= f;
+            }
+        }
+
+        // gravity
+        for (i, node) in nodes.iter().enumerate() {
+            let pos = Vec2::new(node.x, node.y);
+            let dist = pos.length();
+            if dist <= 0.0 {
+                continue;
+            }
+            let magnitude = if self.params.strong_gravity {
+                self.params.gravity * masses[i] * dist
+            } else {
+                self.params.gravity * masses[i]
+            };
+            forces[i] -= pos / dist * magnitude;
+        }
+
+        // adaptive speed
+        let mut swinging_total = 0.0;
+        let mut traction_total = 0.0;
+        let swinging: Vec<f32> = forces
+            .iter()
+            .zip(&self.prev_forces)
+            .zip(&masses)
+            .map(|((f, prev), m)| {
+                let swing = (*f - *prev).length();
+                swinging_total += m * swing;
+                traction_total += m * (*f + *prev).length() / 2.0;
+                swing
+            })
+            .collect();
+        if swinging_total > 0.0 {
+            let target = self.params.jitter_tolerance * traction_total / swinging_total;
+            self.speed = target.min(self.speed * MAX_SPEED_RISE);
+        }
+
+        // integrate
+        let speed = self.speed;
+        let mut movement = 0.0;
+        for (i, node) in graph.nodes_mut().iter_mut().enumerate() {
+            let local = speed / (1.0 + speed * swinging[i].sqrt());
+            node.x += forces[i].x * local;
+            node.y += forces[i].y * local;
+            movement += forces[i].length() * local;
+        }
+        self.prev_forces = forces;
+        self.last_movement = movement;
+        movement
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+    use crate::test_utils::{edge, node_at};
+
+    /// Hub 0 offset from the origin, with spokes scattered around it.
+    fn hub_and_spoke(spokes: u32) -> Graph {
+        let mut nodes = vec![node_at(0, 40.0, -30.0)];
+        nodes.extend((1..=spokes).map(|i| {
+            let angle = i as f32 * 2.4;
+            node_at(i, 40.0 + angle.cos() * 20.0, -30.0 + angle.sin() * 20.0)
+        }));
+        let edges = (1..=spokes).map(|i| edge(0, i)).collect();
+        Graph::new(nodes, edges)
+    }
+
+    fn norm(n: &Node) -> f32 {
+        (n.x * n.x + n.y * n.y).sqrt()
+    }
+
+    #[test]
+    fn hub_settles_near_center() {
+        let mut graph = hub_and_spoke(12);
+        let mut layout = ForceAtlas2::new(ForceAtlas2Params::default());
+        for _ in 0..300 {
+            layout.step(&mut graph);
+        }
+
+        let nodes = graph.nodes();
+        let mean_spoke = nodes[1..].iter().map(norm).sum::<f32>() / 12.0;
+        assert!(
+            norm(&nodes[0]) < 0.25 * mean_spoke,
+            "hub at {} vs spokes at {mean_spoke}",
+            norm(&nodes[0])
+        );
+        assert!(nodes.iter().all(|n| n.x.is_finite() && n.y.is_finite()));
+    }
+
+    #[test]
+    fn strong_gravity_pulls_components_closer() {
+        // Two disconnected pairs far from the origin
+        let run = |strong_gravity| {
+            let nodes = vec![
+                node_at(0, 100.0, 0.0),
+                node_at(1, 105.0, 0.0),
+                node_at(2, -100.0, 0.0),
+                node_at(3, -105.0, 0.0),
+            ];
+            let mut graph = Graph::new(nodes, vec![edge(0, 1), edge(2, 3)]);
+            let mut layout = ForceAtlas2::new(ForceAtlas2Params {
+                strong_gravity,
+                ..ForceAtlas2Params::default()
+            });
+            for _ in 0..200 {
+                layout.step(&mut graph);
+            }
+            graph.nodes().iter().map(norm).fold(0.0, f32::max)
+        };
+        assert!(run(true) < run(false));
+    }
+
+    #[test]
+    fn linlog_weakens_long_range_pull() {
+        let spread = |linlog| {
+            let mut graph = hub_and_spoke(8);
+            let mut layout = ForceAtlas2::new(ForceAtlas2Params {
+                linlog,
+                ..ForceAtlas2Params::default()
+            });
+            for _ in 0..200 {
+                layout.step(&mut graph);
+            }
+            let nodes = graph.nodes();
+            let hub = Vec2::new(nodes[0].x, nodes[0].y);
+            nodes[1..]
+                .iter()
+                .map(|n| (Vec2::new(n.x, n.y) - hub).length())
+                .sum::<f32>()
+        };
+        // Log attraction is weaker at long range, so spokes sit farther out
+        assert!(spread(true) > spread(false));
+    }
+
+    #[test]
+    fn seeded_runs_are_identical() {
+        let run = || {
+            let mut graph = hub_and_spoke(6);
+            let mut layout = ForceAtlas2::new(ForceAtlas2Params {
+                seed: Some(11),
+                ..ForceAtlas2Params::default()
+            });
+            for _ in 0..20 {
+                layout.step(&mut graph);
+            }
+            graph
+                .nodes()
+                .iter()
+                .map(|n| (n.x.to_bits(), n.y.to_bits()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn empty_graph_is_noop() {
+        let mut layout = ForceAtlas2::new(ForceAtlas2Params::default());
+        assert!(!layout.is_settled(0.1));
+        assert_eq!(layout.step(&mut Graph::new(vec![], vec![])), 0.0);
+        assert!(layout.is_settled(0.1));
+    }
+
+    #[test]
+    fn settles_on_small_graph() {
+        let mut graph = hub_and_spoke(6);
+        let mut layout = ForceAtlas2::new(ForceAtlas2Params::default());
+        let first = layout.step(&mut graph);
+        let mut last = first;
+        for _ in 0..300 {
+            last = layout.step(&mut graph);
+        }
+        assert!(last < 0.1 * first, "first {first}, last {last}");
+        assert!(layout.is_settled(0.1 * first));
+    }
+}
@@ -1,5 +1,17 @@
 pub mod barnes_hut;
+pub mod community;
 pub mod force;
+pub mod force_directed;
+pub mod forceatlas2;
+pub mod placement;
+pub mod radial;
+pub mod simulation;
 
 pub use barnes_hut::BarnesHutTree;
-pub use force::{ForceLayout, ForceParams};
+pub use community::community_layout;
+pub use force::{DEFAULT_MAX_DISPLACEMENT, ForceLayout, ForceParams};
+pub use force_directed::ForceDirectedLayout;
+pub use forceatlas2::{ForceAtlas2, ForceAtlas2Params};
+pub use placement::{circular, fit_to_bounds, grid, seed_positions};
+pub use radial::radial_tree;
+pub use simulation::{LayoutAlgorithm, SETTLE_THRESHOLD_PER_NODE, Simulation};
@@ -0,0 +1,218 @@
+use crate::graph::Graph;
+use crate::rng::SplitMix64;
+use crate::spatial::AABB;
+
+/// Scatters nodes uniformly over a `width x height` box centred on the
+/// origin. The same seed always gives the same positions, on every target,
+/// so layouts started from it are reproducible.
+pub fn seed_positions(graph: &mut Graph, seed: u64, width: f32, height: f32) {
+    let mut rng = SplitMix64(seed);
+    for node in graph.nodes_mut() {
+        node.x = (rng.next_unit() - 0.5) * width;
+        node.y = (rng.next_unit() - 0.5) * height;
+    }
+}
+
+/// Places nodes evenly around a circle in node order, starting at angle 0
+/// (the positive x axis) and going counter-clockwise.
+pub fn circular(graph: &mut Graph, radius: f32, center: (f32, f32)) {
+    let n = graph.node_count();
+    for (i, node) in graph.nodes_mut().iter_mut().enumerate() {
+        let angle = i as f32 * std::f32::consts::TAU / n as f32;
+        node.x = center.0 + radius * angle.cos();
+        node.y = center.1 + radius * angle.sin();
+    }
+}
+
+/// Lays nodes out row by row, `cols` per row and `spacing` apart, with the
+/// grid centred on the origin. `cols == 0` picks a square-ish grid of
+/// `ceil(sqrt(n))` columns.
+pub fn grid(graph: &mut Graph, cols: usize, spacing: f32) {
+    let n = graph.node_count();
+    if n == 0 {
+        return;
+    }
+    let cols = if cols == 0 {
+        (n as f32).sqrt().ceil() as usize
+    } else {
+        cols
+    };
+    let rows = n.div_ceil(cols);
+    let offset_x = (cols.min(n) - 1) as f32 * spacing / 2.0;
+    let offset_y = (rows - 1) as f32 * spacing / 2.0;
+    for (i, node) in graph.nodes_mut().iter_mut().enumerate() {
+        node.x = (i % cols) as f32 * spacing - offset_x;
+        node.y = (i / cols) as f32 * spacing - offset_y;
+    }
+}
+
+/// Rescales and translates positions to fill `[padding, width - padding] x
+/// [padding, height - padding]`, preserving aspect ratio and centring the
+/// shorter axis. A layout with no extent (one node, or all coincident)
+/// collapses to the centre of the canvas instead of dividing by zero.
+pub fn fit_to_bounds(graph: &mut Graph, width: f32, height: f32, padding: f32) {
+    let nodes = graph.nodes_mut();
+    let mut extent = AABB::empty();
+    for node in nodes.iter() {
+        extent.expand_to_include(node.x, node.y);
+    }
+    if extent.is_empty() {
+        return;
+    }
+
+    let avail_w = (width - 2.0 * padding).max(0.0);
+    let avail_h = (height - 2.0 * padding).max(0.0);
+    let (extent_w, extent_h) = extent.dimensions();
+    let scale = match (extent_w > 0.0, extent_h > 0.0) {
+        (true, true) => (avail_w / extent_w).min(avail_h / extent_h),
+        (true, false) => avail_w / extent_w,
+        (false, true) => avail_h / extent_h,
+        (false, false) => 0.0,
+    };
+
+    // Centre the scaled layout in the canvas
+    let (mid_x, mid_y) = extent.center();
+    let (canvas_x, canvas_y) = (width / 2.0, height / 2.0);
+    for node in nodes.iter_mut() {
+        node.x = canvas_x + (node.x - mid_x) * scale;
+        node.y = canvas_y + (node.y - mid_y) * scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+
+    fn make_graph(n: u32) -> Graph {
+        let nodes = (0..n)
+            .map(|id| Node {
+                id,
+                ..Node::default()
+            })
+            .collect();
+        Graph::new(nodes, vec![])
+    }
+
+    fn position_bits(graph: &Graph) -> Vec<(u32, u32)> {
+        graph
+            .nodes()
+            .iter()
+            .map(|n| (n.x.to_bits(), n.y.to_bits()))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_positions() {
+        let mut a = make_graph(50);
+        let mut b = make_graph(50);
+        seed_positions(&mut a, 7, 100.0, 80.0);
+        seed_positions(&mut b, 7, 100.0, 80.0);
+        assert_eq!(position_bits(&a), position_bits(&b));
+
+        seed_positions(&mut b, 8, 100.0, 80.0);
+        assert_ne!(position_bits(&a), position_bits(&b));
+    }
+
+    #[test]
+    fn positions_fill_the_box() {
+        let mut g = make_graph(200);
+        seed_positions(&mut g, 0, 100.0, 80.0);
+        let nodes = g.nodes();
+        assert!(
+            nodes
+                .iter()
+                .all(|n| (-50.0..50.0).contains(&n.x) && (-40.0..40.0).contains(&n.y))
+        );
+        // Not collapsed onto a line or a corner
+        assert!(nodes.iter().any(|n| n.x < -25.0) && nodes.iter().any(|n| n.x > 25.0));
+        assert!(nodes.iter().any(|n| n.y < -20.0) && nodes.iter().any(|n| n.y > 20.0));
+    }
+
+    #[test]
+    fn circular_positions() {
+        let mut g = make_graph(4);
+        circular(&mut g, 10.0, (5.0, -5.0));
+        let expected = [(15.0, -5.0), (5.0, 5.0), (-5.0, -5.0), (5.0, -15.0)];
+        for (node, (x, y)) in g.nodes().iter().zip(expected) {
+            assert!(
+                (node.x - x).abs() < 1e-4 && (node.y - y).abs() < 1e-4,
+                "{node:?}"
+            );
+        }
+
+        let mut single = make_graph(1);
+        circular(&mut single, 3.0, (0.0, 0.0));
+        assert_eq!((single.nodes()[0].x, single.nodes()[0].y), (3.0, 0.0));
+        circular(&mut make_graph(0), 3.0, (0.0, 0.0));
+    }
+
+    #[test]
+    fn grid_positions() {
+        // Five nodes, three columns: two rows, centred on the origin
+        let mut g = make_graph(5);
+        grid(&mut g, 3, 10.0);
+        let positions: Vec<(f32, f32)> = g.nodes().iter().map(|n| (n.x, n.y)).collect();
+        assert_eq!(
+            positions,
+            vec![
+                (-10.0, -5.0),
+                (0.0, -5.0),
+                (10.0, -5.0),
+                (-10.0, 5.0),
+                (0.0, 5.0)
+            ]
+        );
+
+        // Automatic columns: 9 nodes make a 3x3 grid
+        let mut square = make_graph(9);
+        grid(&mut square, 0, 1.0);
+        assert_eq!((square.nodes()[8].x, square.nodes()[8].y), (1.0, 1.0));
+        grid(&mut make_graph(0), 4, 1.0);
+    }
+
+    #[test]
+    fn fit_to_bounds_fills_canvas() {
+        let mut g = make_graph(30);
+        seed_positions(&mut g, 4, 5000.0, 300.0);
+        fit_to_bounds(&mut g, 800.0, 600.0, 20.0);
+
+        let nodes = g.nodes();
+        let eps = 1e-3;
+        assert!(nodes.iter().all(|n| {
+            (20.0 - eps..=780.0 + eps).contains(&n.x) && (20.0 - eps..=580.0 + eps).contains(&n.y)
+        }));
+        // The wide axis spans the full padded width
+        let min_x = nodes.iter().map(|n| n.x).fold(f32::MAX, f32::min);
+        let max_x = nodes.iter().map(|n| n.x).fold(f32::MIN, f32::max);
+        assert!((min_x - 20.0).abs() < 1e-2 && (max_x - 780.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn fit_to_bounds_preserves_aspect_ratio() {
+        let mut g = make_graph(3);
+        let positions = [(0.0, 0.0), (10.0, 0.0), (0.0, 5.0)];
+        for (n, (x, y)) in g.nodes_mut().iter_mut().zip(positions) {
+            n.x = x;
+            n.y = y;
+        }
+        fit_to_bounds(&mut g, 100.0, 100.0, 0.0);
+        let n = g.nodes();
+        // 10 wide by 5 tall scales by 10 and is centred vertically
+        assert_eq!((n[0].x, n[0].y), (0.0, 25.0));
+        assert_eq!((n[1].x, n[1].y), (100.0, 25.0));
+        assert_eq!((n[2].x, n[2].y), (0.0, 75.0));
+    }
+
+    #[test]
+    fn fit_to_bounds_degenerate() {
+        let mut g = make_graph(3);
+        for n in g.nodes_mut() {
+            n.x = 42.0;
+            n.y = -7.0;
+        }
+        fit_to_bounds(&mut g, 200.0, 100.0, 10.0);
+        assert!(g.nodes().iter().all(|n| n.x == 100.0 && n.y == 50.0));
+        fit_to_bounds(&mut make_graph(0), 200.0, 100.0, 10.0);
+    }
+}
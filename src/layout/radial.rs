@@ -0,0 +1,187 @@
+use crate::graph::Graph;
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+
+/// Distance between consecutive depth rings.
+const RING_SPACING: f32 = 50.0;
+
+/// Radial tree layout: the root at the origin and every other node on a
+/// ring whose radius grows with its hop distance from the root.
+///
+/// Builds a BFS tree from `root_id` over the undirected view of the graph,
+/// visiting each node once, so cycles and cross edges are simply not part
+/// of the tree; they still draw, just without shaping the layout. Each
+/// node gets an angular wedge of its parent's, proportional to the number
+/// of leaves below it, and sits in the middle of its wedge, so subtrees
+/// never overlap. Nodes the root can't reach are spread around one more
+/// ring outside the tree. An unknown root leaves the graph untouched.
+pub fn radial_tree(graph: &mut Graph, root_id: u32) {
+    let Some(root) = graph.node_index(root_id) else {
+        return;
+    };
+    let n = graph.node_count();
+
+    // BFS tree, with `order` listing nodes as they were reached
+    let mut depth: Vec<Option<usize>> = vec![None; n];
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut order = Vec::with_capacity(n);
+    let mut queue = VecDeque::from([root]);
+    depth[root] = Some(0);
+    while let Some(i) = queue.pop_front() {
+        order.push(i);
+        let next = depth[i].map(|d| d + 1);
+        for &j in graph.neighbor_indices(i) {
+            if depth[j].is_none() {
+                depth[j] = next;
+                children[i].push(j);
+                queue.push_back(j);
+            }
+        }
+    }
+
+    // Leaves under each node, children before parents
+    let mut leaves = vec![1usize; n];
+    for &i in order.iter().rev() {
+        if !children[i].is_empty() {
+            leaves[i] = children[i].iter().map(|&c| leaves[c]).sum();
+        }
+    }
+
+    // Angular wedge start and width per node, handed down the tree
+    let mut wedge = vec![(0.0f32, TAU); n];
+    let nodes = graph.nodes_mut();
+    for &i in &order {
+        let (start, width) = wedge[i];
+        let d = depth[i].unwrap_or(0);
+        let (x, y) = if d == 0 {
+            (0.0, 0.0)
+        } else {
+            let angle = start + width / 2.0;
+            let r = d as f32 * RING_SPACING;
+            (r * angle.cos(), r * angle.sin())
+        };
+        nodes[i].x = x;
+        nodes[i].y = y;
+
+        let mut offset = start;
+        for &c in &children[i] {
+            let share = width * leaves[c] as f32 / leaves[i] as f32;
+            wedge[c] = (offset, share);
+            offset += share;
+        }
+    }
+
+    let unreached: Vec<usize> = (0..n).filter(|&i| depth[i].is_none()).collect();
+    let outer = (depth.iter().flatten().max().unwrap_or(&0) + 1) as f32 * RING_SPACING;
+    for (k, &i) in unreached.iter().enumerate() {
+        let angle = k as f32 * TAU / unreached.len() as f32;
+        nodes[i].x = outer * angle.cos();
+        nodes[i].y = outer * angle.sin();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+    use crate::test_utils::edge;
+
+    fn make_node(id: u32) -> Node {
+        Node {
+            id,
+            x: 7.0,
+            y: 7.0,
+            ..Node::default()
+        }
+    }
+
+    /// Balanced binary tree on ids `0..2^levels - 1`; node `i` has
+    /// children `2i + 1` and `2i + 2`.
+    fn binary_tree(levels: u32) -> Graph {
+        let n = (1 << levels) - 1;
+        let nodes = (0..n).map(make_node).collect();
+        let edges = (1..n).map(|i| edge((i - 1) / 2, i)).collect();
+        Graph::new(nodes, edges)
+    }
+
+    fn radius(node: &Node) -> f32 {
+        node.x.hypot(node.y)
+    }
+
+    /// Depth of node `i` in `binary_tree`.
+    fn tree_depth(i: u32) -> u32 {
+        (i + 1).ilog2()
+    }
+
+    #[test]
+    fn binary_tree_rings_grow_with_depth() {
+        let mut g = binary_tree(5);
+        radial_tree(&mut g, 0);
+        let nodes = g.nodes();
+        assert_eq!((nodes[0].x, nodes[0].y), (0.0, 0.0));
+        for a in nodes {
+            for b in nodes {
+                let (da, db) = (tree_depth(a.id), tree_depth(b.id));
+                if da < db {
+                    assert!(radius(a) < radius(b), "{a:?} vs {b:?}");
+                } else if da == db {
+                    assert!((radius(a) - radius(b)).abs() < 1e-3);
+                    // Subtrees get disjoint wedges, so no two nodes meet
+                    if a.id != b.id {
+                        assert!((a.x - b.x).hypot(a.y - b.y) > 1.0, "{a:?} {b:?}");
+                    }
+                }
+            }
+        }
+        // Equal subtrees split the circle evenly: the root's children
+        // sit opposite each other
+        let (l, r) = (&nodes[1], &nodes[2]);
+        assert!((l.x + r.x).abs() < 1e-3 && (l.y + r.y).abs() < 1e-3);
+    }
+
+    #[test]
+    fn wedges_follow_subtree_size() {
+        // Root 0 with a lone leaf 1 and a child 2 carrying three leaves
+        let nodes = (0..6).map(make_node).collect();
+        let edges = vec![edge(0, 1), edge(0, 2), edge(2, 3), edge(2, 4), edge(2, 5)];
+        let mut g = Graph::new(nodes, edges);
+        radial_tree(&mut g, 0);
+        let angle = |n: &Node| n.y.atan2(n.x).rem_euclid(TAU);
+        let nodes = g.nodes();
+        // Node 1 owns a quarter of the circle, node 2 the rest
+        assert!((angle(&nodes[1]) - TAU / 8.0).abs() < 1e-4);
+        assert!((angle(&nodes[2]) - (TAU / 4.0 + 3.0 * TAU / 8.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn cycles_and_unreachable_nodes() {
+        // A square with a diagonal, and an island pair 4-5
+        let nodes = (0..6).map(make_node).collect();
+        let edges = vec![
+            edge(0, 1),
+            edge(1, 2),
+            edge(2, 3),
+            edge(3, 0),
+            edge(0, 2),
+            edge(4, 5),
+        ];
+        let mut g = Graph::new(nodes, edges);
+        radial_tree(&mut g, 0);
+        let nodes = g.nodes();
+        assert!(nodes.iter().all(|n| n.x.is_finite() && n.y.is_finite()));
+        // 1, 2 and 3 are all one hop out; the cycle doesn't push any deeper
+        for n in &nodes[1..4] {
+            assert!((radius(n) - RING_SPACING).abs() < 1e-3);
+        }
+        // The island lands on the next ring, not on the tree
+        for n in &nodes[4..] {
+            assert!((radius(n) - 2.0 * RING_SPACING).abs() < 1e-3);
+        }
+        assert!((nodes[4].x - nodes[5].x).hypot(nodes[4].y - nodes[5].y) > 1.0);
+
+        // An unknown root changes nothing
+        let mut untouched = binary_tree(2);
+        radial_tree(&mut untouched, 99);
+        assert!(untouched.nodes().iter().all(|n| (n.x, n.y) == (7.0, 7.0)));
+    }
+}
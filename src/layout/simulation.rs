@@ -0,0 +1,166 @@
+use crate::graph::Graph;
+use crate::layout::placement::seed_positions;
+use crate::layout::{ForceAtlas2, ForceDirectedLayout, ForceLayout};
+use crate::protocol::{FlagSet, Flags};
+
+/// A layout counts as settled once the average node moves less than this
+/// many world units per step.
+pub const SETTLE_THRESHOLD_PER_NODE: f32 = 0.01;
+
+/// The layout a `Simulation` runs.
+pub enum LayoutAlgorithm {
+    Force(ForceLayout),
+    ForceDirected(ForceDirectedLayout),
+    ForceAtlas2(ForceAtlas2),
+}
+
+impl LayoutAlgorithm {
+    fn step(&mut self, graph: &mut Graph) -> f32 {
+        match self {
+            Self::Force(layout) => layout.step(graph),
+            Self::ForceDirected(layout) => layout.step(graph),
+            Self::ForceAtlas2(layout) => layout.step(graph),
+        }
+    }
+
+    fn is_settled(&self, threshold: f32) -> bool {
+        match self {
+            Self::Force(layout) => layout.is_settled(threshold),
+            Self::ForceDirected(layout) => layout.is_settled(threshold),
+            Self::ForceAtlas2(layout) => layout.is_settled(threshold),
+        }
+    }
+}
+
+/// A graph and the layout moving it, with no canvas or browser involved,
+/// for laying graphs out ahead of time and baking the positions into a
+/// `.bloom` file.
+pub struct Simulation {
+    graph: Graph,
+    algorithm: LayoutAlgorithm,
+    steps: usize,
+}
+
+impl Simulation {
+    /// Starts from the graph's current positions; call `seed` first if
+    /// they are all zero, as in a file without `HasPositions`.
+    pub fn new(graph: Graph, algorithm: LayoutAlgorithm) -> Self {
+        Self {
+            graph,
+            algorithm,
+            steps: 0,
+        }
+    }
+
+    /// Scatters nodes the way the engine does on load: uniformly over a
+    /// square of side `20 * sqrt(n)`, reproducibly for a given seed.
+    pub fn seed(&mut self, seed: u64) {
+        let side = (self.graph.node_count() as f32).sqrt() * 20.0;
+        seed_positions(&mut self.graph, seed, side, side);
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn into_graph(self) -> Graph {
+        self.graph
+    }
+
+    /// Steps taken so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Runs one layout step and returns the total distance nodes moved.
+    pub fn step(&mut self) -> f32 {
+        self.steps += 1;
+        self.algorithm.step(&mut self.graph)
+    }
+
+    /// Whether the last step moved the average node less than
+    /// `SETTLE_THRESHOLD_PER_NODE`. An empty graph is always settled.
+    pub fn is_settled(&self) -> bool {
+        let n = self.graph.node_count();
+        n == 0
+            || self
+                .algorithm
+                .is_settled(SETTLE_THRESHOLD_PER_NODE * n as f32)
+    }
+
+    /// Steps until the layout settles or `max_steps` more steps have run,
+    /// and returns whether it settled.
+    pub fn run(&mut self, max_steps: usize) -> bool {
+        for _ in 0..max_steps {
+            if self.is_settled() {
+                break;
+            }
+            self.step();
+        }
+        self.is_settled()
+    }
+
+    /// Encodes the laid-out graph. `HasPositions` is always set, on top of
+    /// whatever `flags` asks for, so the positions survive the round trip.
+    pub fn to_bloom_bytes(&self, flags: impl Into<FlagSet>) -> Vec<u8> {
+        self.graph
+            .to_bloom_bytes(flags.into() | Flags::HasPositions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{ForceAtlas2Params, ForceParams};
+    use crate::test_utils::build_blom;
+
+    fn loaded(algorithm: LayoutAlgorithm) -> Simulation {
+        let graph = Graph::from_bloom_bytes(&build_blom(
+            &[(1, 0.25, 2), (2, 0.25, 2), (3, 0.25, 2), (4, 0.25, 2)],
+            &[(1, 2), (2, 3), (3, 4), (4, 1)],
+            None,
+        ))
+        .unwrap();
+        let mut sim = Simulation::new(graph, algorithm);
+        sim.seed(42);
+        sim
+    }
+
+    #[test]
+    fn each_algorithm_settles() {
+        let algorithms = [
+            LayoutAlgorithm::Force(ForceLayout::new(4, ForceParams::default())),
+            LayoutAlgorithm::ForceDirected(ForceDirectedLayout::new(100.0, 100.0)),
+            LayoutAlgorithm::ForceAtlas2(ForceAtlas2::new(ForceAtlas2Params::default())),
+        ];
+        for algorithm in algorithms {
+            let mut sim = loaded(algorithm);
+            assert!(!sim.is_settled());
+            assert!(sim.run(5000));
+            assert!(sim.steps() > 0);
+            assert!(
+                sim.graph()
+                    .nodes()
+                    .iter()
+                    .all(|n| n.x.is_finite() && n.y.is_finite())
+            );
+        }
+    }
+
+    #[test]
+    fn run_stops_at_max_steps() {
+        let mut sim = loaded(LayoutAlgorithm::Force(ForceLayout::new(
+            4,
+            ForceParams::default(),
+        )));
+        assert!(!sim.run(3));
+        assert_eq!(sim.steps(), 3);
+        // An empty graph is settled before its first step
+        let mut empty = Simulation::new(
+            Graph::new(vec![], vec![]),
+            LayoutAlgorithm::ForceDirected(ForceDirectedLayout::new(10.0, 10.0)),
+        );
+        assert!(empty.run(10));
+        assert_eq!(empty.steps(), 0);
+    }
+}
@@ -5,6 +5,7 @@ pub mod graph;
 pub mod layout;
 pub mod protocol;
 pub mod render;
+mod rng;
 pub mod spatial;
 
 #[cfg(test)]
@@ -13,6 +14,9 @@ pub mod test_utils;
 #[wasm_bindgen]
 pub struct BloomEngine {
     inner: engine::BloomEngine,
+    renderer: render::Canvas2d,
+    /// Clock reading at the previous `tick`, for camera smoothing.
+    last_tick_ms: Option<f64>,
 }
 
 #[wasm_bindgen]
@@ -23,30 +27,235 @@ impl BloomEngine {
         log::info!("Bloom engine initializing");
         let width = canvas.width() as f32;
         let height = canvas.height() as f32;
+        let renderer = render::Canvas2d::new(canvas).map_err(|e| JsValue::from_str(&e))?;
         Ok(BloomEngine {
             inner: engine::BloomEngine::new(width, height),
+            renderer,
+            last_tick_ms: None,
         })
     }
 
+    /// Decodes a BLOM buffer and replaces the current graph. Decode errors
+    /// reject with the `DecodeError` message.
     pub fn load_graph(&mut self, data: &[u8]) -> Result<(), JsValue> {
-        self.inner
-            .load_graph(data)
+        self.inner.load_graph(data).map_err(JsValue::from)?;
+        log::info!(
+            "Loaded graph: {} nodes, {} edges",
+            self.inner.node_count(),
+            self.inner.edge_count()
+        );
+        Ok(())
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.inner.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.inner.edge_count()
+    }
+
+    /// Every node's position as a `Float32Array` of `[x0, y0, x1, y1, ...]`
+    /// in node order, for reading the whole layout in one call.
+    pub fn positions(&self) -> Vec<f32> {
+        self.inner.positions()
+    }
+
+    /// Node ids as a `Uint32Array`, in the same order as `positions`.
+    pub fn node_ids(&self) -> Vec<u32> {
+        self.inner.node_ids()
+    }
+
+    /// Advances the layout for up to `max_ms` of wall time and eases the
+    /// camera by the time since the previous call. Returns true once the
+    /// layout has settled; keep calling (e.g. from `requestAnimationFrame`)
+    /// while it returns false, or while a camera move is in progress.
+    pub fn tick(&mut self, max_ms: f64) -> bool {
+        let performance = web_sys::window().and_then(|w| w.performance());
+        let now = || {
+            performance
+                .as_ref()
+                .map_or_else(js_sys::Date::now, |p| p.now())
+        };
+
+        let start = now();
+        let dt = self
+            .last_tick_ms
+            .map_or(0.0, |last| (start - last) / 1000.0);
+        self.last_tick_ms = Some(start);
+        self.inner.update_camera(dt as f32);
+        self.inner.run_layout(max_ms, now)
+    }
+
+    /// Clears the canvas and draws the graph at its current positions:
+    /// edges as lines, then nodes as circles sized by PageRank. Before a
+    /// graph is loaded this just clears.
+    pub fn render(&self) -> Result<(), JsValue> {
+        self.renderer
+            .draw(&self.inner.frame())
             .map_err(|e| JsValue::from_str(&e))
     }
 
-    pub fn tick(&mut self, dt: f32) {
-        self.inner.tick(dt);
+    /// Drags the view by `(dx, dy)` canvas pixels.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.inner.pan(dx, dy);
+    }
+
+    /// Zooms by `factor` (e.g. 1.1 per wheel notch in, 1/1.1 out) keeping
+    /// the point under the cursor stationary.
+    pub fn zoom_at(&mut self, screen_x: f32, screen_y: f32, factor: f32) {
+        self.inner.zoom_at(screen_x, screen_y, factor);
+    }
+
+    pub fn reset_view(&mut self) {
+        self.inner.reset_view();
     }
 
     pub fn resize(&mut self, width: f32, height: f32) {
         self.inner.resize(width, height);
     }
 
-    pub fn hover(&self, screen_x: f32, screen_y: f32) -> Option<u32> {
+    /// Id of the node drawn under the screen point (canvas pixels), or
+    /// `undefined` when the point misses every node.
+    pub fn node_at(&self, screen_x: f32, screen_y: f32) -> Option<u32> {
         self.inner.node_at(screen_x, screen_y).map(|n| n.id)
     }
 
+    pub fn hover(&self, screen_x: f32, screen_y: f32) -> Option<u32> {
+        self.node_at(screen_x, screen_y)
+    }
+
+    /// `[source, target]` ids of the edge drawn nearest the screen point,
+    /// or `undefined` if none passes within `tolerance` pixels.
+    pub fn edge_at(&self, screen_x: f32, screen_y: f32, tolerance: f32) -> Option<Vec<u32>> {
+        self.inner
+            .edge_at(screen_x, screen_y, tolerance)
+            .map(|(source, target)| vec![source, target])
+    }
+
     pub fn focus_node(&mut self, node_id: u32) {
         self.inner.focus_node(node_id);
     }
+
+    /// Dims everything but node `node_id`, its neighbours and its edges,
+    /// e.g. while the node is hovered.
+    pub fn highlight_node(&mut self, node_id: u32) {
+        self.inner.highlight_node(node_id);
+    }
+
+    pub fn clear_highlight(&mut self) {
+        self.inner.clear_highlight();
+    }
+
+    /// Runs PageRank and resizes nodes to match. Returns a `Float32Array`
+    /// of scores in load order; empty before a graph is loaded.
+    pub fn compute_pagerank(&mut self, iterations: usize, damping: f32) -> Vec<f32> {
+        self.inner.compute_pagerank(iterations, damping)
+    }
+
+    /// Louvain community id per node, in load order, as a `Uint32Array`.
+    pub fn compute_communities(&mut self) -> Vec<usize> {
+        self.inner.compute_communities()
+    }
+
+    /// Draws (and hit-tests) every node at `radius` world units.
+    pub fn size_nodes_fixed(&mut self, radius: f32) {
+        self.inner
+            .set_node_sizing(render::NodeSizing::Fixed(radius));
+    }
+
+    /// Scales node radius with degree between `min` and `max`: linearly,
+    /// or logarithmically when `log` is true, so hubs don't dwarf the rest.
+    pub fn size_nodes_by_degree(&mut self, min: f32, max: f32, log: Option<bool>) {
+        self.inner.set_node_sizing(render::NodeSizing::ByDegree {
+            min,
+            max,
+            log: log.unwrap_or(false),
+        });
+    }
+
+    /// Scales node radius with PageRank between `min` and `max` (the
+    /// default, 3 to 12, linear). `log` as for `size_nodes_by_degree`.
+    pub fn size_nodes_by_pagerank(&mut self, min: f32, max: f32, log: Option<bool>) {
+        self.inner.set_node_sizing(render::NodeSizing::ByPageRank {
+            min,
+            max,
+            log: log.unwrap_or(false),
+        });
+    }
+
+    /// Draws edges as curves (so reciprocal edges don't overlap) and/or
+    /// with arrowheads at the target end. Arrows need a directed graph.
+    pub fn set_edge_style(&mut self, curved: bool, arrows: bool) {
+        let style = render::EdgeStyle {
+            curved,
+            arrows,
+            ..self.inner.edge_style()
+        };
+        self.inner.set_edge_style(style);
+    }
+
+    /// Encodes edge weight as opacity and stroke width: the lightest edge
+    /// gets `min_alpha`/`min_width`, the heaviest `max_alpha`/`max_width`.
+    /// Pass equal ends to map only one of them. Graphs whose edges all
+    /// weigh the same keep the default look.
+    pub fn map_edge_weights(
+        &mut self,
+        min_alpha: f64,
+        max_alpha: f64,
+        min_width: f64,
+        max_width: f64,
+    ) {
+        self.inner
+            .map_edge_weights((min_alpha, max_alpha), (min_width, max_width));
+    }
+
+    /// Draws every edge the same again.
+    pub fn clear_edge_weight_mapping(&mut self) {
+        self.inner.clear_edge_weight_mapping();
+    }
+
+    /// How many animation frames hit-testing may lag behind a moving
+    /// layout (default 4) before its spatial index is rebuilt; it also
+    /// catches up early after large moves and whenever the layout settles.
+    /// 1 rebuilds every frame.
+    pub fn set_spatial_rebuild_interval(&mut self, frames: u32) {
+        self.inner.set_spatial_rebuild_interval(frames);
+    }
+
+    /// Furthest any node may move in one layout step, in world units
+    /// (default 200). Lower it if a dense graph's layout flies apart.
+    /// Negative and NaN values count as 0, freezing the layout.
+    pub fn set_max_displacement(&mut self, px: f32) {
+        self.inner.set_max_displacement(px);
+    }
+
+    /// Minimum on-screen node radius, in pixels, for a label to be drawn
+    /// (default 8). Labels go to the highest-ranked qualifying nodes first.
+    pub fn set_label_threshold(&mut self, px: f64) {
+        self.inner.set_label_threshold(px);
+    }
+
+    /// Node/edge counts, density, degree and connectivity summary for the
+    /// loaded graph, or `undefined` before a load.
+    pub fn stats(&self) -> Option<graph::algorithms::GraphStats> {
+        self.inner.stats()
+    }
+
+    /// Colours nodes along a viridis gradient by PageRank (the default).
+    pub fn color_by_pagerank(&mut self) {
+        self.inner.set_node_coloring(render::NodeColoring::PageRank);
+    }
+
+    /// Colours nodes by Louvain community, computing it if needed.
+    pub fn color_by_community(&mut self) {
+        self.inner
+            .set_node_coloring(render::NodeColoring::Community);
+    }
+
+    /// Node ids along the shortest path, source and target included, or
+    /// `undefined` when there is no path.
+    pub fn compute_shortest_path(&self, source_id: u32, target_id: u32) -> Option<Vec<u32>> {
+        self.inner.compute_shortest_path(source_id, target_id)
+    }
 }
@@ -1,54 +1,375 @@
-use super::format::{HEADER_SIZE, Header};
+use super::checksum::crc32;
+use super::error::DecodeError;
+use super::format::{Flags, Header, NO_COMMUNITY};
 use crate::graph::types::{Edge, Graph, Node};
+use miniz_oxide::inflate::{TINFLStatus, decompress_to_vec_zlib_with_limit};
+use std::ops::Range;
 
 pub struct Decoder<'a> {
     data: &'a [u8],
     offset: usize,
+    sections: Option<Sections>,
+}
+
+/// Absolute byte offsets of each array in an uncompressed BLOM buffer, used by
+/// the chunked API to seek straight to any node or edge window.
+#[derive(Debug, Clone, Copy)]
+struct Sections {
+    header: Header,
+    string_total_len: usize,
+    string_offsets: usize,
+    string_data: usize,
+    ids: usize,
+    pageranks: usize,
+    degrees: usize,
+    communities: usize,
+    xs: usize,
+    ys: usize,
+    sources: usize,
+    targets: usize,
+    weights: usize,
 }
 
 impl<'a> Decoder<'a> {
     pub fn new(data: &'a [u8]) -> Self {
-        Self { data, offset: 0 }
+        Self {
+            data,
+            offset: 0,
+            sections: None,
+        }
+    }
+
+    /// Decodes the graph and rejects edges whose endpoints are not in the
+    /// node table.
+    pub fn decode_graph(&mut self) -> Result<Graph, DecodeError> {
+        self.decode_graph_with_progress(|_| {})
+    }
+
+    /// `decode_graph`, reporting progress as the fraction (0.0 to 1.0) of
+    /// the body read so far: once after the string table, once after the
+    /// node arrays, and 1.0 after the edge arrays. Values never decrease.
+    /// For compressed files the fraction is of the inflated body.
+    pub fn decode_graph_with_progress(
+        &mut self,
+        mut on_progress: impl FnMut(f32),
+    ) -> Result<Graph, DecodeError> {
+        let graph = self.decode_unchecked_with_progress(&mut on_progress)?;
+        validate_edges(&graph)?;
+        Ok(graph)
     }
 
-    pub fn decode_graph(&mut self) -> Result<Graph, String> {
+    /// Decodes the graph without checking edge endpoints. Dangling edges are
+    /// kept as-is and skipped by lookups that go through `node_index`.
+    pub fn decode_graph_unchecked(&mut self) -> Result<Graph, DecodeError> {
+        self.decode_unchecked_with_progress(&mut |_| {})
+    }
+
+    fn decode_unchecked_with_progress(
+        &mut self,
+        on_progress: &mut dyn FnMut(f32),
+    ) -> Result<Graph, DecodeError> {
         let header = Header::parse(self.data)?;
-        self.offset = HEADER_SIZE;
+        self.verify_checksum(&header)?;
+        self.offset = header.size();
+
+        if header.has_flag(Flags::Compressed) {
+            // Only the body is deflated; the header stays readable as-is.
+            let body = inflate_body(&header, &self.data[header.size()..])?;
+            self.offset = self.data.len();
+            return Decoder::new(&body).decode_body(&header, on_progress);
+        }
 
-        let labels = if header.has_flag(super::format::Flags::HasLabels) {
+        self.decode_body(&header, on_progress)
+    }
+
+    fn decode_body(
+        &mut self,
+        header: &Header,
+        on_progress: &mut dyn FnMut(f32),
+    ) -> Result<Graph, DecodeError> {
+        // Compressed bodies were only bounded before inflating; this is
+        // the exact check for them
+        header.check_counts(self.data.len().saturating_sub(self.offset))?;
+        let labels = if header.has_flag(Flags::HasLabels) {
             self.decode_string_table(header.node_count as usize)?
         } else {
             vec![String::new(); header.node_count as usize]
         };
+        on_progress(self.progress());
 
         let (ids, pageranks, degrees) = self.decode_node_data(header.node_count as usize)?;
+        let communities = if header.has_communities() {
+            self.read_u32_array(header.node_count as usize)?
+                .into_iter()
+                .map(community_from_wire)
+                .collect()
+        } else {
+            vec![None; header.node_count as usize]
+        };
+        let (xs, ys) = if header.has_positions() {
+            let xs = self.read_f32_array(header.node_count as usize)?;
+            let ys = self.read_f32_array(header.node_count as usize)?;
+            (xs, ys)
+        } else {
+            let zeros = vec![0.0; header.node_count as usize];
+            (zeros.clone(), zeros)
+        };
+        on_progress(self.progress());
         let (sources, targets) = self.decode_edge_data(header.edge_count as usize)?;
+        let weights = if header.has_flag(Flags::HasWeights) {
+            self.read_f32_array(header.edge_count as usize)?
+        } else {
+            vec![1.0; header.edge_count as usize]
+        };
+        on_progress(1.0);
 
         let nodes = ids
             .into_iter()
             .zip(labels)
             .zip(pageranks)
             .zip(degrees)
-            .map(|(((id, label), pagerank), degree)| Node {
-                id,
-                label,
-                pagerank,
-                degree,
-                x: 0.0,
-                y: 0.0,
-            })
+            .zip(communities)
+            .zip(xs.into_iter().zip(ys))
+            .map(
+                |(((((id, label), pagerank), degree), community), (x, y))| Node {
+                    id,
+                    label,
+                    pagerank,
+                    degree,
+                    x,
+                    y,
+                    community,
+                    attributes: Default::default(),
+                },
+            )
             .collect();
 
         let edges = sources
             .into_iter()
             .zip(targets)
-            .map(|(source, target)| Edge { source, target })
+            .zip(weights)
+            .map(|((source, target), weight)| Edge {
+                source,
+                target,
+                weight,
+            })
             .collect();
 
         Ok(Graph::new(nodes, edges))
     }
 
-    fn decode_string_table(&mut self, count: usize) -> Result<Vec<String>, String> {
+    /// Reads only the header, e.g. to show node/edge counts or
+    /// reject oversized files before allocating. Does not advance the
+    /// cursor, so a later `decode_graph` starts from the beginning as usual.
+    /// The body checksum is not verified here.
+    pub fn peek_header(&self) -> Result<Header, DecodeError> {
+        Header::parse(self.data)
+    }
+
+    /// Parses the header and locates every section so that
+    /// `decode_nodes_into` / `decode_edges_into` can read arbitrary windows.
+    /// Compressed payloads can't be seeked into and are rejected.
+    pub fn decode_header(&mut self) -> Result<Header, DecodeError> {
+        let header = Header::parse(self.data)?;
+        if header.has_flag(Flags::Compressed) {
+            return Err(DecodeError::CompressedChunks);
+        }
+        self.verify_checksum(&header)?;
+        header.check_counts(self.data.len() - header.size())?;
+
+        let node_count = header.node_count as usize;
+        let edge_count = header.edge_count as usize;
+
+        let body = header.size();
+        self.offset = body;
+        let (string_total_len, string_offsets, string_data) = if header.has_flag(Flags::HasLabels) {
+            let total_len = self.read_u32()? as usize;
+            let offsets = self.offset;
+            (total_len, offsets, offsets + node_count * 4)
+        } else {
+            (0, body, body)
+        };
+
+        // The string length is the one wire value in the layout math; every
+        // other term is bounded by `check_counts`
+        let ids = string_data
+            .checked_add(string_total_len)
+            .filter(|&ids| ids <= self.data.len())
+            .ok_or(DecodeError::UnexpectedEof {
+                offset: string_data,
+            })?;
+        let pageranks = ids + node_count * 4;
+        let degrees = pageranks + node_count * 4;
+        let communities = degrees + node_count * 2;
+        let xs = if header.has_communities() {
+            communities + node_count * 4
+        } else {
+            communities
+        };
+        let (ys, sources) = if header.has_positions() {
+            (xs + node_count * 4, xs + node_count * 8)
+        } else {
+            (xs, xs)
+        };
+        let targets = sources + edge_count * 4;
+        let weights = targets + edge_count * 4;
+
+        self.sections = Some(Sections {
+            header,
+            string_total_len,
+            string_offsets,
+            string_data,
+            ids,
+            pageranks,
+            degrees,
+            communities,
+            xs,
+            ys,
+            sources,
+            targets,
+            weights,
+        });
+        Ok(header)
+    }
+
+    /// Appends nodes `range.start..range.end` (by index) to `out`. Positions
+    /// are zero unless the file carries them, as with `decode_graph`.
+    pub fn decode_nodes_into(
+        &mut self,
+        out: &mut Vec<Node>,
+        range: Range<usize>,
+    ) -> Result<(), DecodeError> {
+        let sections = self.sections()?;
+        let count = sections.header.node_count as usize;
+        check_range(&range, count)?;
+
+        out.reserve(range.len());
+        for i in range {
+            let label = if sections.header.has_flag(Flags::HasLabels) {
+                self.read_label(&sections, i)?
+            } else {
+                String::new()
+            };
+            self.offset = sections.ids + i * 4;
+            let id = self.read_u32()?;
+            self.offset = sections.pageranks + i * 4;
+            let pagerank = self.read_f32()?;
+            self.offset = sections.degrees + i * 2;
+            let degree = self.read_u16()?;
+            let community = if sections.header.has_communities() {
+                self.offset = sections.communities + i * 4;
+                community_from_wire(self.read_u32()?)
+            } else {
+                None
+            };
+            let (x, y) = if sections.header.has_positions() {
+                self.offset = sections.xs + i * 4;
+                let x = self.read_f32()?;
+                self.offset = sections.ys + i * 4;
+                (x, self.read_f32()?)
+            } else {
+                (0.0, 0.0)
+            };
+
+            out.push(Node {
+                id,
+                label,
+                pagerank,
+                degree,
+                x,
+                y,
+                community,
+                attributes: Default::default(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Appends edges `range.start..range.end` (by index) to `out`. Endpoints
+    /// are not validated since the full node table may not be loaded yet.
+    pub fn decode_edges_into(
+        &mut self,
+        out: &mut Vec<Edge>,
+        range: Range<usize>,
+    ) -> Result<(), DecodeError> {
+        let sections = self.sections()?;
+        let count = sections.header.edge_count as usize;
+        check_range(&range, count)?;
+
+        out.reserve(range.len());
+        for i in range {
+            self.offset = sections.sources + i * 4;
+            let source = self.read_u32()?;
+            self.offset = sections.targets + i * 4;
+            let target = self.read_u32()?;
+            let weight = if sections.header.has_flag(Flags::HasWeights) {
+                self.offset = sections.weights + i * 4;
+                self.read_f32()?
+            } else {
+                1.0
+            };
+            out.push(Edge {
+                source,
+                target,
+                weight,
+            });
+        }
+        Ok(())
+    }
+
+    /// Fraction of the buffer consumed so far.
+    fn progress(&self) -> f32 {
+        if self.data.is_empty() {
+            return 1.0;
+        }
+        (self.offset as f64 / self.data.len() as f64) as f32
+    }
+
+    fn verify_checksum(&self, header: &Header) -> Result<(), DecodeError> {
+        if let Some(expected) = header.checksum {
+            let actual = crc32(&self.data[header.size()..]);
+            if actual != expected {
+                return Err(DecodeError::ChecksumMismatch { expected, actual });
+            }
+        }
+        Ok(())
+    }
+
+    fn sections(&mut self) -> Result<Sections, DecodeError> {
+        match self.sections {
+            Some(sections) => Ok(sections),
+            None => {
+                self.decode_header()?;
+                // decode_header always fills sections on success
+                Ok(self.sections.expect("sections set by decode_header"))
+            }
+        }
+    }
+
+    fn read_label(&mut self, sections: &Sections, i: usize) -> Result<String, DecodeError> {
+        let count = sections.header.node_count as usize;
+        self.offset = sections.string_offsets + i * 4;
+        let start = self.read_u32()? as usize;
+        let end = if i + 1 < count {
+            self.read_u32()? as usize
+        } else {
+            sections.string_total_len
+        };
+        if start > end || end > sections.string_total_len {
+            return Err(DecodeError::BadStringOffset {
+                index: i,
+                start,
+                end,
+            });
+        }
+
+        self.offset = sections.string_data + start;
+        let bytes = self.read_bytes(end - start)?;
+        let s = std::str::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8 { index: i })?;
+        Ok(s.to_string())
+    }
+
+    fn decode_string_table(&mut self, count: usize) -> Result<Vec<String>, DecodeError> {
         let total_len = self.read_u32()? as usize;
         let offsets: Vec<u32> = (0..count)
             .map(|_| self.read_u32())
@@ -64,22 +385,33 @@ impl<'a> Decoder<'a> {
             } else {
                 total_len
             };
+            // Offsets come straight off the wire; a bad table must not panic
+            if start > end || end > total_len {
+                return Err(DecodeError::BadStringOffset {
+                    index: i,
+                    start,
+                    end,
+                });
+            }
             let s = std::str::from_utf8(&string_data[start..end])
-                .map_err(|e| format!("Invalid UTF-8: {}", e))?;
+                .map_err(|_| DecodeError::InvalidUtf8 { index: i })?;
             labels.push(s.to_string());
         }
         Ok(labels)
     }
 
     #[allow(clippy::type_complexity)]
-    fn decode_node_data(&mut self, count: usize) -> Result<(Vec<u32>, Vec<f32>, Vec<u16>), String> {
+    fn decode_node_data(
+        &mut self,
+        count: usize,
+    ) -> Result<(Vec<u32>, Vec<f32>, Vec<u16>), DecodeError> {
         let ids = self.read_u32_array(count)?;
         let pageranks = self.read_f32_array(count)?;
         let degrees = self.read_u16_array(count)?;
         Ok((ids, pageranks, degrees))
     }
 
-    fn decode_edge_data(&mut self, count: usize) -> Result<(Vec<u32>, Vec<u32>), String> {
+    fn decode_edge_data(&mut self, count: usize) -> Result<(Vec<u32>, Vec<u32>), DecodeError> {
         let sources = self.read_u32_array(count)?;
         let targets = self.read_u32_array(count)?;
         Ok((sources, targets))
@@ -87,48 +419,135 @@ impl<'a> Decoder<'a> {
 
     // primatives
 
-    fn read_u32_array(&mut self, count: usize) -> Result<Vec<u32>, String> {
+    fn read_u32_array(&mut self, count: usize) -> Result<Vec<u32>, DecodeError> {
         (0..count).map(|_| self.read_u32()).collect()
     }
 
-    fn read_u16_array(&mut self, count: usize) -> Result<Vec<u16>, String> {
+    fn read_u16_array(&mut self, count: usize) -> Result<Vec<u16>, DecodeError> {
         (0..count).map(|_| self.read_u16()).collect()
     }
 
-    fn read_f32_array(&mut self, count: usize) -> Result<Vec<f32>, String> {
+    fn read_f32_array(&mut self, count: usize) -> Result<Vec<f32>, DecodeError> {
         (0..count).map(|_| self.read_f32()).collect()
     }
 
-    fn read_u32(&mut self) -> Result<u32, String> {
+    fn read_u32(&mut self) -> Result<u32, DecodeError> {
         let bytes = self.read_bytes(4)?;
         Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
     }
 
-    fn read_u16(&mut self) -> Result<u16, String> {
+    fn read_u16(&mut self) -> Result<u16, DecodeError> {
         let bytes = self.read_bytes(2)?;
         Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
     }
 
-    fn read_f32(&mut self) -> Result<f32, String> {
+    fn read_f32(&mut self) -> Result<f32, DecodeError> {
         let bytes = self.read_bytes(4)?;
         Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
     }
 
-    fn read_bytes(&mut self, len: usize) -> Result<&[u8], String> {
-        if self.offset + len > self.data.len() {
-            return Err(format!("Unexpected EOF at offset {}", self.offset));
-        }
-        let slice = &self.data[self.offset..self.offset + len];
-        self.offset += len;
+    fn read_bytes(&mut self, len: usize) -> Result<&[u8], DecodeError> {
+        // `len` can come off the wire; on 32-bit wasm a plain add could wrap
+        // past the bounds check
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(DecodeError::UnexpectedEof {
+                offset: self.offset,
+            })?;
+        let slice = &self.data[self.offset..end];
+        self.offset = end;
         Ok(slice)
     }
 }
 
+impl Graph {
+    /// Decodes BLOM bytes, rejecting dangling edges; shorthand for
+    /// `Decoder::new(bytes).decode_graph()`.
+    pub fn from_bloom_bytes(bytes: &[u8]) -> Result<Graph, DecodeError> {
+        Decoder::new(bytes).decode_graph()
+    }
+}
+
+fn check_range(range: &Range<usize>, count: usize) -> Result<(), DecodeError> {
+    if range.start > range.end || range.end > count {
+        return Err(DecodeError::RangeOutOfBounds {
+            start: range.start,
+            end: range.end,
+            count,
+        });
+    }
+    Ok(())
+}
+
+fn community_from_wire(id: u32) -> Option<u32> {
+    (id != NO_COMMUNITY).then_some(id)
+}
+
+/// Most bytes one byte of zlib can inflate to. DEFLATE tops out just
+/// above 1030:1.
+const MAX_INFLATE_RATIO: usize = 1032;
+
+/// Inflates a compressed body, refusing to produce more bytes than the
+/// header says the body holds, so a small zlib bomb can't exhaust memory.
+/// Counts the stream couldn't hold even fully inflated are rejected before
+/// anything is inflated.
+fn inflate_body(header: &Header, compressed: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    header.check_counts(compressed.len().saturating_mul(MAX_INFLATE_RATIO))?;
+
+    let mut body_size = header.min_body_size();
+    if header.has_flag(Flags::HasLabels) {
+        // The string table's byte length leads the body; inflate just
+        // enough to read it
+        let prefix = match decompress_to_vec_zlib_with_limit(compressed, 4) {
+            Ok(prefix) => prefix,
+            Err(e) if e.status == TINFLStatus::HasMoreOutput => e.output,
+            Err(_) => return Err(DecodeError::InvalidCompression),
+        };
+        if let Some(len) = prefix.first_chunk::<4>() {
+            body_size += u32::from_le_bytes(*len) as u64;
+        }
+    }
+
+    let implausible = |body_len| DecodeError::ImplausibleCounts {
+        node_count: header.node_count,
+        edge_count: header.edge_count,
+        body_len,
+    };
+    let limit = usize::try_from(body_size).map_err(|_| implausible(usize::MAX))?;
+    decompress_to_vec_zlib_with_limit(compressed, limit).map_err(|e| match e.status {
+        TINFLStatus::HasMoreOutput => implausible(limit),
+        _ => DecodeError::InvalidCompression,
+    })
+}
+
+fn validate_edges(graph: &Graph) -> Result<(), DecodeError> {
+    for (edge_index, edge) in graph.edges().iter().enumerate() {
+        for id in [edge.source, edge.target] {
+            if graph.node_index(id).is_none() {
+                return Err(DecodeError::DanglingEdge { edge_index, id });
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::protocol::format::HEADER_SIZE;
+    use crate::protocol::{Encoder, format::CHECKSUM_SIZE};
     use crate::test_utils::build_blom;
+    use miniz_oxide::deflate::compress_to_vec_zlib;
+
+    fn compress_blom(data: &[u8]) -> Vec<u8> {
+        let mut out = data[..HEADER_SIZE].to_vec();
+        let flags = u16::from_le_bytes([out[14], out[15]]) | Flags::Compressed as u16;
+        out[14..16].copy_from_slice(&flags.to_le_bytes());
+        out.extend(compress_to_vec_zlib(&data[HEADER_SIZE..], 6));
+        out
+    }
 
     #[test]
     fn decode_minimal_graph() {
@@ -187,7 +606,93 @@ mod tests {
         let mut data = build_blom(&[(1, 0.0, 0)], &[], None);
         data.truncate(HEADER_SIZE + 2); // cut off mid-node-data
         let err = Decoder::new(&data).decode_graph().unwrap_err();
-        assert!(err.contains("Unexpected EOF"), "got: {err}");
+        assert_eq!(
+            err,
+            DecodeError::ImplausibleCounts {
+                node_count: 1,
+                edge_count: 0,
+                body_len: 2,
+            }
+        );
+
+        // String bytes aren't part of the size floor, so cutting into
+        // them still surfaces as EOF
+        let mut data = build_blom(&[(1, 0.0, 0)], &[], Some(&["a long label"]));
+        data.drain(HEADER_SIZE + 8..HEADER_SIZE + 18);
+        let err = Decoder::new(&data).decode_graph().unwrap_err();
+        assert!(
+            matches!(err, DecodeError::UnexpectedEof { .. }),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn decode_rejects_implausible_counts() {
+        let mut data = build_blom(&[(1, 0.0, 0)], &[], None);
+        data[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = Decoder::new(&data).decode_graph().unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::ImplausibleCounts {
+                node_count: u32::MAX,
+                edge_count: 0,
+                body_len: 10,
+            }
+        );
+        let err = Decoder::new(&data).decode_header().unwrap_err();
+        assert!(matches!(err, DecodeError::ImplausibleCounts { .. }));
+
+        // Compressed bodies are bounded by the most they could inflate to,
+        // before inflating
+        let mut data = compress_blom(&build_blom(&[(1, 0.0, 0)], &[], None));
+        data[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            Decoder::new(&data).decode_graph().unwrap_err(),
+            DecodeError::ImplausibleCounts {
+                node_count: u32::MAX,
+                edge_count: 0,
+                body_len: (data.len() - HEADER_SIZE) * MAX_INFLATE_RATIO,
+            }
+        );
+        let mut data = compress_blom(&build_blom(&[(1, 0.0, 0)], &[(1, 1)], None));
+        data[10..14].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = Decoder::new(&data).decode_graph().unwrap_err();
+        assert!(matches!(err, DecodeError::ImplausibleCounts { .. }));
+    }
+
+    #[test]
+    fn decode_stops_inflating_past_declared_body() {
+        // A few KB of zlib that would inflate to 16 MB behind a header
+        // declaring one node
+        let plain = build_blom(&[(1, 0.0, 0)], &[], None);
+        let mut bomb = compress_blom(&plain)[..HEADER_SIZE].to_vec();
+        bomb.extend(compress_to_vec_zlib(&vec![0; 16 << 20], 10));
+        assert!(bomb.len() < 100_000);
+        let err = Decoder::new(&bomb).decode_graph().unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::ImplausibleCounts {
+                node_count: 1,
+                edge_count: 0,
+                body_len: 10,
+            }
+        );
+
+        // With labels the limit also covers the declared string bytes
+        let labelled = build_blom(&[(1, 0.0, 0)], &[], Some(&["label"]));
+        let mut body = labelled[HEADER_SIZE..].to_vec();
+        body.resize(16 << 20, 0);
+        let mut bomb = compress_blom(&labelled)[..HEADER_SIZE].to_vec();
+        bomb.extend(compress_to_vec_zlib(&body, 10));
+        let err = Decoder::new(&bomb).decode_graph().unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::ImplausibleCounts {
+                node_count: 1,
+                edge_count: 0,
+                body_len: 4 + 14 + 5,
+            }
+        );
     }
 
     #[test]
@@ -210,4 +715,377 @@ mod tests {
         assert_eq!(graph.node_by_id(99).unwrap().id, 99);
         assert!(graph.node_by_id(1).is_none());
     }
+
+    #[test]
+    fn decode_compressed_matches_uncompressed() {
+        let nodes = &[(1, 0.1, 2), (2, 0.2, 3), (3, 0.3, 1)];
+        let edges = &[(1, 2), (2, 3)];
+        let labels = &["one", "two", "three"];
+        let plain = build_blom(nodes, edges, Some(labels));
+        let compressed = compress_blom(&plain);
+
+        let expected = Decoder::new(&plain).decode_graph().unwrap();
+        let graph = Decoder::new(&compressed).decode_graph().unwrap();
+        assert_eq!(graph.nodes(), expected.nodes());
+        assert_eq!(graph.edges(), expected.edges());
+    }
+
+    #[test]
+    fn decode_corrupt_compressed_payload() {
+        let plain = build_blom(&[(1, 0.0, 0), (2, 0.0, 0)], &[(1, 2)], None);
+        let mut compressed = compress_blom(&plain);
+        compressed.truncate(compressed.len() - 4);
+        let err = Decoder::new(&compressed).decode_graph().unwrap_err();
+        assert_eq!(err, DecodeError::InvalidCompression);
+
+        let mut garbage = plain[..HEADER_SIZE].to_vec();
+        garbage[14..16].copy_from_slice(&(Flags::Compressed as u16).to_le_bytes());
+        garbage.extend_from_slice(&[0xFF; 8]);
+        let err = Decoder::new(&garbage).decode_graph().unwrap_err();
+        assert_eq!(err, DecodeError::InvalidCompression);
+    }
+
+    #[test]
+    fn decode_unweighted_edges_default_to_one() {
+        let data = build_blom(&[(1, 0.0, 1), (2, 0.0, 1)], &[(1, 2)], None);
+        let graph = Decoder::new(&data).decode_graph().unwrap();
+        assert_eq!(graph.edges()[0].weight, 1.0);
+    }
+
+    #[test]
+    fn decode_weights_after_targets() {
+        let mut data = build_blom(
+            &[(1, 0.0, 1), (2, 0.0, 2), (3, 0.0, 1)],
+            &[(1, 2), (2, 3)],
+            None,
+        );
+        data[14..16].copy_from_slice(&(Flags::HasWeights as u16).to_le_bytes());
+        data.extend_from_slice(&0.5f32.to_le_bytes());
+        data.extend_from_slice(&2.5f32.to_le_bytes());
+
+        let graph = Decoder::new(&data).decode_graph().unwrap();
+        assert_eq!(graph.edges()[0].source, 1);
+        assert_eq!(graph.edges()[0].weight, 0.5);
+        assert_eq!(graph.edges()[1].target, 3);
+        assert_eq!(graph.edges()[1].weight, 2.5);
+    }
+
+    #[test]
+    fn decode_missing_weights_is_rejected() {
+        let mut data = build_blom(&[(1, 0.0, 1), (2, 0.0, 1)], &[(1, 2)], None);
+        data[14..16].copy_from_slice(&(Flags::HasWeights as u16).to_le_bytes());
+        let err = Decoder::new(&data).decode_graph().unwrap_err();
+        assert!(
+            matches!(err, DecodeError::ImplausibleCounts { .. }),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn decode_invalid_utf8_label() {
+        let mut data = build_blom(&[(1, 0.0, 0), (2, 0.0, 0)], &[], Some(&["ab", "cd"]));
+        // String bytes start after header + total_len + two offsets
+        data[HEADER_SIZE + 4 + 8 + 2] = 0xFF;
+        let err = Decoder::new(&data).decode_graph().unwrap_err();
+        assert_eq!(err, DecodeError::InvalidUtf8 { index: 1 });
+    }
+
+    #[test]
+    fn decode_rejects_dangling_edge() {
+        let data = build_blom(&[(1, 0.0, 1), (2, 0.0, 1)], &[(1, 2), (2, 7)], None);
+        let err = Decoder::new(&data).decode_graph().unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::DanglingEdge {
+                edge_index: 1,
+                id: 7
+            }
+        );
+    }
+
+    #[test]
+    fn decode_unchecked_keeps_dangling_edge() {
+        let data = build_blom(&[(1, 0.0, 1)], &[(9, 1)], None);
+        let graph = Decoder::new(&data).decode_graph_unchecked().unwrap();
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.edges()[0].source, 9);
+    }
+
+    fn set_string_offset(data: &mut [u8], index: usize, offset: u32) {
+        let at = HEADER_SIZE + 4 + index * 4;
+        data[at..at + 4].copy_from_slice(&offset.to_le_bytes());
+    }
+
+    #[test]
+    fn decode_string_offset_past_total_len() {
+        let mut data = build_blom(&[(1, 0.0, 0), (2, 0.0, 0)], &[], Some(&["ab", "cd"]));
+        set_string_offset(&mut data, 1, 99);
+        let err = Decoder::new(&data).decode_graph().unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::BadStringOffset {
+                index: 0,
+                start: 0,
+                end: 99
+            }
+        );
+    }
+
+    #[test]
+    fn decode_string_offsets_not_monotonic() {
+        let labels = &["ab", "cd", "ef"];
+        let mut data = build_blom(&[(1, 0.0, 0), (2, 0.0, 0), (3, 0.0, 0)], &[], Some(labels));
+        set_string_offset(&mut data, 1, 5);
+        set_string_offset(&mut data, 2, 3);
+        let err = Decoder::new(&data).decode_graph().unwrap_err();
+        assert!(
+            matches!(err, DecodeError::BadStringOffset { index: 1, .. }),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn decode_random_string_offsets_never_panic() {
+        let labels = &["alpha", "beta", "gamma", "delta"];
+        let nodes = &[(1, 0.0, 0), (2, 0.0, 0), (3, 0.0, 0), (4, 0.0, 0)];
+        let base = build_blom(nodes, &[], Some(labels));
+
+        let mut state = 0x9E37_79B9u32;
+        for _ in 0..1000 {
+            let mut data = base.clone();
+            for i in 0..labels.len() {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                // Mostly small offsets so some tables are valid, plus the odd huge one
+                let offset = if state.is_multiple_of(8) {
+                    state
+                } else {
+                    state % 24
+                };
+                set_string_offset(&mut data, i, offset);
+            }
+            // Must return Ok or Err, never panic
+            let _ = Decoder::new(&data).decode_graph();
+        }
+    }
+
+    fn chunked_fixture() -> Vec<u8> {
+        let nodes = &[(1, 0.1, 1), (2, 0.2, 2), (3, 0.3, 2), (4, 0.4, 1)];
+        let edges = &[(1, 2), (2, 3), (3, 4)];
+        build_blom(nodes, edges, Some(&["a", "bb", "", "dddd"]))
+    }
+
+    #[test]
+    fn chunked_decode_matches_full_decode() {
+        let data = chunked_fixture();
+        let full = Decoder::new(&data).decode_graph().unwrap();
+
+        let mut decoder = Decoder::new(&data);
+        let header = decoder.decode_header().unwrap();
+        let mut nodes = Vec::new();
+        decoder.decode_nodes_into(&mut nodes, 0..3).unwrap();
+        decoder.decode_nodes_into(&mut nodes, 3..4).unwrap();
+        let mut edges = Vec::new();
+        decoder.decode_edges_into(&mut edges, 0..2).unwrap();
+        decoder.decode_edges_into(&mut edges, 2..3).unwrap();
+
+        assert_eq!(header.node_count, 4);
+        assert_eq!(nodes, full.nodes());
+        assert_eq!(edges, full.edges());
+    }
+
+    #[test]
+    fn chunked_decode_arbitrary_window() {
+        let data = chunked_fixture();
+        let mut decoder = Decoder::new(&data);
+        let mut nodes = Vec::new();
+        // decode_header is called implicitly on first use
+        decoder.decode_nodes_into(&mut nodes, 1..3).unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].id, 2);
+        assert_eq!(nodes[0].label, "bb");
+        assert_eq!(nodes[1].id, 3);
+        assert!(nodes[1].label.is_empty());
+
+        let mut edges = Vec::new();
+        decoder.decode_edges_into(&mut edges, 2..3).unwrap();
+        assert_eq!((edges[0].source, edges[0].target), (3, 4));
+    }
+
+    #[test]
+    fn chunked_decode_rejects_bad_range() {
+        let data = chunked_fixture();
+        let mut decoder = Decoder::new(&data);
+        let mut nodes = Vec::new();
+        let err = decoder.decode_nodes_into(&mut nodes, 2..9).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::RangeOutOfBounds {
+                start: 2,
+                end: 9,
+                count: 4
+            }
+        );
+        assert!(nodes.is_empty());
+    }
+
+    fn set_version(data: &mut [u8], version: u16) {
+        data[4..6].copy_from_slice(&version.to_le_bytes());
+    }
+
+    #[test]
+    fn decode_v1_file_unchanged() {
+        let mut data = build_blom(&[(1, 0.5, 1), (2, 0.5, 1)], &[(1, 2)], Some(&["a", "b"]));
+        set_version(&mut data, 1);
+        let graph = Decoder::new(&data).decode_graph().unwrap();
+
+        assert_eq!(graph.node_count(), 2);
+        assert_eq!(graph.nodes()[1].label, "b");
+        assert!(graph.nodes().iter().all(|n| n.x == 0.0 && n.y == 0.0));
+        assert_eq!(graph.edges()[0].target, 2);
+    }
+
+    #[test]
+    fn decode_v2_positions() {
+        let mut data = build_blom(&[(1, 0.0, 1), (2, 0.0, 1)], &[(1, 2)], None);
+        data[14..16].copy_from_slice(&(Flags::HasPositions as u16).to_le_bytes());
+        // Splice x/y arrays in between the degrees and the edge data
+        let edges_at = data.len() - 8;
+        let mut positions = Vec::new();
+        for v in [1.5f32, -2.0, 3.0, 4.25] {
+            positions.extend_from_slice(&v.to_le_bytes());
+        }
+        data.splice(edges_at..edges_at, positions);
+
+        let graph = Decoder::new(&data).decode_graph().unwrap();
+        assert_eq!((graph.nodes()[0].x, graph.nodes()[0].y), (1.5, 3.0));
+        assert_eq!((graph.nodes()[1].x, graph.nodes()[1].y), (-2.0, 4.25));
+        assert_eq!(graph.edges()[0].source, 1);
+
+        let mut nodes = Vec::new();
+        Decoder::new(&data)
+            .decode_nodes_into(&mut nodes, 1..2)
+            .unwrap();
+        assert_eq!((nodes[0].x, nodes[0].y), (-2.0, 4.25));
+    }
+
+    #[test]
+    fn community_flag_ignored_before_v2() {
+        // The bit is set but v1 has no community array, so the edges
+        // follow the degrees directly
+        let mut data = build_blom(&[(1, 0.0, 1), (2, 0.0, 1)], &[(1, 2)], None);
+        data[14..16].copy_from_slice(&(Flags::HasCommunities as u16).to_le_bytes());
+        set_version(&mut data, 1);
+        let graph = Decoder::new(&data).decode_graph().unwrap();
+        assert!(graph.nodes().iter().all(|n| n.community.is_none()));
+        assert_eq!(graph.edges()[0].target, 2);
+
+        // At v2 the same bytes are four short of two community ids
+        set_version(&mut data, 2);
+        assert!(matches!(
+            Decoder::new(&data).decode_graph(),
+            Err(DecodeError::ImplausibleCounts { .. })
+        ));
+    }
+
+    #[test]
+    fn peek_header_leaves_cursor() {
+        let data = build_blom(&[(1, 0.0, 1), (2, 0.0, 1)], &[(1, 2)], None);
+        let mut decoder = Decoder::new(&data);
+        let header = decoder.peek_header().unwrap();
+        assert_eq!(header.node_count, 2);
+        assert_eq!(header.edge_count, 1);
+        assert_eq!(decoder.offset, 0);
+
+        let graph = decoder.decode_graph().unwrap();
+        assert_eq!(graph.node_count(), 2);
+    }
+
+    #[test]
+    fn peek_header_on_truncated_body() {
+        let data = build_blom(&[(1, 0.0, 0)], &[], None);
+        let decoder = Decoder::new(&data[..HEADER_SIZE]);
+        assert_eq!(decoder.peek_header().unwrap().node_count, 1);
+    }
+
+    #[test]
+    fn decode_v3_verifies_checksum() {
+        let data = build_blom(&[(1, 0.1, 1), (2, 0.2, 1)], &[(1, 2)], Some(&["a", "b"]));
+        let graph = Decoder::new(&data).decode_graph().unwrap();
+        let encoded = Encoder::new().encode_graph(&graph, Flags::HasLabels as u16);
+        assert!(Decoder::new(&encoded).decode_graph().is_ok());
+
+        let mut corrupt = encoded.clone();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 0x01;
+        let err = Decoder::new(&corrupt).decode_graph().unwrap_err();
+        let expected = u32::from_le_bytes(
+            encoded[HEADER_SIZE..HEADER_SIZE + CHECKSUM_SIZE]
+                .try_into()
+                .unwrap(),
+        );
+        assert_eq!(
+            err,
+            DecodeError::ChecksumMismatch {
+                expected,
+                actual: crc32(&corrupt[HEADER_SIZE + CHECKSUM_SIZE..]),
+            }
+        );
+
+        let err = Decoder::new(&corrupt).decode_header().unwrap_err();
+        assert!(matches!(err, DecodeError::ChecksumMismatch { .. }));
+    }
+
+    #[test]
+    fn read_bytes_rejects_wrapping_length() {
+        let data = [0u8; 8];
+        let mut decoder = Decoder::new(&data);
+        decoder.offset = 4;
+        // offset + len would wrap to 3 and slip past a naive bounds check
+        let err = decoder.read_bytes(usize::MAX).unwrap_err();
+        assert_eq!(err, DecodeError::UnexpectedEof { offset: 4 });
+        assert_eq!(decoder.offset, 4);
+        assert_eq!(decoder.read_bytes(4).unwrap(), &[0; 4]);
+        assert!(decoder.read_bytes(1).is_err());
+    }
+
+    #[test]
+    fn chunked_decode_rejects_oversized_string_table() {
+        let mut data = build_blom(&[(1, 0.0, 0)], &[], Some(&["a"]));
+        data[HEADER_SIZE..HEADER_SIZE + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = Decoder::new(&data).decode_header().unwrap_err();
+        assert!(
+            matches!(err, DecodeError::UnexpectedEof { .. }),
+            "got: {err}"
+        );
+        let err = Decoder::new(&data).decode_graph().unwrap_err();
+        assert!(
+            matches!(err, DecodeError::UnexpectedEof { .. }),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn decode_progress_is_monotonic_and_ends_at_one() {
+        let nodes = &[(1, 0.1, 1), (2, 0.2, 2), (3, 0.3, 1)];
+        let data = build_blom(nodes, &[(1, 2), (2, 3)], Some(&["a", "bb", "ccc"]));
+        for bytes in [data.clone(), compress_blom(&data)] {
+            let mut seen = Vec::new();
+            let graph = Decoder::new(&bytes)
+                .decode_graph_with_progress(|p| seen.push(p))
+                .unwrap();
+            assert_eq!(graph.node_count(), 3);
+            assert_eq!(seen.len(), 3);
+            assert!(seen.windows(2).all(|w| w[0] <= w[1]), "{seen:?}");
+            assert!(seen[0] > 0.0);
+            assert_eq!(seen.last(), Some(&1.0));
+        }
+
+        // No callback at all once decoding fails
+        let mut calls = 0;
+        let err = Decoder::new(&data[..10]).decode_graph_with_progress(|_| calls += 1);
+        assert!(err.is_err());
+        assert_eq!(calls, 0);
+    }
 }
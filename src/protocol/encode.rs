@@ -0,0 +1,348 @@
+use super::checksum::crc32;
+use super::format::{CHECKSUM_SIZE, FlagSet, Flags, HEADER_SIZE, Header, NO_COMMUNITY};
+use crate::graph::types::Graph;
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// Writes a `Graph` into the BLOM binary format read by `Decoder`.
+pub struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    pub fn encode_graph(&mut self, graph: &Graph, flags: impl Into<FlagSet>) -> Vec<u8> {
+        let flags = flags.into();
+        self.buf.clear();
+
+        self.encode_header(graph, flags);
+        if flags.contains(Flags::HasLabels) {
+            self.encode_string_table(graph);
+        }
+        self.encode_node_data(graph);
+        if flags.contains(Flags::HasCommunities) {
+            self.encode_communities(graph);
+        }
+        if flags.contains(Flags::HasPositions) {
+            self.encode_positions(graph);
+        }
+        self.encode_edge_data(graph);
+        if flags.contains(Flags::HasWeights) {
+            self.encode_edge_weights(graph);
+        }
+
+        let body_start = HEADER_SIZE + CHECKSUM_SIZE;
+        if flags.contains(Flags::Compressed) {
+            let body = compress_to_vec_zlib(&self.buf[body_start..], COMPRESSION_LEVEL);
+            self.buf.truncate(body_start);
+            self.buf.extend_from_slice(&body);
+        }
+
+        // The checksum covers the body as written, i.e. after compression
+        let checksum = crc32(&self.buf[body_start..]);
+        self.buf[HEADER_SIZE..body_start].copy_from_slice(&checksum.to_le_bytes());
+
+        std::mem::take(&mut self.buf)
+    }
+
+    fn encode_header(&mut self, graph: &Graph, flags: FlagSet) {
+        let header = Header::new(graph.node_count() as u32, graph.edge_count() as u32, flags);
+        self.buf.extend_from_slice(&header.to_bytes());
+        // Checksum placeholder, filled in once the body is written
+        self.write_u32(0);
+    }
+
+    fn encode_string_table(&mut self, graph: &Graph) {
+        let total_len: usize = graph.nodes().iter().map(|n| n.label.len()).sum();
+        self.write_u32(total_len as u32);
+
+        let mut offset = 0u32;
+        for node in graph.nodes() {
+            self.write_u32(offset);
+            offset += node.label.len() as u32;
+        }
+        for node in graph.nodes() {
+            self.buf.extend_from_slice(node.label.as_bytes());
+        }
+    }
+
+    fn encode_node_data(&mut self, graph: &Graph) {
+        for node in graph.nodes() {
+            self.write_u32(node.id);
+        }
+        for node in graph.nodes() {
+            self.write_f32(node.pagerank);
+        }
+        for node in graph.nodes() {
+            self.write_u16(node.degree);
+        }
+    }
+
+    fn encode_communities(&mut self, graph: &Graph) {
+        for node in graph.nodes() {
+            self.write_u32(node.community.unwrap_or(NO_COMMUNITY));
+        }
+    }
+
+    fn encode_positions(&mut self, graph: &Graph) {
+        for node in graph.nodes() {
+            self.write_f32(node.x);
+        }
+        for node in graph.nodes() {
+            self.write_f32(node.y);
+        }
+    }
+
+    fn encode_edge_data(&mut self, graph: &Graph) {
+        for edge in graph.edges() {
+            self.write_u32(edge.source);
+        }
+        for edge in graph.edges() {
+            self.write_u32(edge.target);
+        }
+    }
+
+    fn encode_edge_weights(&mut self, graph: &Graph) {
+        for edge in graph.edges() {
+            self.write_f32(edge.weight);
+        }
+    }
+
+    // primatives
+
+    fn write_u32(&mut self, value: u32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_f32(&mut self, value: f32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+impl Graph {
+    /// Encodes the graph as BLOM bytes; shorthand for a fresh `Encoder`.
+    /// The format has no direction bit, so a directed graph decodes back
+    /// as undirected.
+    pub fn to_bloom_bytes(&self, flags: impl Into<FlagSet>) -> Vec<u8> {
+        Encoder::new().encode_graph(self, flags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::algorithms::louvain;
+    use crate::graph::types::{Edge, Node};
+    use crate::protocol::decode::Decoder;
+    use crate::protocol::format::{CHECKSUM_SIZE, HEADER_SIZE, VERSION};
+    use crate::test_utils::build_blom;
+
+    fn make_node(id: u32, label: &str, pagerank: f32, degree: u16) -> Node {
+        Node {
+            id,
+            label: label.to_string(),
+            pagerank,
+            degree,
+            ..Node::default()
+        }
+    }
+
+    fn sample_graph() -> Graph {
+        let nodes = vec![
+            make_node(1, "alpha", 0.5, 1),
+            make_node(2, "", 0.3, 2),
+            make_node(3, "gamma", 0.2, 1),
+        ];
+        let edges = vec![
+            Edge {
+                source: 1,
+                target: 2,
+                weight: 1.0,
+            },
+            Edge {
+                source: 2,
+                target: 3,
+                weight: 1.0,
+            },
+        ];
+        Graph::new(nodes, edges)
+    }
+
+    #[test]
+    fn encode_empty_graph() {
+        let g = Graph::new(vec![], vec![]);
+        let data = Encoder::new().encode_graph(&g, 0);
+        assert_eq!(data.len(), HEADER_SIZE + CHECKSUM_SIZE);
+
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+        assert_eq!(decoded.node_count(), 0);
+        assert_eq!(decoded.edge_count(), 0);
+    }
+
+    #[test]
+    fn encode_matches_test_fixture() {
+        let g = sample_graph();
+        let data = Encoder::new().encode_graph(&g, 0);
+        let expected = build_blom(
+            &[(1, 0.5, 1), (2, 0.3, 2), (3, 0.2, 1)],
+            &[(1, 2), (2, 3)],
+            None,
+        );
+        // Same body as the v2 fixture, behind a v3 header with a checksum
+        assert_eq!(data[HEADER_SIZE + CHECKSUM_SIZE..], expected[HEADER_SIZE..]);
+        assert_eq!(data[4..6], VERSION.to_le_bytes());
+        assert_eq!(
+            data[HEADER_SIZE..HEADER_SIZE + CHECKSUM_SIZE],
+            crc32(&expected[HEADER_SIZE..]).to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn roundtrip_with_labels() {
+        let g = sample_graph();
+        let data = Encoder::new().encode_graph(&g, Flags::HasLabels as u16);
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+
+        assert_eq!(decoded.nodes(), g.nodes());
+        assert_eq!(decoded.edges(), g.edges());
+        assert_eq!(decoded.nodes()[0].label, "alpha");
+        assert!(decoded.nodes()[1].label.is_empty());
+    }
+
+    #[test]
+    fn roundtrip_without_labels_drops_them() {
+        let g = sample_graph();
+        let data = Encoder::new().encode_graph(&g, 0);
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+
+        assert_eq!(decoded.node_count(), 3);
+        assert!(decoded.nodes().iter().all(|n| n.label.is_empty()));
+        assert_eq!(decoded.nodes()[2].pagerank, 0.2);
+    }
+
+    #[test]
+    fn encoder_is_reusable() {
+        let g = sample_graph();
+        let mut encoder = Encoder::new();
+        let first = encoder.encode_graph(&g, Flags::HasLabels as u16);
+        let second = encoder.encode_graph(&g, Flags::HasLabels as u16);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn roundtrip_compressed() {
+        let g = sample_graph();
+        let flags = Flags::Compressed | Flags::HasLabels;
+        let data = Encoder::new().encode_graph(&g, flags);
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+
+        assert_eq!(decoded.nodes(), g.nodes());
+        assert_eq!(decoded.edges(), g.edges());
+    }
+
+    #[test]
+    fn roundtrip_weights() {
+        let nodes = vec![make_node(1, "", 0.0, 1), make_node(2, "", 0.0, 1)];
+        let edges = vec![Edge {
+            source: 1,
+            target: 2,
+            weight: 3.5,
+        }];
+        let g = Graph::new(nodes, edges);
+
+        let data = Encoder::new().encode_graph(&g, Flags::HasWeights as u16);
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+        assert_eq!(decoded.edges()[0].weight, 3.5);
+
+        let data = Encoder::new().encode_graph(&g, 0);
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+        assert_eq!(decoded.edges()[0].weight, 1.0);
+    }
+
+    #[test]
+    fn roundtrip_positions() {
+        let mut g = sample_graph();
+        for (i, node) in g.nodes_mut().iter_mut().enumerate() {
+            node.x = i as f32 * 10.0;
+            node.y = -(i as f32) * 2.5;
+        }
+
+        let flags = Flags::HasPositions | Flags::HasLabels;
+        let data = Encoder::new().encode_graph(&g, flags);
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+        assert_eq!(decoded.nodes(), g.nodes());
+
+        let data = Encoder::new().encode_graph(&g, 0);
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+        assert!(decoded.nodes().iter().all(|n| n.x == 0.0 && n.y == 0.0));
+    }
+
+    #[test]
+    fn roundtrip_communities() {
+        let mut g = sample_graph();
+        let assigned = louvain(&g);
+        for (node, &c) in g.nodes_mut().iter_mut().zip(&assigned) {
+            node.community = Some(c as u32);
+            node.x = c as f32;
+        }
+        // One node left unassigned
+        g.nodes_mut()[1].community = None;
+
+        let flags = Flags::HasCommunities | Flags::HasPositions | Flags::HasLabels;
+        let data = Encoder::new().encode_graph(&g, flags);
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+        assert_eq!(decoded.nodes(), g.nodes());
+        assert_eq!(decoded.nodes()[1].community, None);
+
+        // The chunked reader finds the positions past the community ids
+        let mut chunked = Vec::new();
+        Decoder::new(&data)
+            .decode_nodes_into(&mut chunked, 0..3)
+            .unwrap();
+        assert_eq!(chunked, g.nodes());
+
+        let data = Encoder::new().encode_graph(&g, 0);
+        let decoded = Decoder::new(&data).decode_graph().unwrap();
+        assert!(decoded.nodes().iter().all(|n| n.community.is_none()));
+    }
+
+    #[test]
+    fn bloom_bytes_round_trip() {
+        let mut g = sample_graph();
+        g.add_edge(Edge {
+            source: 3,
+            target: 1,
+            weight: 2.5,
+        })
+        .unwrap();
+        for (i, node) in g.nodes_mut().iter_mut().enumerate() {
+            node.x = i as f32 * 10.0;
+            node.y = -(i as f32);
+        }
+
+        let flags = Flags::HasLabels | Flags::HasWeights | Flags::HasPositions;
+        let bytes = g.to_bloom_bytes(flags);
+        assert_eq!(bytes, Encoder::new().encode_graph(&g, flags));
+
+        let decoded = Graph::from_bloom_bytes(&bytes).unwrap();
+        assert_eq!(decoded.nodes(), g.nodes());
+        assert_eq!(decoded.edges(), g.edges());
+        assert_eq!(decoded.kind(), g.kind());
+
+        assert!(Graph::from_bloom_bytes(&bytes[..8]).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    TooShort {
+        len: usize,
+    },
+    BadMagic {
+        found: u32,
+    },
+    UnsupportedVersion {
+        found: u16,
+    },
+    UnexpectedEof {
+        offset: usize,
+    },
+    InvalidUtf8 {
+        index: usize,
+    },
+    BadStringOffset {
+        index: usize,
+        start: usize,
+        end: usize,
+    },
+    InvalidCompression,
+    DanglingEdge {
+        edge_index: usize,
+        id: u32,
+    },
+    RangeOutOfBounds {
+        start: usize,
+        end: usize,
+        count: usize,
+    },
+    CompressedChunks,
+    ChecksumMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    ImplausibleCounts {
+        node_count: u32,
+        edge_count: u32,
+        body_len: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TooShort { len } => write!(f, "Header is too short: {} bytes", len),
+            DecodeError::BadMagic { found } => write!(f, "Invalid magic number: 0x{:08X}", found),
+            DecodeError::UnsupportedVersion { found } => {
+                write!(f, "Unsupported version: {}", found)
+            }
+            DecodeError::UnexpectedEof { offset } => {
+                write!(f, "Unexpected EOF at offset {}", offset)
+            }
+            DecodeError::InvalidUtf8 { index } => write!(f, "Invalid UTF-8 in label {}", index),
+            DecodeError::BadStringOffset { index, start, end } => {
+                write!(
+                    f,
+                    "Label {} has invalid string range {}..{}",
+                    index, start, end
+                )
+            }
+            DecodeError::InvalidCompression => write!(f, "Invalid compressed payload"),
+            DecodeError::DanglingEdge { edge_index, id } => {
+                write!(f, "Edge {} references unknown node {}", edge_index, id)
+            }
+            DecodeError::RangeOutOfBounds { start, end, count } => {
+                write!(
+                    f,
+                    "Range {}..{} is out of bounds for {} items",
+                    start, end, count
+                )
+            }
+            DecodeError::CompressedChunks => {
+                write!(f, "Chunked decoding does not support compressed payloads")
+            }
+            DecodeError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "Checksum mismatch: expected 0x{:08X}, got 0x{:08X}",
+                expected, actual
+            ),
+            DecodeError::ImplausibleCounts {
+                node_count,
+                edge_count,
+                body_len,
+            } => write!(
+                f,
+                "Header declares {} nodes and {} edges, too many for a {}-byte body",
+                node_count, edge_count, body_len
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<DecodeError> for JsValue {
+    fn from(err: DecodeError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_matches_legacy_messages() {
+        assert_eq!(
+            DecodeError::TooShort { len: 10 }.to_string(),
+            "Header is too short: 10 bytes"
+        );
+        assert_eq!(
+            DecodeError::BadMagic { found: 0xDEADBEEF }.to_string(),
+            "Invalid magic number: 0xDEADBEEF"
+        );
+        assert_eq!(
+            DecodeError::UnexpectedEof { offset: 18 }.to_string(),
+            "Unexpected EOF at offset 18"
+        );
+    }
+}
@@ -1,15 +1,110 @@
+use super::error::DecodeError;
+use std::ops::BitOr;
+
 pub const MAGIC: u32 = 0x424C4F4D;
 
-pub const VERSION: u16 = 1;
+/// Version written by `Encoder`. Version 2 adds optional node positions;
+/// version 3 appends a CRC-32 of the body to the header.
+pub const VERSION: u16 = 3;
+
+/// Oldest version `Header::parse` still accepts.
+pub const MIN_VERSION: u16 = 1;
 
+/// Size of the fixed header fields shared by every version.
 pub const HEADER_SIZE: usize = 16;
 
+/// Size of the body checksum that follows the fixed header in version 3+.
+pub const CHECKSUM_SIZE: usize = 4;
+
+/// Community id written for a node with no community.
+pub const NO_COMMUNITY: u32 = u32::MAX;
+
+/// A single header flag bit. Combine them with `|` or `FlagSet`.
 #[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Flags {
     None = 0,
     Compressed = 1 << 0,
     HasLabels = 1 << 1,
     HasWeights = 1 << 2,
+    /// x/y arrays after the degrees. Only honoured in version 2+.
+    HasPositions = 1 << 3,
+    /// Community ids after the degrees, before any positions. Only
+    /// honoured in version 2+.
+    HasCommunities = 1 << 4,
+}
+
+/// Any combination of `Flags`, stored as the header's on-wire `u16`.
+/// Unknown bits are kept as-is so a round trip never drops them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FlagSet(u16);
+
+impl FlagSet {
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub fn bits(self) -> u16 {
+        self.0
+    }
+
+    pub fn contains(self, flag: Flags) -> bool {
+        self.0 & flag as u16 != 0
+    }
+
+    pub fn insert(&mut self, flag: Flags) {
+        self.0 |= flag as u16;
+    }
+
+    pub fn union(self, other: FlagSet) -> FlagSet {
+        Self(self.0 | other.0)
+    }
+}
+
+impl From<Flags> for FlagSet {
+    fn from(flag: Flags) -> Self {
+        Self(flag as u16)
+    }
+}
+
+impl From<u16> for FlagSet {
+    fn from(bits: u16) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<FlagSet> for u16 {
+    fn from(flags: FlagSet) -> Self {
+        flags.0
+    }
+}
+
+impl BitOr for Flags {
+    type Output = FlagSet;
+
+    fn bitor(self, rhs: Flags) -> FlagSet {
+        FlagSet::from(self).union(rhs.into())
+    }
+}
+
+impl BitOr<Flags> for FlagSet {
+    type Output = FlagSet;
+
+    fn bitor(self, rhs: Flags) -> FlagSet {
+        self.union(rhs.into())
+    }
+}
+
+impl BitOr for FlagSet {
+    type Output = FlagSet;
+
+    fn bitor(self, rhs: FlagSet) -> FlagSet {
+        self.union(rhs)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -18,36 +113,134 @@ pub struct Header {
     pub version: u16,
     pub node_count: u32,
     pub edge_count: u32,
-    pub flags: u16,
+    pub flags: FlagSet,
+    /// CRC-32 of every byte after the header (version 3+).
+    pub checksum: Option<u32>,
 }
 
 impl Header {
-    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+    /// A current-version header. The checksum starts as a zero placeholder
+    /// for the encoder to fill in once the body is written.
+    pub fn new(node_count: u32, edge_count: u32, flags: impl Into<FlagSet>) -> Self {
+        Header {
+            magic: MAGIC,
+            version: VERSION,
+            node_count,
+            edge_count,
+            flags: flags.into(),
+            checksum: Some(0),
+        }
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, DecodeError> {
         if bytes.len() < HEADER_SIZE {
-            return Err(format!("Header is too short: {} bytes", bytes.len()));
+            return Err(DecodeError::TooShort { len: bytes.len() });
         }
 
         let magic = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
         if magic != MAGIC {
-            return Err(format!("Invalid magic number: 0x{:08X}", magic));
+            return Err(DecodeError::BadMagic { found: magic });
         }
 
         let version = u16::from_le_bytes([bytes[4], bytes[5]]);
-        if version != VERSION {
-            return Err(format!("Unsupported version: {}", version));
+        if !(MIN_VERSION..=VERSION).contains(&version) {
+            return Err(DecodeError::UnsupportedVersion { found: version });
         }
 
+        let checksum = if version >= 3 {
+            if bytes.len() < HEADER_SIZE + CHECKSUM_SIZE {
+                return Err(DecodeError::TooShort { len: bytes.len() });
+            }
+            Some(u32::from_le_bytes([
+                bytes[16], bytes[17], bytes[18], bytes[19],
+            ]))
+        } else {
+            None
+        };
+
         Ok(Header {
             magic,
             version,
             node_count: u32::from_le_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]),
             edge_count: u32::from_le_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]),
-            flags: u16::from_le_bytes([bytes[14], bytes[15]]),
+            flags: FlagSet::from_bits(u16::from_le_bytes([bytes[14], bytes[15]])),
+            checksum,
         })
     }
 
+    /// The fixed fields in their little-endian wire layout. A version 3+
+    /// checksum is written separately, straight after these bytes.
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0; HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&self.magic.to_le_bytes());
+        bytes[4..6].copy_from_slice(&self.version.to_le_bytes());
+        bytes[6..10].copy_from_slice(&self.node_count.to_le_bytes());
+        bytes[10..14].copy_from_slice(&self.edge_count.to_le_bytes());
+        bytes[14..16].copy_from_slice(&self.flags.bits().to_le_bytes());
+        bytes
+    }
+
+    /// Encoded size of this header, i.e. the offset where the body starts.
+    pub fn size(&self) -> usize {
+        if self.version >= 3 {
+            HEADER_SIZE + CHECKSUM_SIZE
+        } else {
+            HEADER_SIZE
+        }
+    }
+
+    /// Fewest body bytes that can hold the arrays this header declares,
+    /// counting only fixed-width fields (a label table adds its offsets
+    /// but no string bytes). Computed in `u64` so hostile counts can't
+    /// overflow, even on 32-bit wasm.
+    pub fn min_body_size(&self) -> u64 {
+        let mut per_node = 4 + 4 + 2; // id, pagerank, degree
+        let mut fixed = 0;
+        if self.has_flag(Flags::HasLabels) {
+            per_node += 4;
+            fixed += 4;
+        }
+        if self.has_communities() {
+            per_node += 4;
+        }
+        if self.has_positions() {
+            per_node += 8;
+        }
+        let per_edge = if self.has_flag(Flags::HasWeights) {
+            12
+        } else {
+            8
+        };
+        fixed + self.node_count as u64 * per_node + self.edge_count as u64 * per_edge
+    }
+
+    /// Rejects counts that can't fit in a `body_len`-byte body, before
+    /// anything is allocated for them.
+    pub fn check_counts(&self, body_len: usize) -> Result<(), DecodeError> {
+        if self.min_body_size() > body_len as u64 {
+            return Err(DecodeError::ImplausibleCounts {
+                node_count: self.node_count,
+                edge_count: self.edge_count,
+                body_len,
+            });
+        }
+        Ok(())
+    }
+
     pub fn has_flag(&self, flag: Flags) -> bool {
-        (self.flags & flag as u16) != 0
+        self.flags.contains(flag)
+    }
+
+    /// Whether the body carries node positions. Version 1 predates the
+    /// flag, so the bit is ignored there.
+    pub fn has_positions(&self) -> bool {
+        self.version >= 2 && self.has_flag(Flags::HasPositions)
+    }
+
+    /// Whether the body carries community ids. Like positions, version 1
+    /// ignores the bit.
+    pub fn has_communities(&self) -> bool {
+        self.version >= 2 && self.has_flag(Flags::HasCommunities)
     }
 }
 
@@ -56,12 +249,13 @@ mod tests {
     use super::*;
 
     fn build_header(node_count: u32, edge_count: u32, flags: u16) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(HEADER_SIZE);
+        let mut buf = Vec::with_capacity(HEADER_SIZE + CHECKSUM_SIZE);
         buf.extend_from_slice(&MAGIC.to_le_bytes());
         buf.extend_from_slice(&VERSION.to_le_bytes());
         buf.extend_from_slice(&node_count.to_le_bytes());
         buf.extend_from_slice(&edge_count.to_le_bytes());
         buf.extend_from_slice(&flags.to_le_bytes());
+        buf.extend_from_slice(&0xCAFEF00Du32.to_le_bytes());
         buf
     }
 
@@ -73,13 +267,22 @@ mod tests {
         assert_eq!(h.version, VERSION);
         assert_eq!(h.node_count, 100);
         assert_eq!(h.edge_count, 50);
-        assert_eq!(h.flags, Flags::HasLabels as u16);
+        assert_eq!(h.flags, FlagSet::from(Flags::HasLabels));
+        assert_eq!(h.checksum, Some(0xCAFEF00D));
+        assert_eq!(h.size(), HEADER_SIZE + CHECKSUM_SIZE);
+    }
+
+    #[test]
+    fn parse_v3_without_checksum_is_too_short() {
+        let data = build_header(0, 0, 0);
+        let err = Header::parse(&data[..HEADER_SIZE]).unwrap_err();
+        assert_eq!(err, DecodeError::TooShort { len: HEADER_SIZE });
     }
 
     #[test]
     fn parse_too_short() {
         let err = Header::parse(&[0u8; 10]).unwrap_err();
-        assert!(err.contains("too short"), "got: {err}");
+        assert_eq!(err, DecodeError::TooShort { len: 10 });
     }
 
     #[test]
@@ -87,7 +290,7 @@ mod tests {
         let mut data = build_header(0, 0, 0);
         data[0..4].copy_from_slice(&0xDEADBEEFu32.to_le_bytes());
         let err = Header::parse(&data).unwrap_err();
-        assert!(err.contains("Invalid magic"), "got: {err}");
+        assert_eq!(err, DecodeError::BadMagic { found: 0xDEADBEEF });
     }
 
     #[test]
@@ -95,7 +298,34 @@ mod tests {
         let mut data = build_header(0, 0, 0);
         data[4..6].copy_from_slice(&99u16.to_le_bytes());
         let err = Header::parse(&data).unwrap_err();
-        assert!(err.contains("Unsupported version"), "got: {err}");
+        assert_eq!(err, DecodeError::UnsupportedVersion { found: 99 });
+    }
+
+    #[test]
+    fn parse_accepts_version_one() {
+        let mut data = build_header(3, 2, 0);
+        data[4..6].copy_from_slice(&1u16.to_le_bytes());
+        let h = Header::parse(&data[..HEADER_SIZE]).unwrap();
+        assert_eq!(h.version, 1);
+        assert_eq!(h.node_count, 3);
+        assert_eq!(h.checksum, None);
+        assert_eq!(h.size(), HEADER_SIZE);
+    }
+
+    #[test]
+    fn parse_rejects_version_zero() {
+        let mut data = build_header(0, 0, 0);
+        data[4..6].copy_from_slice(&0u16.to_le_bytes());
+        let err = Header::parse(&data).unwrap_err();
+        assert_eq!(err, DecodeError::UnsupportedVersion { found: 0 });
+    }
+
+    #[test]
+    fn positions_flag_ignored_before_v2() {
+        let mut data = build_header(0, 0, Flags::HasPositions as u16);
+        assert!(Header::parse(&data).unwrap().has_positions());
+        data[4..6].copy_from_slice(&1u16.to_le_bytes());
+        assert!(!Header::parse(&data).unwrap().has_positions());
     }
 
     #[test]
@@ -127,4 +357,74 @@ mod tests {
     fn magic_constant() {
         assert_eq!(MAGIC, 0x424C4F4D);
     }
+
+    #[test]
+    fn min_body_size_counts_optional_arrays() {
+        let plain = Header::parse(&build_header(3, 2, 0)).unwrap();
+        assert_eq!(plain.min_body_size(), 3 * 10 + 2 * 8);
+        assert!(plain.check_counts(46).is_ok());
+        assert_eq!(
+            plain.check_counts(45),
+            Err(DecodeError::ImplausibleCounts {
+                node_count: 3,
+                edge_count: 2,
+                body_len: 45,
+            })
+        );
+
+        let flags = (Flags::HasLabels | Flags::HasWeights | Flags::HasPositions).bits();
+        let full = Header::parse(&build_header(3, 2, flags)).unwrap();
+        assert_eq!(full.min_body_size(), 4 + 3 * 22 + 2 * 12);
+        let communities = Header::parse(&build_header(3, 2, Flags::HasCommunities as u16)).unwrap();
+        assert!(communities.has_communities());
+        assert_eq!(communities.min_body_size(), 3 * 14 + 2 * 8);
+
+        // No overflow at the extremes
+        let huge = Header::parse(&build_header(u32::MAX, u32::MAX, flags)).unwrap();
+        assert_eq!(huge.min_body_size(), 4 + u32::MAX as u64 * 34);
+    }
+
+    #[test]
+    fn new_header_round_trips() {
+        let flags = Flags::HasLabels | Flags::HasWeights;
+        let mut h = Header::new(7, 12, flags);
+        h.checksum = Some(0xCAFEF00D);
+        let mut bytes = h.to_bytes().to_vec();
+        assert_eq!(bytes, build_header(7, 12, flags.bits())[..HEADER_SIZE]);
+        bytes.extend_from_slice(&h.checksum.unwrap().to_le_bytes());
+
+        let parsed = Header::parse(&bytes).unwrap();
+        assert_eq!(parsed.magic, MAGIC);
+        assert_eq!(parsed.version, VERSION);
+        assert_eq!(parsed.node_count, 7);
+        assert_eq!(parsed.edge_count, 12);
+        assert_eq!(parsed.flags, flags);
+        assert_eq!(parsed.checksum, Some(0xCAFEF00D));
+        assert_eq!(parsed.size(), h.size());
+    }
+
+    #[test]
+    fn flag_set_combines_and_keeps_wire_bits() {
+        let mut set = Flags::Compressed | Flags::HasWeights;
+        assert!(set.contains(Flags::Compressed));
+        assert!(set.contains(Flags::HasWeights));
+        assert!(!set.contains(Flags::HasLabels));
+        assert!(!set.contains(Flags::HasPositions));
+
+        set.insert(Flags::HasPositions);
+        assert_eq!(set.bits(), 0b1101);
+        assert_eq!(set.union(Flags::HasLabels.into()).bits(), 0b1111);
+        assert_eq!((FlagSet::empty() | Flags::HasLabels).bits(), 0b0010);
+        // `Flags::None` is the empty set
+        assert!(!FlagSet::empty().contains(Flags::None));
+
+        // Header bits parse to the same set, unknown bits included
+        let h = Header::parse(&build_header(0, 0, 0x8000 | set.bits())).unwrap();
+        for flag in [Flags::Compressed, Flags::HasWeights, Flags::HasPositions] {
+            assert!(h.has_flag(flag));
+        }
+        assert!(!h.has_flag(Flags::HasLabels));
+        assert_eq!(u16::from(h.flags), 0x800D);
+        assert_eq!(h.to_bytes()[14..16], 0x800Du16.to_le_bytes());
+    }
 }
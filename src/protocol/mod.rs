@@ -1,5 +1,10 @@
+pub mod checksum;
 pub mod decode;
+pub mod encode;
+pub mod error;
 pub mod format;
 
 pub use decode::Decoder;
-pub use format::{Header, MAGIC, VERSION};
+pub use encode::Encoder;
+pub use error::DecodeError;
+pub use format::{FlagSet, Flags, Header, MAGIC, MIN_VERSION, VERSION};
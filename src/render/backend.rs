@@ -1 +1,102 @@
+use crate::render::Frame;
+use std::collections::BTreeMap;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
 
+const EDGE_RGB: &str = "150, 150, 150";
+const ARROW_COLOR: &str = "rgba(150, 150, 150, 0.8)";
+const LABEL_COLOR: &str = "#222";
+const LABEL_FONT: &str = "12px sans-serif";
+
+/// Canvas2D fallback renderer, the bottom GPU-less tier. Draws a `Frame`
+/// with one path per primitive type and colour: edges are stroked together
+/// per (quantised) opacity and width, then all arrowheads filled, then the
+/// nodes of each fill colour are filled together. Labels are drawn last,
+/// one `fillText` each.
+pub struct Canvas2d {
+    canvas: HtmlCanvasElement,
+    ctx: CanvasRenderingContext2d,
+}
+
+impl Canvas2d {
+    pub fn new(canvas: HtmlCanvasElement) -> Result<Self, String> {
+        let ctx = canvas
+            .get_context("2d")
+            .map_err(|e| format!("getContext('2d') failed: {e:?}"))?
+            .ok_or("canvas has no 2d context")?
+            .dyn_into::<CanvasRenderingContext2d>()
+            .map_err(|_| "2d context is not a CanvasRenderingContext2d".to_string())?;
+        Ok(Self { canvas, ctx })
+    }
+
+    pub fn draw(&self, frame: &Frame) -> Result<(), String> {
+        let ctx = &self.ctx;
+        let (width, height) = (self.canvas.width() as f64, self.canvas.height() as f64);
+        ctx.clear_rect(0.0, 0.0, width, height);
+
+        // Weighted edges vary continuously; bucketing to 1/100 opacity and
+        // quarter pixels keeps the stroke count small
+        let mut by_stroke = BTreeMap::<(u8, u16), Vec<_>>::new();
+        for line in &frame.edges {
+            let key = (
+                (line.alpha.clamp(0.0, 1.0) * 100.0).round() as u8,
+                (line.width.max(0.0) * 4.0).round() as u16,
+            );
+            by_stroke.entry(key).or_default().push(line);
+        }
+        for ((alpha, width), lines) in by_stroke {
+            ctx.begin_path();
+            for line in lines {
+                ctx.move_to(line.x1, line.y1);
+                match line.control {
+                    Some((cx, cy)) => ctx.quadratic_curve_to(cx, cy, line.x2, line.y2),
+                    None => ctx.line_to(line.x2, line.y2),
+                }
+            }
+            let alpha = alpha as f64 / 100.0;
+            ctx.set_stroke_style_str(&format!("rgba({EDGE_RGB}, {alpha})"));
+            ctx.set_line_width(width as f64 / 4.0);
+            ctx.stroke();
+        }
+
+        if frame.edges.iter().any(|line| line.arrow.is_some()) {
+            ctx.begin_path();
+            for arrow in frame.edges.iter().filter_map(|line| line.arrow) {
+                ctx.move_to(arrow.tip.0, arrow.tip.1);
+                ctx.line_to(arrow.left.0, arrow.left.1);
+                ctx.line_to(arrow.right.0, arrow.right.1);
+                ctx.close_path();
+            }
+            ctx.set_fill_style_str(ARROW_COLOR);
+            ctx.fill();
+        }
+
+        let mut by_color = BTreeMap::<[u8; 4], Vec<_>>::new();
+        for node in &frame.nodes {
+            by_color.entry(node.color).or_default().push(node);
+        }
+        for ([r, g, b, a], nodes) in by_color {
+            ctx.begin_path();
+            for node in nodes {
+                // Start each circle on its rim so arcs aren't joined
+                ctx.move_to(node.x + node.radius, node.y);
+                ctx.arc(node.x, node.y, node.radius, 0.0, std::f64::consts::TAU)
+                    .map_err(|e| format!("arc failed: {e:?}"))?;
+            }
+            let alpha = a as f64 / 255.0;
+            ctx.set_fill_style_str(&format!("rgba({r}, {g}, {b}, {alpha})"));
+            ctx.fill();
+        }
+
+        if !frame.labels.is_empty() {
+            ctx.set_font(LABEL_FONT);
+            ctx.set_text_baseline("middle");
+            ctx.set_fill_style_str(LABEL_COLOR);
+            for label in &frame.labels {
+                ctx.fill_text(&label.text, label.x, label.y)
+                    .map_err(|e| format!("fillText failed: {e:?}"))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,12 @@
+use crate::spatial::AABB;
+
+/// Zoom limits for `zoom_at`, so a runaway wheel can't flip or blow up
+/// the transform.
+pub const MIN_ZOOM: f32 = 0.01;
+pub const MAX_ZOOM: f32 = 100.0;
+
+/// Maps world space to the canvas: `(x, y)` is the world point shown at
+/// the canvas centre and `zoom` is screen pixels per world unit.
 pub struct Camera {
     pub x: f32,
     pub y: f32,
@@ -38,6 +47,60 @@ impl Camera {
         self.target_zoom = zoom;
     }
 
+    /// Shifts the view by a screen-space drag of `(dx, dy)` pixels, so
+    /// content follows the pointer. Takes effect immediately and cancels
+    /// any `focus_on` animation in flight.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.x -= dx / self.zoom;
+        self.y -= dy / self.zoom;
+        self.snap();
+    }
+
+    /// Multiplies the zoom by `factor` (clamped to `MIN_ZOOM..=MAX_ZOOM`)
+    /// while keeping the world point under `(screen_x, screen_y)` fixed on
+    /// screen. Immediate, like `pan`.
+    pub fn zoom_at(
+        &mut self,
+        screen_x: f64,
+        screen_y: f64,
+        factor: f32,
+        canvas_w: f64,
+        canvas_h: f64,
+    ) {
+        if !(factor.is_finite() && factor > 0.0) {
+            return;
+        }
+        let (wx, wy) = self.screen_to_world(screen_x, screen_y, canvas_w, canvas_h);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.x = wx - (screen_x as f32 - canvas_w as f32 / 2.0) / self.zoom;
+        self.y = wy - (screen_y as f32 - canvas_h as f32 / 2.0) / self.zoom;
+        self.snap();
+    }
+
+    /// Back to the origin at zoom 1, immediately.
+    pub fn reset_view(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Makes the current view the target so `update` holds it.
+    fn snap(&mut self) {
+        self.target_x = self.x;
+        self.target_y = self.y;
+        self.target_zoom = self.zoom;
+    }
+
+    /// The world-space rectangle visible on a `canvas_w x canvas_h` canvas.
+    pub fn visible_bounds(&self, canvas_w: f64, canvas_h: f64) -> AABB {
+        let (min_x, min_y) = self.screen_to_world(0.0, 0.0, canvas_w, canvas_h);
+        let (max_x, max_y) = self.screen_to_world(canvas_w, canvas_h, canvas_w, canvas_h);
+        AABB {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
     pub fn world_to_screen(&self, wx: f32, wy: f32, canvas_w: f64, canvas_h: f64) -> (f64, f64) {
         let sx = ((wx - self.x) * self.zoom + canvas_w as f32 / 2.0) as f64;
         let sy = ((wy - self.y) * self.zoom + canvas_h as f32 / 2.0) as f64;
@@ -118,6 +181,80 @@ mod tests {
         assert!((c.zoom - 2.0).abs() < 1e-2);
     }
 
+    #[test]
+    fn roundtrip_after_pan_and_zoom() {
+        let mut c = Camera::new();
+        c.pan(37.0, -12.5);
+        c.zoom_at(120.0, 80.0, 3.5, W, H);
+        c.pan(-4.0, 9.0);
+        for (x, y) in [(0.0, 0.0), (30.0, 40.0), (-250.0, 1200.0)] {
+            let (sx, sy) = c.world_to_screen(x, y, W, H);
+            let (wx, wy) = c.screen_to_world(sx, sy, W, H);
+            assert!((wx - x).abs() < 1e-3 && (wy - y).abs() < 1e-3, "{x},{y}");
+        }
+    }
+
+    #[test]
+    fn pan_moves_content_with_pointer() {
+        let mut c = Camera::new();
+        c.zoom = 2.0;
+        let (sx, sy) = c.world_to_screen(10.0, 10.0, W, H);
+        c.pan(30.0, -20.0);
+        let (sx2, sy2) = c.world_to_screen(10.0, 10.0, W, H);
+        assert!((sx2 - sx - 30.0).abs() < 1e-3 && (sy2 - sy + 20.0).abs() < 1e-3);
+
+        // update() holds the panned view instead of easing back
+        c.update(1.0);
+        let (sx3, _) = c.world_to_screen(10.0, 10.0, W, H);
+        assert!((sx3 - sx2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn zoom_at_keeps_cursor_point_fixed() {
+        let mut c = Camera::new();
+        c.pan(50.0, 25.0);
+        let (cx, cy) = (650.0, 120.0);
+        let before = c.screen_to_world(cx, cy, W, H);
+        c.zoom_at(cx, cy, 1.8, W, H);
+        assert!((c.zoom - 1.8).abs() < 1e-6);
+        let after = c.screen_to_world(cx, cy, W, H);
+        assert!((before.0 - after.0).abs() < 1e-3 && (before.1 - after.1).abs() < 1e-3);
+
+        // Clamped, and degenerate factors are ignored
+        c.zoom_at(cx, cy, 1e9, W, H);
+        assert_eq!(c.zoom, MAX_ZOOM);
+        c.zoom_at(cx, cy, 0.0, W, H);
+        c.zoom_at(cx, cy, f32::NAN, W, H);
+        assert_eq!(c.zoom, MAX_ZOOM);
+    }
+
+    #[test]
+    fn reset_view_restores_defaults() {
+        let mut c = Camera::new();
+        c.pan(10.0, 10.0);
+        c.zoom_at(0.0, 0.0, 4.0, W, H);
+        c.reset_view();
+        assert_eq!((c.x, c.y, c.zoom), (0.0, 0.0, 1.0));
+        c.update(1.0);
+        assert_eq!((c.x, c.y, c.zoom), (0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn visible_bounds_track_view() {
+        let mut c = Camera::new();
+        let b = c.visible_bounds(W, H);
+        assert_eq!(
+            (b.min_x, b.min_y, b.max_x, b.max_y),
+            (-400.0, -300.0, 400.0, 300.0)
+        );
+
+        c.x = 100.0;
+        c.zoom = 4.0;
+        let b = c.visible_bounds(W, H);
+        assert_eq!((b.min_x, b.max_x), (0.0, 200.0));
+        assert_eq!((b.min_y, b.max_y), (-75.0, 75.0));
+    }
+
     #[test]
     fn zoom_affects_world_to_screen() {
         let mut c = Camera::new();
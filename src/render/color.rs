@@ -0,0 +1,177 @@
+/// Viridis sampled at nine evenly spaced stops, dark purple to yellow.
+/// Perceptually uniform, so equal steps in value read as equal steps in
+/// colour, and it survives greyscale and common colour blindness.
+const VIRIDIS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 45, 123],
+    [59, 82, 139],
+    [44, 114, 142],
+    [33, 145, 140],
+    [40, 174, 128],
+    [94, 201, 98],
+    [173, 220, 48],
+    [253, 231, 37],
+];
+
+/// Tableau 10. Neighbouring entries differ in hue, so consecutive
+/// community ids never share a colour.
+const CATEGORICAL: [[u8; 3]; 10] = [
+    [78, 121, 167],
+    [242, 142, 43],
+    [225, 87, 89],
+    [118, 183, 178],
+    [89, 161, 79],
+    [237, 201, 72],
+    [176, 122, 161],
+    [255, 157, 167],
+    [156, 117, 95],
+    [186, 176, 172],
+];
+
+/// Colour for categories outside a categorical scale's range.
+const UNCATEGORIZED: [u8; 4] = [150, 150, 150, 255];
+
+/// Hue step for categories past the fixed palette: the golden angle keeps
+/// successive hues as far apart as possible however many there are.
+const GOLDEN_ANGLE: f32 = 137.507_77;
+
+/// Maps node data to RGBA colours.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorScale {
+    /// Continuous values in `min..=max` along the viridis gradient.
+    Sequential { min: f32, max: f32 },
+    /// Category ids `0..count`, each with its own colour.
+    Categorical { count: usize },
+}
+
+impl ColorScale {
+    pub fn sequential(min: f32, max: f32) -> Self {
+        Self::Sequential { min, max }
+    }
+
+    pub fn categorical(count: usize) -> Self {
+        Self::Categorical { count }
+    }
+
+    /// Opaque RGBA for `value`. Sequential scales clamp to their range (a
+    /// degenerate range or NaN maps to the first colour). Categorical
+    /// scales truncate `value` to an id; ids past the fixed palette get
+    /// golden-angle hues, and ids outside `0..count` are grey.
+    pub fn color_for(&self, value: f32) -> [u8; 4] {
+        match *self {
+            Self::Sequential { min, max } => {
+                let t = if max > min {
+                    ((value - min) / (max - min)).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                // NaN survives the clamp; treat it as the low end
+                gradient(if t.is_nan() { 0.0 } else { t })
+            }
+            Self::Categorical { count } => {
+                if !(value >= 0.0 && (value as usize) < count) {
+                    return UNCATEGORIZED;
+                }
+                category(value as usize)
+            }
+        }
+    }
+}
+
+/// Which node property drives node colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeColoring {
+    #[default]
+    PageRank,
+    Community,
+}
+
+fn gradient(t: f32) -> [u8; 4] {
+    let pos = t * (VIRIDIS.len() - 1) as f32;
+    let i = (pos as usize).min(VIRIDIS.len() - 2);
+    let frac = pos - i as f32;
+    let (a, b) = (VIRIDIS[i], VIRIDIS[i + 1]);
+    let lerp = |c: usize| (a[c] as f32 + (b[c] as f32 - a[c] as f32) * frac).round() as u8;
+    [lerp(0), lerp(1), lerp(2), 255]
+}
+
+fn category(id: usize) -> [u8; 4] {
+    if let Some(&[r, g, b]) = CATEGORICAL.get(id) {
+        return [r, g, b, 255];
+    }
+    let hue = (id as f32 * GOLDEN_ANGLE) % 360.0;
+    let [r, g, b] = hsl_to_rgb(hue, 0.55, 0.55);
+    [r, g, b, 255]
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [u8; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = hue / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |c: f32| ((c + m) * 255.0).round() as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba([r, g, b]: [u8; 3]) -> [u8; 4] {
+        [r, g, b, 255]
+    }
+
+    #[test]
+    fn sequential_endpoints_match_gradient() {
+        let scale = ColorScale::sequential(0.1, 0.5);
+        assert_eq!(scale.color_for(0.1), rgba(VIRIDIS[0]));
+        assert_eq!(scale.color_for(0.5), rgba(VIRIDIS[VIRIDIS.len() - 1]));
+        // Out of range clamps to the ends
+        assert_eq!(scale.color_for(-3.0), rgba(VIRIDIS[0]));
+        assert_eq!(scale.color_for(9.0), rgba(VIRIDIS[VIRIDIS.len() - 1]));
+        // The midpoint lands on the middle stop
+        assert_eq!(scale.color_for(0.3), rgba(VIRIDIS[4]));
+    }
+
+    #[test]
+    fn sequential_degenerate_inputs() {
+        assert_eq!(
+            ColorScale::sequential(1.0, 1.0).color_for(1.0),
+            rgba(VIRIDIS[0])
+        );
+        assert_eq!(
+            ColorScale::sequential(0.0, 1.0).color_for(f32::NAN),
+            rgba(VIRIDIS[0])
+        );
+    }
+
+    #[test]
+    fn categorical_adjacent_ids_are_distinct() {
+        let scale = ColorScale::categorical(40);
+        for id in 0..39 {
+            assert_ne!(
+                scale.color_for(id as f32),
+                scale.color_for((id + 1) as f32),
+                "ids {id} and {}",
+                id + 1
+            );
+        }
+        assert_eq!(scale.color_for(0.0), rgba(CATEGORICAL[0]));
+    }
+
+    #[test]
+    fn categorical_out_of_range_is_grey() {
+        let scale = ColorScale::categorical(3);
+        assert_eq!(scale.color_for(3.0), UNCATEGORIZED);
+        assert_eq!(scale.color_for(-1.0), UNCATEGORIZED);
+        assert_ne!(scale.color_for(2.0), UNCATEGORIZED);
+    }
+}
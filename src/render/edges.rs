@@ -1 +1,517 @@
+use crate::graph::{Edge, Graph};
+use crate::render::camera::Camera;
+use crate::render::highlight::{DIMMED_OPACITY, Highlight};
+use crate::render::nodes::NodeStyle;
 
+/// Arrowhead length in screen pixels; the base is half as wide as this.
+pub const ARROW_LENGTH: f64 = 8.0;
+/// Stroke opacity and width for edges when weight isn't mapped, or the
+/// graph's weights are all equal.
+pub const DEFAULT_EDGE_ALPHA: f64 = 0.4;
+pub const DEFAULT_EDGE_WIDTH: f64 = 1.0;
+/// How far a curved edge's control point sits from the chord midpoint, as
+/// a fraction of the chord length.
+const CURVE_BEND: f64 = 0.15;
+/// Chords a curved edge is split into for hit-testing.
+const CURVE_PICK_SEGMENTS: usize = 16;
+
+/// Edge drawing options. `arrows` marks the target end of each edge and
+/// only applies to directed graphs; `curved` bows every edge to the left
+/// of its direction, so `a -> b` and `b -> a` trace separate arcs.
+/// `weights`, when set, maps each edge's weight onto its opacity and width.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EdgeStyle {
+    pub curved: bool,
+    pub arrows: bool,
+    pub weights: Option<EdgeWeightStyle>,
+}
+
+/// Maps edge weight linearly from `min_weight..=max_weight` (clamped) onto
+/// the `alpha` and `width` ranges. Give a range equal ends to leave that
+/// channel fixed. When the weight range is empty, as for an unweighted
+/// graph, every edge gets the defaults.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeWeightStyle {
+    pub min_weight: f32,
+    pub max_weight: f32,
+    pub alpha: (f64, f64),
+    pub width: (f64, f64),
+}
+
+impl Default for EdgeWeightStyle {
+    fn default() -> Self {
+        Self {
+            min_weight: 0.0,
+            max_weight: 1.0,
+            alpha: (0.15, 0.9),
+            width: (0.5, 3.0),
+        }
+    }
+}
+
+impl EdgeWeightStyle {
+    /// `alpha` and `width` ranges over the weight range of `graph`'s edges.
+    pub fn fitted(graph: &Graph, alpha: (f64, f64), width: (f64, f64)) -> Self {
+        let (min_weight, max_weight) = graph
+            .edges()
+            .iter()
+            .map(|e| e.weight)
+            .filter(|w| w.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), w| {
+                (lo.min(w), hi.max(w))
+            });
+        Self {
+            min_weight,
+            max_weight,
+            alpha,
+            width,
+        }
+    }
+
+    pub fn alpha_for(&self, weight: f32) -> f64 {
+        self.fraction(weight)
+            .map_or(DEFAULT_EDGE_ALPHA, |t| lerp(self.alpha, t))
+    }
+
+    pub fn width_for(&self, weight: f32) -> f64 {
+        self.fraction(weight)
+            .map_or(DEFAULT_EDGE_WIDTH, |t| lerp(self.width, t))
+    }
+
+    /// Position of `weight` in the weight range, clamped to 0..=1; None
+    /// when the range is empty. NaN counts as the low end.
+    fn fraction(&self, weight: f32) -> Option<f64> {
+        // Also catches the inverted range `fitted` gives an edgeless graph
+        if self.max_weight.partial_cmp(&self.min_weight) != Some(std::cmp::Ordering::Greater) {
+            return None;
+        }
+        let t = (weight - self.min_weight) / (self.max_weight - self.min_weight);
+        Some(if t.is_nan() {
+            0.0
+        } else {
+            t.clamp(0.0, 1.0) as f64
+        })
+    }
+}
+
+fn lerp((lo, hi): (f64, f64), t: f64) -> f64 {
+    lo + (hi - lo) * t
+}
+
+/// A filled triangle at an edge's target end, in screen space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arrowhead {
+    pub tip: (f64, f64),
+    pub left: (f64, f64),
+    pub right: (f64, f64),
+}
+
+/// An edge as drawn: a screen-space segment between its endpoints, or a
+/// quadratic bezier through `control` when curved, plus an optional
+/// arrowhead, stroked at `alpha` opacity and `width` pixels. `source` and
+/// `target` are the ids of the edge as stored, for picking; the line may
+/// run either way between them on undirected graphs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeLine {
+    pub source: u32,
+    pub target: u32,
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+    pub control: Option<(f64, f64)>,
+    pub arrow: Option<Arrowhead>,
+    pub alpha: f64,
+    pub width: f64,
+}
+
+/// Screen-space edges with at least one endpoint in `visible` (sorted node
+/// indices). Walks the visible nodes' incident edges rather than the whole
+/// edge list, so the cost tracks what is on screen. Undirected edges between two
+/// visible nodes are emitted once, from the lower endpoint; directed edges
+/// keep their direction, so reciprocal pairs give two lines. Self-loops are
+/// skipped, as they would draw as a single point hidden under the node.
+/// Edges outside `highlight` are faded to `DIMMED_OPACITY` and lose their
+/// arrowheads, so only the hovered node's edges show direction.
+#[allow(clippy::too_many_arguments)]
+pub fn edge_lines(
+    graph: &Graph,
+    visible: &[usize],
+    style: &EdgeStyle,
+    node_style: &NodeStyle,
+    highlight: Option<&Highlight>,
+    camera: &Camera,
+    width: f64,
+    height: f64,
+) -> Vec<EdgeLine> {
+    let nodes = graph.nodes();
+    let edges = graph.edges();
+    let arrows = style.arrows && graph.is_directed();
+    let is_visible = |j: &usize| visible.binary_search(j).is_ok();
+    let mut lines = Vec::new();
+    let mut push = |i: usize, j: usize, edge: &Edge| {
+        let (x1, y1) = camera.world_to_screen(nodes[i].x, nodes[i].y, width, height);
+        let (x2, y2) = camera.world_to_screen(nodes[j].x, nodes[j].y, width, height);
+        let control = style.curved.then(|| curve_control((x1, y1), (x2, y2)));
+        let dimmed = highlight.is_some_and(|h| !h.contains_edge(i, j));
+        let arrow = (arrows && !dimmed)
+            .then(|| {
+                let radius = node_style.sizing.radius_for(&nodes[j], &node_style.domain);
+                let from = control.unwrap_or((x1, y1));
+                arrowhead(from, (x2, y2), (radius * camera.zoom) as f64, ARROW_LENGTH)
+            })
+            .flatten();
+        let (mut alpha, width) = style
+            .weights
+            .map_or((DEFAULT_EDGE_ALPHA, DEFAULT_EDGE_WIDTH), |w| {
+                (w.alpha_for(edge.weight), w.width_for(edge.weight))
+            });
+        if dimmed {
+            alpha *= DIMMED_OPACITY;
+        }
+        lines.push(EdgeLine {
+            source: edge.source,
+            target: edge.target,
+            x1,
+            y1,
+            x2,
+            y2,
+            control,
+            arrow,
+            alpha,
+            width,
+        });
+    };
+
+    for &i in visible {
+        let id = nodes[i].id;
+        for &k in graph.incident_edge_indices(i) {
+            let edge = &edges[k];
+            // The incident list only holds edges with both endpoints known
+            let other_id = if edge.source == id {
+                edge.target
+            } else {
+                edge.source
+            };
+            let Some(j) = graph.node_index(other_id).filter(|&j| j != i) else {
+                continue;
+            };
+            if graph.is_directed() {
+                // Incoming edges from off-screen sources have no other
+                // chance; ones from visible sources are drawn from there
+                if edge.source == id {
+                    push(i, j, edge);
+                } else if !is_visible(&j) {
+                    push(j, i, edge);
+                }
+            } else if j > i || !is_visible(&j) {
+                push(i, j, edge);
+            }
+        }
+    }
+    lines
+}
+
+/// Control point for a quadratic bezier from `from` to `to`: the chord
+/// midpoint pushed to the left of the direction of travel. Reversing the
+/// endpoints flips the side, which is what separates reciprocal edges.
+pub fn curve_control(from: (f64, f64), to: (f64, f64)) -> (f64, f64) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let mid = ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+    // (dy, -dx) is the left-hand normal in y-down screen space, scaled
+    // by the chord length already
+    (mid.0 + dy * CURVE_BEND, mid.1 - dx * CURVE_BEND)
+}
+
+/// Distance from `p` to the segment from `a` to `b`: to the nearest point
+/// on it, which is an endpoint when `p` projects beyond either end.
+pub fn point_segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (p.0 - (a.0 + t * dx)).hypot(p.1 - (a.1 + t * dy))
+}
+
+impl EdgeLine {
+    /// Screen distance from `(x, y)` to the stroked line. Curves are
+    /// flattened into 16 chords, which at drawn sizes stays well within a
+    /// pixel of the true bezier.
+    pub fn distance_to(&self, x: f64, y: f64) -> f64 {
+        let (from, to) = ((self.x1, self.y1), (self.x2, self.y2));
+        let Some(c) = self.control else {
+            return point_segment_distance((x, y), from, to);
+        };
+        let at = |k: usize| {
+            let t = k as f64 / CURVE_PICK_SEGMENTS as f64;
+            let u = 1.0 - t;
+            (
+                u * u * from.0 + 2.0 * u * t * c.0 + t * t * to.0,
+                u * u * from.1 + 2.0 * u * t * c.1 + t * t * to.1,
+            )
+        };
+        (0..CURVE_PICK_SEGMENTS)
+            .map(|k| point_segment_distance((x, y), at(k), at(k + 1)))
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+/// Arrowhead pointing from `from` towards `to`, with its tip pulled back by
+/// `target_radius` so it sits on the rim of the target circle instead of
+/// under it. `from` is the source for straight edges and the control point
+/// for curves, whose tangent at the end runs from there. None when the
+/// points are too close for the tip to clear the source side.
+pub fn arrowhead(
+    from: (f64, f64),
+    to: (f64, f64),
+    target_radius: f64,
+    length: f64,
+) -> Option<Arrowhead> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let dist = (dx * dx + dy * dy).sqrt();
+    if dist <= target_radius {
+        return None;
+    }
+    let (ux, uy) = (dx / dist, dy / dist);
+    let tip = (to.0 - ux * target_radius, to.1 - uy * target_radius);
+    let base = (tip.0 - ux * length, tip.1 - uy * length);
+    let half = length / 2.0;
+    Some(Arrowhead {
+        tip,
+        left: (base.0 + uy * half, base.1 - ux * half),
+        right: (base.0 - uy * half, base.1 + ux * half),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::GraphKind;
+    use crate::render::nodes::{NodeSizing, SizeDomain};
+    use crate::test_utils::{edge, node_at};
+
+    /// Edges on an 800x600 canvas with the default camera, so world
+    /// (0, 0) is screen (400, 300). Nodes are a fixed 5 units across.
+    fn draw(graph: &Graph, visible: &[usize], style: &EdgeStyle) -> Vec<EdgeLine> {
+        let node_style = NodeStyle {
+            sizing: NodeSizing::Fixed(5.0),
+            domain: SizeDomain::default(),
+            colors: &[],
+        };
+        edge_lines(
+            graph,
+            visible,
+            style,
+            &node_style,
+            None,
+            &Camera::new(),
+            800.0,
+            600.0,
+        )
+    }
+
+    #[test]
+    fn lines_connect_endpoints() {
+        let nodes = vec![node_at(1, 0.0, 0.0), node_at(2, 10.0, -5.0)];
+        // A dangling edge and a self-loop are dropped
+        let edges = vec![edge(1, 2), edge(2, 9), edge(1, 1)];
+        let graph = Graph::new(nodes, edges);
+        let lines = draw(&graph, &[0, 1], &EdgeStyle::default());
+        assert_eq!(
+            lines,
+            vec![EdgeLine {
+                source: 1,
+                target: 2,
+                x1: 400.0,
+                y1: 300.0,
+                x2: 410.0,
+                y2: 295.0,
+                control: None,
+                arrow: None,
+                alpha: DEFAULT_EDGE_ALPHA,
+                width: DEFAULT_EDGE_WIDTH,
+            }]
+        );
+    }
+
+    #[test]
+    fn offscreen_edges_are_culled() {
+        // Path 0-1-2-3; only nodes 1 and 2 are visible
+        let nodes = (0..4).map(|i| node_at(i, i as f32, 0.0)).collect();
+        let graph = Graph::new(nodes, vec![edge(0, 1), edge(1, 2), edge(2, 3)]);
+        let lines = draw(&graph, &[1, 2], &EdgeStyle::default());
+        // 0-1 and 2-3 have one visible endpoint; 1-2 appears once
+        assert_eq!(lines.len(), 3);
+
+        // Only node 0 visible: its edge stays, 1-2 and 2-3 are dropped
+        let lines = draw(&graph, &[0], &EdgeStyle::default());
+        assert_eq!(lines.len(), 1);
+        assert_eq!((lines[0].x1, lines[0].x2), (400.0, 401.0));
+        assert!(draw(&graph, &[], &EdgeStyle::default()).is_empty());
+    }
+
+    #[test]
+    fn arrowhead_geometry() {
+        // Pointing along +x at a node of radius 5 centred on (100, 0)
+        let arrow = arrowhead((0.0, 0.0), (100.0, 0.0), 5.0, 8.0).unwrap();
+        assert_eq!(arrow.tip, (95.0, 0.0));
+        assert_eq!(arrow.left, (87.0, -4.0));
+        assert_eq!(arrow.right, (87.0, 4.0));
+
+        // Pointing straight down: barbs swap to the x axis
+        let arrow = arrowhead((10.0, 0.0), (10.0, 50.0), 10.0, 4.0).unwrap();
+        assert_eq!(arrow.tip, (10.0, 40.0));
+        assert_eq!(arrow.left, (12.0, 36.0));
+        assert_eq!(arrow.right, (8.0, 36.0));
+
+        // Source inside the target circle: nothing to draw
+        assert!(arrowhead((0.0, 0.0), (3.0, 0.0), 5.0, 8.0).is_none());
+    }
+
+    #[test]
+    fn directed_edges_keep_direction() {
+        let nodes = vec![node_at(1, 0.0, 0.0), node_at(2, 100.0, 0.0)];
+        let edges = vec![edge(1, 2), edge(2, 1)];
+        let graph = Graph::with_kind(nodes, edges, GraphKind::Directed);
+        let style = EdgeStyle {
+            curved: true,
+            arrows: true,
+            weights: None,
+        };
+        let lines = draw(&graph, &[0, 1], &style);
+        assert_eq!(lines.len(), 2);
+        let (forward, back) = (lines[0], lines[1]);
+        assert_eq!((forward.x1, forward.x2), (400.0, 500.0));
+        assert_eq!((back.x1, back.x2), (500.0, 400.0));
+        // Reciprocal edges bow to opposite sides of the chord
+        let (fc, bc) = (forward.control.unwrap(), back.control.unwrap());
+        assert_eq!(fc.0, bc.0);
+        assert!(fc.1 < 300.0 && bc.1 > 300.0);
+        // The arrow tip lands on the target's rim, along the curve's
+        // final tangent
+        let tip = forward.arrow.unwrap().tip;
+        let rim = ((tip.0 - 500.0).powi(2) + (tip.1 - 300.0).powi(2)).sqrt();
+        assert!((rim - 5.0).abs() < 1e-9);
+        assert!(tip.1 < 300.0);
+
+        // Only the target visible: the edge in still arrives
+        let lines = draw(&graph, &[1], &EdgeStyle::default());
+        assert_eq!(lines.len(), 2);
+        assert!(
+            lines
+                .iter()
+                .all(|l| l.arrow.is_none() && l.control.is_none())
+        );
+    }
+
+    #[test]
+    fn undirected_graphs_get_no_arrows() {
+        let nodes = vec![node_at(1, 0.0, 0.0), node_at(2, 100.0, 0.0)];
+        let graph = Graph::new(nodes, vec![edge(1, 2)]);
+        let style = EdgeStyle {
+            curved: false,
+            arrows: true,
+            weights: None,
+        };
+        let lines = draw(&graph, &[0, 1], &style);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].arrow, None);
+    }
+
+    #[test]
+    fn weight_maps_to_alpha_and_width() {
+        let style = EdgeWeightStyle {
+            min_weight: 2.0,
+            max_weight: 4.0,
+            alpha: (0.2, 1.0),
+            width: (1.0, 5.0),
+        };
+        assert_eq!(style.alpha_for(2.0), 0.2);
+        assert!((style.alpha_for(3.0) - 0.6).abs() < 1e-12);
+        assert_eq!(style.alpha_for(4.0), 1.0);
+        assert_eq!(style.width_for(3.0), 3.0);
+        // Out of range clamps to the ends
+        assert_eq!(style.alpha_for(-10.0), 0.2);
+        assert_eq!(style.alpha_for(10.0), 1.0);
+        assert_eq!(style.width_for(f32::NAN), 1.0);
+
+        // Equal weights (an unweighted graph) fall back to the defaults
+        let flat = EdgeWeightStyle {
+            min_weight: 1.0,
+            max_weight: 1.0,
+            ..style
+        };
+        assert_eq!(flat.alpha_for(1.0), DEFAULT_EDGE_ALPHA);
+        assert_eq!(flat.width_for(1.0), DEFAULT_EDGE_WIDTH);
+    }
+
+    #[test]
+    fn weighted_edges_draw_with_mapped_style() {
+        let nodes = vec![
+            node_at(1, 0.0, 0.0),
+            node_at(2, 10.0, 0.0),
+            node_at(3, 20.0, 0.0),
+        ];
+        let mut heavy = edge(2, 3);
+        heavy.weight = 5.0;
+        let graph = Graph::new(nodes, vec![edge(1, 2), heavy]);
+        let weights = EdgeWeightStyle::fitted(&graph, (0.1, 0.9), (1.0, 1.0));
+        assert_eq!((weights.min_weight, weights.max_weight), (1.0, 5.0));
+
+        let style = EdgeStyle {
+            weights: Some(weights),
+            ..EdgeStyle::default()
+        };
+        let lines = draw(&graph, &[0, 1, 2], &style);
+        let styled: Vec<(f64, f64)> = lines.iter().map(|l| (l.alpha, l.width)).collect();
+        assert_eq!(styled, vec![(0.1, 1.0), (0.9, 1.0)]);
+    }
+
+    #[test]
+    fn point_segment_distance_geometry() {
+        let (a, b) = ((0.0, 0.0), (10.0, 0.0));
+        // Perpendicular onto the interior
+        assert_eq!(point_segment_distance((4.0, 3.0), a, b), 3.0);
+        assert_eq!(point_segment_distance((4.0, -3.0), a, b), 3.0);
+        assert_eq!(point_segment_distance((7.0, 0.0), a, b), 0.0);
+        // Past either end the nearest point is the endpoint: a 3-4-5
+        // triangle off each end
+        assert_eq!(point_segment_distance((-3.0, 4.0), a, b), 5.0);
+        assert_eq!(point_segment_distance((13.0, -4.0), a, b), 5.0);
+        // Diagonal segment
+        let d = point_segment_distance((0.0, 2.0), (0.0, 0.0), (2.0, 2.0));
+        assert!((d - 2f64.sqrt()).abs() < 1e-12);
+        // A degenerate segment is a point
+        assert_eq!(
+            point_segment_distance((3.0, 4.0), (0.0, 0.0), (0.0, 0.0)),
+            5.0
+        );
+    }
+
+    #[test]
+    fn curved_lines_measure_to_the_arc() {
+        let nodes = vec![node_at(1, 0.0, 0.0), node_at(2, 100.0, 0.0)];
+        let graph = Graph::new(nodes, vec![edge(1, 2)]);
+        let straight = draw(&graph, &[0, 1], &EdgeStyle::default())[0];
+        let curved = draw(
+            &graph,
+            &[0, 1],
+            &EdgeStyle {
+                curved: true,
+                ..EdgeStyle::default()
+            },
+        )[0];
+        // The chord midpoint is on the straight line but off the arc,
+        // whose apex sits halfway to the control point
+        let (c_x, c_y) = curved.control.unwrap();
+        assert_eq!(straight.distance_to(450.0, 300.0), 0.0);
+        let bow = (c_y - 300.0).abs() / 2.0;
+        assert!((curved.distance_to(450.0, 300.0) - bow).abs() < 0.1);
+        assert!(curved.distance_to(c_x, (300.0 + c_y) / 2.0) < 0.1);
+        // Endpoints are on both
+        assert!(curved.distance_to(400.0, 300.0) < 1e-9);
+    }
+}
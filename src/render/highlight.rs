@@ -0,0 +1,79 @@
+use crate::graph::Graph;
+
+/// Fraction of its normal opacity a node or edge outside the highlight
+/// keeps.
+pub const DIMMED_OPACITY: f64 = 0.15;
+
+/// A hovered node and its neighbours. While one is set, frames draw these
+/// nodes and the hovered node's edges as usual and fade everything else
+/// to `DIMMED_OPACITY`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    /// Index of the hovered node.
+    pub focus: usize,
+    /// Sorted node indices drawn at full opacity: `focus` and its
+    /// neighbours in either direction.
+    pub nodes: Vec<usize>,
+}
+
+impl Highlight {
+    /// The highlight around node `id`, read from the adjacency index in
+    /// O(degree log degree). None for an unknown id.
+    pub fn around(graph: &Graph, id: u32) -> Option<Self> {
+        let focus = graph.node_index(id)?;
+        let mut nodes = graph.neighbor_indices(focus).to_vec();
+        nodes.push(focus);
+        nodes.sort_unstable();
+        nodes.dedup();
+        Some(Self { focus, nodes })
+    }
+
+    pub fn contains_node(&self, index: usize) -> bool {
+        self.nodes.binary_search(&index).is_ok()
+    }
+
+    /// Whether the edge between node indices `i` and `j` stays bright:
+    /// only edges touching the hovered node do.
+    pub fn contains_edge(&self, i: usize, j: usize) -> bool {
+        i == self.focus || j == self.focus
+    }
+}
+
+/// `color` with its alpha scaled down to `DIMMED_OPACITY`.
+pub fn dim_color([r, g, b, a]: [u8; 4]) -> [u8; 4] {
+    [r, g, b, (a as f64 * DIMMED_OPACITY).round() as u8]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::{edge, node};
+
+    #[test]
+    fn highlight_is_node_plus_neighbors() {
+        // 1-2, 1-3, 3-4, and a parallel 1-2
+        let g = Graph::new(
+            (1..=4).map(node).collect(),
+            vec![edge(1, 2), edge(1, 3), edge(3, 4), edge(2, 1)],
+        );
+        let h = Highlight::around(&g, 1).unwrap();
+        assert_eq!(h.focus, 0);
+        assert_eq!(h.nodes, vec![0, 1, 2]);
+        assert!(h.contains_node(2));
+        assert!(!h.contains_node(3));
+        assert!(h.contains_edge(2, 0));
+        // Between two neighbours, but not the hovered node's edge
+        assert!(!h.contains_edge(1, 2));
+
+        let leaf = Highlight::around(&g, 4).unwrap();
+        assert_eq!(leaf.nodes, vec![2, 3]);
+        assert_eq!(Highlight::around(&g, 99), None);
+    }
+
+    #[test]
+    fn dimming_scales_alpha_only() {
+        assert_eq!(dim_color([10, 20, 30, 255]), [10, 20, 30, 38]);
+        assert_eq!(dim_color([10, 20, 30, 0]), [10, 20, 30, 0]);
+    }
+}
@@ -1,5 +1,164 @@
 mod backend;
 pub mod camera;
-mod edges;
-mod nodes;
-mod text;
+pub mod color;
+pub mod edges;
+pub mod highlight;
+pub mod nodes;
+pub mod text;
+
+pub use backend::Canvas2d;
+pub use color::{ColorScale, NodeColoring};
+pub use edges::{Arrowhead, EdgeLine, EdgeStyle, EdgeWeightStyle, edge_lines};
+pub use highlight::Highlight;
+pub use nodes::{NodeCircle, NodeSizing, NodeStyle, SizeDomain, node_circles};
+pub use text::{Label, labels};
+
+use crate::graph::{Graph, Quadtree};
+use camera::Camera;
+
+/// Everything one frame draws, in screen space. Edges are listed (and
+/// drawn) before nodes so nodes sit on top, and labels go over both.
+#[derive(Debug, Default)]
+pub struct Frame {
+    pub edges: Vec<EdgeLine>,
+    pub nodes: Vec<NodeCircle>,
+    pub labels: Vec<Label>,
+}
+
+/// Drawing options for a whole frame. `label_threshold` is the on-screen
+/// node radius, in pixels, below which labels are left out. A
+/// `highlight` dims everything outside it.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameStyle<'a> {
+    pub nodes: NodeStyle<'a>,
+    pub edges: EdgeStyle,
+    pub label_threshold: f64,
+    pub highlight: Option<&'a Highlight>,
+}
+
+impl Default for FrameStyle<'_> {
+    fn default() -> Self {
+        Self {
+            nodes: NodeStyle::default(),
+            edges: EdgeStyle::default(),
+            label_threshold: text::DEFAULT_LABEL_THRESHOLD,
+            highlight: None,
+        }
+    }
+}
+
+impl Frame {
+    /// Projects the on-screen part of the graph through the camera onto a
+    /// `width x height` canvas. `quadtree` must index the graph's current
+    /// positions; it picks the visible nodes, and edges are kept when at
+    /// least one endpoint is visible. Labels are picked from the visible
+    /// nodes by on-screen size and PageRank.
+    pub fn build(
+        graph: &Graph,
+        quadtree: &Quadtree,
+        style: &FrameStyle,
+        camera: &Camera,
+        width: f64,
+        height: f64,
+    ) -> Self {
+        let margin = style.nodes.sizing.max_radius();
+        let visible = visible_nodes(quadtree, camera, margin, width, height);
+        let mut nodes = node_circles(graph, &visible, &style.nodes, camera, width, height);
+        if let Some(highlight) = style.highlight {
+            for (&i, circle) in visible.iter().zip(&mut nodes) {
+                if !highlight.contains_node(i) {
+                    circle.color = highlight::dim_color(circle.color);
+                }
+            }
+        }
+        Self {
+            edges: edge_lines(
+                graph,
+                &visible,
+                &style.edges,
+                &style.nodes,
+                style.highlight,
+                camera,
+                width,
+                height,
+            ),
+            labels: labels(graph, &visible, &nodes, style.label_threshold),
+            nodes,
+        }
+    }
+}
+
+/// Sorted indices of nodes that would draw inside the viewport. The view
+/// rectangle is grown by `margin`, the largest world-space node radius, so
+/// circles straddling the canvas edge are kept.
+pub fn visible_nodes(
+    quadtree: &Quadtree,
+    camera: &Camera,
+    margin: f32,
+    width: f64,
+    height: f64,
+) -> Vec<usize> {
+    let mut view = camera.visible_bounds(width, height);
+    view.min_x -= margin;
+    view.min_y -= margin;
+    view.max_x += margin;
+    view.max_y += margin;
+    let mut visible = quadtree.query_rect(&view);
+    visible.sort_unstable();
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, Node};
+    use crate::test_utils::node_at;
+
+    #[test]
+    fn tight_viewport_draws_only_visible_subset() {
+        // A 10x10 grid, 100 world units apart
+        let nodes: Vec<Node> = (0..100)
+            .map(|i| node_at(i, (i % 10) as f32 * 100.0, (i / 10) as f32 * 100.0))
+            .collect();
+        let edges = (0..99)
+            .filter(|i| i % 10 != 9)
+            .map(|i| Edge {
+                source: i,
+                target: i + 1,
+                weight: 1.0,
+            })
+            .collect();
+        let graph = Graph::new(nodes, edges);
+        let quadtree = Quadtree::from_nodes(graph.nodes(), 4);
+
+        // 100x100 canvas centred between nodes 44, 45, 54 and 55
+        let mut camera = Camera::new();
+        camera.x = 450.0;
+        camera.y = 450.0;
+        assert_eq!(
+            visible_nodes(&quadtree, &camera, nodes::MAX_NODE_RADIUS, 100.0, 100.0),
+            vec![44, 45, 54, 55]
+        );
+
+        let build = |camera: &Camera| {
+            Frame::build(
+                &graph,
+                &quadtree,
+                &FrameStyle::default(),
+                camera,
+                100.0,
+                100.0,
+            )
+        };
+        let frame = build(&camera);
+        assert_eq!(frame.nodes.len(), 4);
+        // 44-45 and 54-55, plus 43-44, 45-46, 53-54, 55-56 leaving the view
+        assert_eq!(frame.edges.len(), 6);
+
+        // Zoomed out over everything
+        camera.zoom = 0.01;
+        let frame = build(&camera);
+        assert_eq!(frame.nodes.len(), 100);
+        assert_eq!(frame.edges.len(), 90);
+    }
+}
@@ -1 +1,328 @@
+use crate::graph::{Graph, Node};
+use crate::render::camera::Camera;
 
+/// World-space radius of the lowest-ranked node.
+pub const MIN_NODE_RADIUS: f32 = 3.0;
+/// World-space radius of the highest-ranked node.
+pub const MAX_NODE_RADIUS: f32 = 12.0;
+
+/// Fill for nodes with no colour assigned.
+pub const DEFAULT_NODE_COLOR: [u8; 4] = [74, 144, 217, 255];
+
+/// A node as drawn: screen-space centre, radius and RGBA fill.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeCircle {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    pub color: [u8; 4],
+}
+
+/// How node radius is chosen. Radii are world-space, so they scale with
+/// zoom like positions do. With `log` set, values go through `ln(1 + v)`
+/// before being mapped, which compresses a few dominant hubs so the rest
+/// of the graph stays visible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeSizing {
+    /// Every node the same size.
+    Fixed(f32),
+    /// Degree mapped from `0..=max_degree` onto `min..=max`.
+    ByDegree { min: f32, max: f32, log: bool },
+    /// PageRank mapped from `0..=max_pagerank` onto `min..=max`. On the log
+    /// scale ranks are first counted in uniform shares (`1 / n`), as raw
+    /// ranks are so small that `ln(1 + v)` would barely bend them.
+    ByPageRank { min: f32, max: f32, log: bool },
+}
+
+impl Default for NodeSizing {
+    fn default() -> Self {
+        Self::ByPageRank {
+            min: MIN_NODE_RADIUS,
+            max: MAX_NODE_RADIUS,
+            log: false,
+        }
+    }
+}
+
+impl NodeSizing {
+    /// World-space radius for `node`, normalised against the graph's
+    /// largest value in `domain`. A graph whose values are all zero draws
+    /// every node at `min`.
+    pub fn radius_for(&self, node: &Node, domain: &SizeDomain) -> f32 {
+        let (min, max, log, value, top) = match *self {
+            Self::Fixed(radius) => return radius,
+            Self::ByDegree { min, max, log } => {
+                (min, max, log, node.degree as f32, domain.max_degree as f32)
+            }
+            Self::ByPageRank { min, max, log } => {
+                let share = domain.node_count.max(1) as f32;
+                (
+                    min,
+                    max,
+                    log,
+                    node.pagerank * share,
+                    domain.max_pagerank * share,
+                )
+            }
+        };
+        let (value, top) = if log {
+            (log_scale(value), log_scale(top))
+        } else {
+            (value, top)
+        };
+        if top <= 0.0 {
+            return min;
+        }
+        min + (max - min) * (value / top).clamp(0.0, 1.0)
+    }
+
+    /// Largest radius any node can get, for growing query regions so
+    /// circles centred just outside them are still found.
+    pub fn max_radius(&self) -> f32 {
+        match *self {
+            Self::Fixed(radius) => radius,
+            Self::ByDegree { min, max, .. } | Self::ByPageRank { min, max, .. } => min.max(max),
+        }
+    }
+}
+
+/// `ln(1 + v)`, with zero, negative and NaN values all mapping to 0.
+fn log_scale(v: f32) -> f32 {
+    if v > 0.0 { v.ln_1p() } else { 0.0 }
+}
+
+/// The per-graph maxima `NodeSizing` normalises against, plus the node
+/// count that sets the log scale's PageRank unit. Cached by the engine and
+/// refreshed when the graph or its ranks change.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SizeDomain {
+    pub max_pagerank: f32,
+    pub max_degree: u16,
+    pub node_count: usize,
+}
+
+impl SizeDomain {
+    pub fn from_graph(graph: &Graph) -> Self {
+        Self {
+            max_pagerank: max_pagerank(graph),
+            max_degree: graph.nodes().iter().map(|n| n.degree).max().unwrap_or(0),
+            node_count: graph.node_count(),
+        }
+    }
+}
+
+/// How nodes are drawn: size and per-node fill. `colors` is index-aligned
+/// with `graph.nodes()`; nodes past its end use `DEFAULT_NODE_COLOR`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeStyle<'a> {
+    pub sizing: NodeSizing,
+    pub domain: SizeDomain,
+    pub colors: &'a [[u8; 4]],
+}
+
+/// Highest PageRank in the graph, or 0 when it has no nodes.
+pub fn max_pagerank(graph: &Graph) -> f32 {
+    graph.nodes().iter().map(|n| n.pagerank).fold(0.0, f32::max)
+}
+
+/// Screen-space circles for the nodes at `indices`, in that order.
+pub fn node_circles(
+    graph: &Graph,
+    indices: &[usize],
+    style: &NodeStyle,
+    camera: &Camera,
+    width: f64,
+    height: f64,
+) -> Vec<NodeCircle> {
+    let nodes = graph.nodes();
+    indices
+        .iter()
+        .filter_map(|&i| Some((nodes.get(i)?, i)))
+        .map(|(node, i)| {
+            let (x, y) = camera.world_to_screen(node.x, node.y, width, height);
+            let radius = (style.sizing.radius_for(node, &style.domain) * camera.zoom) as f64;
+            let color = style.colors.get(i).copied().unwrap_or(DEFAULT_NODE_COLOR);
+            NodeCircle {
+                x,
+                y,
+                radius,
+                color,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_node(id: u32, pagerank: f32) -> Node {
+        Node {
+            id,
+            pagerank,
+            x: id as f32 * 10.0,
+            ..Node::default()
+        }
+    }
+
+    #[test]
+    fn radius_scales_with_pagerank() {
+        let sizing = NodeSizing::default();
+        let domain = SizeDomain {
+            max_pagerank: 0.4,
+            max_degree: 0,
+            node_count: 5,
+        };
+        let radius = |rank| sizing.radius_for(&make_node(0, rank), &domain);
+        assert_eq!(radius(0.0), MIN_NODE_RADIUS);
+        assert_eq!(radius(0.4), MAX_NODE_RADIUS);
+        assert!(radius(0.3) > radius(0.1));
+        assert_eq!(radius(0.2), (MIN_NODE_RADIUS + MAX_NODE_RADIUS) / 2.0);
+        // No ranks at all: everything at the minimum, no NaN
+        let flat = SizeDomain::default();
+        assert_eq!(
+            sizing.radius_for(&make_node(0, 0.0), &flat),
+            MIN_NODE_RADIUS
+        );
+    }
+
+    #[test]
+    fn sizing_modes() {
+        let mut node = make_node(0, 0.1);
+        node.degree = 3;
+        let domain = SizeDomain {
+            max_pagerank: 0.2,
+            max_degree: 6,
+            node_count: 10,
+        };
+        assert_eq!(NodeSizing::Fixed(5.0).radius_for(&node, &domain), 5.0);
+        let by_degree = NodeSizing::ByDegree {
+            min: 2.0,
+            max: 10.0,
+            log: false,
+        };
+        assert_eq!(by_degree.radius_for(&node, &domain), 6.0);
+        assert_eq!(by_degree.max_radius(), 10.0);
+        let by_rank = NodeSizing::ByPageRank {
+            min: 1.0,
+            max: 3.0,
+            log: false,
+        };
+        assert_eq!(by_rank.radius_for(&node, &domain), 2.0);
+        assert_eq!(
+            SizeDomain::from_graph(&Graph::new(vec![node], vec![])),
+            SizeDomain {
+                max_pagerank: 0.1,
+                max_degree: 3,
+                node_count: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn log_scale_narrows_hub_ratio() {
+        // One hub holding half the rank and linked to everything; the
+        // other 49 nodes share the rest and have degree 1 or 2
+        let mut nodes: Vec<Node> = (0..50)
+            .map(|i| {
+                let mut node = make_node(i, 0.5 / 49.0);
+                node.degree = 1 + (i % 2) as u16;
+                node
+            })
+            .collect();
+        nodes[0].pagerank = 0.5;
+        nodes[0].degree = 49;
+        let graph = Graph::new(nodes, vec![]);
+        let domain = SizeDomain::from_graph(&graph);
+        let median = &graph.nodes()[25];
+        let hub = &graph.nodes()[0];
+
+        let pairs = [
+            (
+                NodeSizing::ByPageRank {
+                    min: 3.0,
+                    max: 12.0,
+                    log: false,
+                },
+                NodeSizing::ByPageRank {
+                    min: 3.0,
+                    max: 12.0,
+                    log: true,
+                },
+            ),
+            (
+                NodeSizing::ByDegree {
+                    min: 2.0,
+                    max: 20.0,
+                    log: false,
+                },
+                NodeSizing::ByDegree {
+                    min: 2.0,
+                    max: 20.0,
+                    log: true,
+                },
+            ),
+        ];
+        for (linear, log) in pairs {
+            let ratio = |s: NodeSizing| s.radius_for(hub, &domain) / s.radius_for(median, &domain);
+            assert!(
+                ratio(log) < ratio(linear),
+                "{log:?}: {} vs {}",
+                ratio(log),
+                ratio(linear)
+            );
+            // The hub is still drawn at the top of the range
+            assert_eq!(log.radius_for(hub, &domain), log.max_radius());
+        }
+    }
+
+    #[test]
+    fn log_scale_guards_non_positive_values() {
+        assert_eq!(log_scale(0.0), 0.0);
+        assert_eq!(log_scale(-3.0), 0.0);
+        assert_eq!(log_scale(f32::NAN), 0.0);
+        assert!((log_scale(std::f32::consts::E - 1.0) - 1.0).abs() < 1e-6);
+
+        let sizing = NodeSizing::ByPageRank {
+            min: 3.0,
+            max: 12.0,
+            log: true,
+        };
+        let domain = SizeDomain {
+            max_pagerank: 0.5,
+            max_degree: 0,
+            node_count: 10,
+        };
+        assert_eq!(sizing.radius_for(&make_node(0, -0.2), &domain), 3.0);
+        assert_eq!(sizing.radius_for(&make_node(0, 0.0), &domain), 3.0);
+        // All-zero ranks still size everything at the minimum
+        let flat = SizeDomain::default();
+        assert_eq!(sizing.radius_for(&make_node(0, 0.0), &flat), 3.0);
+    }
+
+    #[test]
+    fn circles_follow_camera() {
+        let graph = Graph::new(vec![make_node(0, 0.1), make_node(1, 0.3)], vec![]);
+        let mut camera = Camera::new();
+        camera.zoom = 2.0;
+        let red = [255, 0, 0, 255];
+        let style = NodeStyle {
+            domain: SizeDomain::from_graph(&graph),
+            colors: &[red],
+            ..NodeStyle::default()
+        };
+        let circles = node_circles(&graph, &[0, 1], &style, &camera, 800.0, 600.0);
+
+        assert_eq!(circles.len(), 2);
+        assert_eq!((circles[0].x, circles[0].y), (400.0, 300.0));
+        assert_eq!((circles[1].x, circles[1].y), (420.0, 300.0));
+        assert_eq!(circles[1].radius, (MAX_NODE_RADIUS * 2.0) as f64);
+        assert!(circles[0].radius < circles[1].radius);
+        assert_eq!(circles[0].color, red);
+        assert_eq!(circles[1].color, DEFAULT_NODE_COLOR);
+
+        // Only the requested nodes, in the requested order
+        let subset = node_circles(&graph, &[1], &style, &camera, 800.0, 600.0);
+        assert_eq!(subset, vec![circles[1]]);
+    }
+}
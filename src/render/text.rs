@@ -1 +1,129 @@
+use crate::graph::Graph;
+use crate::render::nodes::NodeCircle;
 
+/// Default on-screen radius, in pixels, a node needs before its label is
+/// drawn.
+pub const DEFAULT_LABEL_THRESHOLD: f64 = 8.0;
+/// Most labels drawn in one frame. Past this the canvas is unreadable and
+/// text layout starts to dominate frame time.
+pub const MAX_LABELS: usize = 200;
+
+/// A node label as drawn: text anchored at its left-middle point, just
+/// right of the node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub x: f64,
+    pub y: f64,
+    pub text: String,
+}
+
+/// Whether a labelled node gets its label drawn. `rank` is the node's
+/// position (0 = highest PageRank) among the visible labelled nodes that
+/// clear the threshold; only the top `MAX_LABELS` of those are drawn.
+pub fn should_draw_label(screen_radius: f64, rank: usize, threshold: f64) -> bool {
+    screen_radius >= threshold && rank < MAX_LABELS
+}
+
+/// Labels for the nodes at `indices`, whose circles (in the same order)
+/// are `circles`. Nodes with empty labels are skipped; the rest compete
+/// by PageRank for the `MAX_LABELS` slots once big enough on screen.
+pub fn labels(
+    graph: &Graph,
+    indices: &[usize],
+    circles: &[NodeCircle],
+    threshold: f64,
+) -> Vec<Label> {
+    let nodes = graph.nodes();
+    let mut candidates: Vec<(usize, &NodeCircle)> = indices
+        .iter()
+        .zip(circles)
+        .filter(|&(&i, circle)| !nodes[i].label.is_empty() && circle.radius >= threshold)
+        .map(|(&i, circle)| (i, circle))
+        .collect();
+    candidates.sort_by(|a, b| nodes[b.0].pagerank.total_cmp(&nodes[a.0].pagerank));
+
+    candidates
+        .into_iter()
+        .enumerate()
+        .take_while(|&(rank, (_, circle))| should_draw_label(circle.radius, rank, threshold))
+        .map(|(_, (i, circle))| Label {
+            x: circle.x + circle.radius + 2.0,
+            y: circle.y,
+            text: nodes[i].label.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+
+    fn make_node(id: u32, label: &str, pagerank: f32) -> Node {
+        Node {
+            id,
+            label: label.to_string(),
+            pagerank,
+            ..Node::default()
+        }
+    }
+
+    fn circle(radius: f64) -> NodeCircle {
+        NodeCircle {
+            x: 100.0,
+            y: 50.0,
+            radius,
+            color: [0; 4],
+        }
+    }
+
+    #[test]
+    fn label_decision() {
+        assert!(should_draw_label(10.0, 0, 8.0));
+        assert!(should_draw_label(8.0, 0, 8.0));
+        // Too small on screen, whatever its rank
+        assert!(!should_draw_label(7.9, 0, 8.0));
+        // Big enough but crowded out by higher-ranked nodes
+        assert!(should_draw_label(10.0, MAX_LABELS - 1, 8.0));
+        assert!(!should_draw_label(10.0, MAX_LABELS, 8.0));
+        // A zero threshold labels everything up to the cap
+        assert!(should_draw_label(0.0, 0, 0.0));
+    }
+
+    #[test]
+    fn labels_skip_small_and_empty() {
+        let graph = Graph::new(
+            vec![
+                make_node(1, "small", 0.9),
+                make_node(2, "", 0.8),
+                make_node(3, "big", 0.1),
+            ],
+            vec![],
+        );
+        let circles = [circle(4.0), circle(12.0), circle(12.0)];
+        let drawn = labels(&graph, &[0, 1, 2], &circles, 8.0);
+        assert_eq!(
+            drawn,
+            vec![Label {
+                x: 114.0,
+                y: 50.0,
+                text: "big".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn labels_prefer_high_pagerank() {
+        let nodes = (0..MAX_LABELS as u32 + 10)
+            .map(|i| make_node(i, &format!("n{i}"), i as f32))
+            .collect();
+        let graph = Graph::new(nodes, vec![]);
+        let indices: Vec<usize> = (0..graph.node_count()).collect();
+        let circles = vec![circle(10.0); indices.len()];
+        let drawn = labels(&graph, &indices, &circles, 8.0);
+        assert_eq!(drawn.len(), MAX_LABELS);
+        assert_eq!(drawn[0].text, format!("n{}", MAX_LABELS + 9));
+        // The ten lowest-ranked lose out
+        assert!(drawn.iter().all(|l| l.text != "n9"));
+    }
+}
@@ -9,16 +9,61 @@ pub struct AABB {
 }
 
 impl AABB {
+    /// Inverted sentinel that contains nothing: expanding it by a point
+    /// gives that point's box, and it is the identity for `union`. Its
+    /// extent and centre are meaningless until it has been expanded.
+    pub fn empty() -> Self {
+        AABB {
+            min_x: f32::INFINITY,
+            min_y: f32::INFINITY,
+            max_x: f32::NEG_INFINITY,
+            max_y: f32::NEG_INFINITY,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_x > self.max_x || self.min_y > self.max_y
+    }
+
+    pub fn expand_to_include(&mut self, x: f32, y: f32) {
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+    }
+
+    /// Smallest box covering both.
+    pub fn union(&self, other: &AABB) -> AABB {
+        AABB {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
     pub fn contains(&self, x: f32, y: f32) -> bool {
         x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
     }
 
+    /// True if the boxes overlap or touch.
+    pub fn intersects(&self, other: &AABB) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+
     pub fn intersects_circle(&self, cx: f32, cy: f32, radius: f32) -> bool {
-        let nearest_x = cx.clamp(self.min_x, self.max_x);
-        let nearest_y = cy.clamp(self.min_y, self.max_y);
-        let dx = cx - nearest_x;
-        let dy = cy - nearest_y;
-        dx * dx + dy * dy <= radius * radius
+        self.distance_squared(cx, cy) <= radius * radius
+    }
+
+    /// Squared distance from `(x, y)` to the nearest point of the box;
+    /// zero when the point is inside.
+    pub fn distance_squared(&self, x: f32, y: f32) -> f32 {
+        let dx = x - x.clamp(self.min_x, self.max_x);
+        let dy = y - y.clamp(self.min_y, self.max_y);
+        dx * dx + dy * dy
     }
 
     pub fn width(&self) -> f32 {
@@ -29,6 +74,11 @@ impl AABB {
         self.max_y - self.min_y
     }
 
+    /// `(width, height)`.
+    pub fn dimensions(&self) -> (f32, f32) {
+        (self.width(), self.height())
+    }
+
     pub fn center(&self) -> (f32, f32) {
         (
             (self.min_x + self.max_x) / 2.0,
@@ -98,6 +148,40 @@ mod tests {
         assert!(!b.intersects_circle(115.0, 50.0, 10.0));
     }
 
+    #[test]
+    fn intersects() {
+        let b = world_bounds();
+        let overlapping = AABB {
+            min_x: 90.0,
+            min_y: -10.0,
+            max_x: 120.0,
+            max_y: 10.0,
+        };
+        let touching = AABB {
+            min_x: 100.0,
+            min_y: 100.0,
+            max_x: 110.0,
+            max_y: 110.0,
+        };
+        let apart = AABB {
+            min_x: 101.0,
+            min_y: 0.0,
+            max_x: 110.0,
+            max_y: 10.0,
+        };
+        assert!(b.intersects(&overlapping) && overlapping.intersects(&b));
+        assert!(b.intersects(&touching));
+        assert!(!b.intersects(&apart));
+    }
+
+    #[test]
+    fn distance_squared() {
+        let b = world_bounds();
+        assert_eq!(b.distance_squared(50.0, 50.0), 0.0);
+        assert_eq!(b.distance_squared(103.0, 104.0), 25.0);
+        assert_eq!(b.distance_squared(-2.0, 50.0), 4.0);
+    }
+
     #[test]
     fn subdivide_produces_four_quadrants() {
         let b = world_bounds();
@@ -109,4 +193,48 @@ mod tests {
             assert!((q.height() - 50.0).abs() < f32::EPSILON);
         }
     }
+
+    #[test]
+    fn expand_empty_with_points() {
+        let mut b = AABB::empty();
+        assert!(b.is_empty());
+        assert!(!b.contains(0.0, 0.0));
+
+        b.expand_to_include(3.0, -2.0);
+        assert!(!b.is_empty());
+        assert_eq!(b.dimensions(), (0.0, 0.0));
+        assert_eq!(b.center(), (3.0, -2.0));
+
+        for &(x, y) in &[(-1.0, 4.0), (7.0, 0.0), (2.0, 2.0)] {
+            b.expand_to_include(x, y);
+        }
+        assert_eq!((b.min_x, b.min_y, b.max_x, b.max_y), (-1.0, -2.0, 7.0, 4.0));
+        assert_eq!(b.dimensions(), (8.0, 6.0));
+        assert_eq!(b.center(), (3.0, 1.0));
+    }
+
+    #[test]
+    fn union_of_overlapping_boxes() {
+        let a = world_bounds();
+        let b = AABB {
+            min_x: 50.0,
+            min_y: -20.0,
+            max_x: 130.0,
+            max_y: 60.0,
+        };
+        let u = a.union(&b);
+        assert_eq!(
+            (u.min_x, u.min_y, u.max_x, u.max_y),
+            (0.0, -20.0, 130.0, 100.0)
+        );
+        let v = b.union(&a);
+        assert_eq!(
+            (v.min_x, v.min_y, v.max_x, v.max_y),
+            (0.0, -20.0, 130.0, 100.0)
+        );
+        // The empty box is the identity
+        let same = a.union(&AABB::empty());
+        assert_eq!(same.dimensions(), a.dimensions());
+        assert!(AABB::empty().union(&AABB::empty()).is_empty());
+    }
 }
@@ -1,4 +1,8 @@
-use crate::protocol::format::{Flags, HEADER_SIZE, MAGIC, VERSION};
+use crate::graph::{Edge, Node};
+use crate::protocol::format::{Flags, HEADER_SIZE, MAGIC};
+
+/// Fixtures use the version 2 layout: fixed 16-byte header, no checksum.
+const FIXTURE_VERSION: u16 = 2;
 
 pub fn build_blom(
     nodes: &[(u32, f32, u16)],
@@ -14,7 +18,7 @@ pub fn build_blom(
 
     // Header
     buf.extend_from_slice(&MAGIC.to_le_bytes());
-    buf.extend_from_slice(&VERSION.to_le_bytes());
+    buf.extend_from_slice(&FIXTURE_VERSION.to_le_bytes());
     buf.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
     buf.extend_from_slice(&(edges.len() as u32).to_le_bytes());
     buf.extend_from_slice(&flags.to_le_bytes());
@@ -55,3 +59,29 @@ pub fn build_blom(
 
     buf
 }
+
+/// Node `id` with every other field at its default.
+pub fn node(id: u32) -> Node {
+    Node {
+        id,
+        ..Node::default()
+    }
+}
+
+/// Node `id` at `(x, y)`.
+pub fn node_at(id: u32, x: f32, y: f32) -> Node {
+    Node { x, y, ..node(id) }
+}
+
+/// Unit-weight edge.
+pub fn edge(source: u32, target: u32) -> Edge {
+    weighted_edge(source, target, 1.0)
+}
+
+pub fn weighted_edge(source: u32, target: u32, weight: f32) -> Edge {
+    Edge {
+        source,
+        target,
+        weight,
+    }
+}
@@ -0,0 +1,23 @@
+//! Fixtures shared by the integration tests. Each test binary uses a
+//! different subset.
+#![allow(dead_code)]
+
+use bloom::graph::{Edge, Node};
+
+/// Node `id` labelled `label`, every other field at its default.
+pub fn node(id: u32, label: &str) -> Node {
+    Node {
+        id,
+        label: label.to_string(),
+        ..Node::default()
+    }
+}
+
+/// Unit-weight edge.
+pub fn edge(source: u32, target: u32) -> Edge {
+    Edge {
+        source,
+        target,
+        weight: 1.0,
+    }
+}
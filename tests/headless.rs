@@ -0,0 +1,48 @@
+//! Native end-to-end check of the canvas-free path: decode a file, lay it
+//! out, bake the positions in, and read them back.
+
+mod common;
+
+use bloom::graph::Graph;
+use bloom::layout::{ForceDirectedLayout, LayoutAlgorithm, Simulation};
+use bloom::protocol::{Decoder, Flags};
+use common::{edge, node};
+
+#[test]
+fn layout_positions_survive_encode_decode() {
+    let source = Graph::new(
+        vec![
+            node(1, "a"),
+            node(2, "b"),
+            node(3, "c"),
+            node(4, "d"),
+            node(5, "e"),
+        ],
+        vec![edge(1, 2), edge(2, 3), edge(3, 1), edge(3, 4), edge(4, 5)],
+    );
+    // A file as it arrives from the pipeline: labels, no positions
+    let file = source.to_bloom_bytes(Flags::HasLabels);
+    let graph = Decoder::new(&file).decode_graph().unwrap();
+    assert!(graph.nodes().iter().all(|n| n.x == 0.0 && n.y == 0.0));
+
+    let n = graph.node_count();
+    let mut sim = Simulation::new(
+        graph,
+        LayoutAlgorithm::ForceDirected(ForceDirectedLayout::new(200.0, 200.0)),
+    );
+    sim.seed(42);
+    assert!(sim.run(10_000));
+
+    let baked = sim.to_bloom_bytes(Flags::HasLabels);
+    let reloaded = Decoder::new(&baked).decode_graph().unwrap();
+    let laid_out = sim.into_graph();
+    assert_eq!(reloaded.node_count(), n);
+    assert_eq!(reloaded.edges(), laid_out.edges());
+    for (got, want) in reloaded.nodes().iter().zip(laid_out.nodes()) {
+        assert_eq!(got.id, want.id);
+        assert_eq!(got.label, want.label);
+        assert_eq!((got.x, got.y), (want.x, want.y));
+    }
+    // The layout actually spread the nodes out
+    assert!(reloaded.nodes().iter().any(|n| n.x != 0.0 || n.y != 0.0));
+}
@@ -0,0 +1,116 @@
+//! Browser tests for the `#[wasm_bindgen]` API. Run with
+//! `wasm-pack test --headless --chrome`.
+#![cfg(target_arch = "wasm32")]
+
+use bloom::BloomEngine;
+mod common;
+
+use bloom::graph::{Graph, Node};
+use bloom::protocol::{Encoder, Flags};
+use common::{edge, node};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn canvas() -> web_sys::HtmlCanvasElement {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .expect("test runs in a browser");
+    document
+        .create_element("canvas")
+        .expect("create canvas")
+        .dyn_into()
+        .expect("element is a canvas")
+}
+
+fn fixture() -> Vec<u8> {
+    let nodes = (1..=4)
+        .map(|id| Node {
+            pagerank: 0.25,
+            degree: 1,
+            ..node(id, &format!("n{id}"))
+        })
+        .collect();
+    let edges = vec![edge(1, 2), edge(3, 4)];
+    Encoder::new().encode_graph(&Graph::new(nodes, edges), Flags::HasLabels as u16)
+}
+
+#[wasm_bindgen_test]
+fn load_graph_reports_counts() {
+    let mut engine = BloomEngine::new(canvas()).unwrap();
+    assert_eq!(engine.node_count(), 0);
+    engine.load_graph(&fixture()).unwrap();
+    assert_eq!(engine.node_count(), 4);
+    assert_eq!(engine.edge_count(), 2);
+}
+
+#[wasm_bindgen_test]
+fn load_graph_rejects_garbage() {
+    let mut engine = BloomEngine::new(canvas()).unwrap();
+    let err = engine.load_graph(&[0u8; 3]).unwrap_err();
+    assert!(err.as_string().is_some_and(|m| !m.is_empty()));
+    assert_eq!(engine.node_count(), 0);
+}
+
+#[wasm_bindgen_test]
+fn render_before_and_after_load() {
+    let mut engine = BloomEngine::new(canvas()).unwrap();
+    // An empty engine renders a blank canvas
+    engine.render().unwrap();
+    engine.load_graph(&fixture()).unwrap();
+    engine.render().unwrap();
+}
+
+#[wasm_bindgen_test]
+fn tick_runs_layout_until_settled() {
+    let mut engine = BloomEngine::new(canvas()).unwrap();
+    assert!(engine.tick(1.0), "nothing loaded counts as settled");
+    engine.load_graph(&fixture()).unwrap();
+    let settled = (0..1000).any(|_| engine.tick(4.0));
+    assert!(settled);
+}
+
+#[wasm_bindgen_test]
+fn compute_algorithms_return_arrays() {
+    let mut engine = BloomEngine::new(canvas()).unwrap();
+    assert!(engine.compute_pagerank(20, 0.85).is_empty());
+    engine.load_graph(&fixture()).unwrap();
+
+    let scores = engine.compute_pagerank(20, 0.85);
+    assert_eq!(scores.len(), 4);
+    assert!((scores.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+
+    // Two disjoint edges make two communities
+    let communities = engine.compute_communities();
+    assert_eq!(communities.len(), 4);
+    assert_eq!(communities[0], communities[1]);
+    assert_ne!(communities[0], communities[2]);
+
+    assert_eq!(engine.compute_shortest_path(1, 2), Some(vec![1, 2]));
+    assert_eq!(engine.compute_shortest_path(1, 3), None);
+}
+
+#[wasm_bindgen_test]
+fn stats_summarise_loaded_graph() {
+    let mut engine = BloomEngine::new(canvas()).unwrap();
+    assert!(engine.stats().is_none());
+    engine.load_graph(&fixture()).unwrap();
+    let stats = engine.stats().unwrap();
+    assert_eq!(stats.node_count, 4);
+    assert_eq!(stats.component_count, 2);
+    assert!(!stats.is_connected);
+}
+
+#[wasm_bindgen_test]
+fn positions_export_as_flat_array() {
+    let mut engine = BloomEngine::new(canvas()).unwrap();
+    assert!(engine.positions().is_empty());
+    engine.load_graph(&fixture()).unwrap();
+    engine.tick(10.0);
+
+    let positions = engine.positions();
+    assert_eq!(positions.len(), 2 * engine.node_count());
+    assert!(positions.iter().all(|p| p.is_finite()));
+    assert_eq!(engine.node_ids(), vec![1, 2, 3, 4]);
+}